@@ -7,7 +7,7 @@ use std::time::Duration;
 
 use criterion::{criterion_main, Criterion, Throughput};
 
-use ip::{Ipv4, Ipv6, Prefix, PrefixRange};
+use ip::{traits::Prefix as _, Ipv4, Ipv6, Prefix, PrefixRange};
 
 use itertools::Itertools;
 
@@ -32,7 +32,10 @@ macro_rules! benchmarks {
                 // construct_by_copy(&mut c);
                 iterate_prefix_ranges(&mut c);
                 iterate_prefixes(&mut c);
+                collect_prefixes(&mut c);
                 compute_intersection(&mut c);
+                #[cfg(feature = "rayon")]
+                compute_par_intersection(&mut c);
                 compute_union(&mut c);
                 compute_difference(&mut c);
             }
@@ -95,6 +98,20 @@ macro_rules! benchmarks {
                 g.finish()
             }
 
+            fn collect_prefixes(c: &mut Criterion) {
+                let mut g = c.benchmark_group("prefix collection");
+                g.measurement_time(Duration::from_secs(10));
+
+                for ds in DATA_SETS {
+                    let set: PrefixSet<_> = ds.read().into_iter().collect();
+                    g.throughput(Throughput::Elements(ds.prefixes() as u64));
+                    g.bench_function(ds.name(), |b| {
+                        b.iter(|| assert_eq!(set.prefixes().collect::<Vec<_>>().len(), ds.prefixes()))
+                    });
+                }
+                g.finish()
+            }
+
             fn compute_intersection(c: &mut Criterion) {
                 let mut g = c.benchmark_group("intersection computation");
                 g.measurement_time(Duration::from_secs(30));
@@ -111,6 +128,28 @@ macro_rules! benchmarks {
                 g.finish()
             }
 
+            #[cfg(feature = "rayon")]
+            fn compute_par_intersection(c: &mut Criterion) {
+                let mut g = c.benchmark_group("parallel intersection computation");
+                g.measurement_time(Duration::from_secs(30));
+                g.sample_size(20);
+
+                DATA_SETS.into_iter()
+                    .tuple_combinations()
+                    .for_each(|(x, y)| {
+                        let name = format!("{} & {}", x.name(), y.name());
+                        let s: PrefixSet<_> = x.read().into_iter().collect();
+                        let t: PrefixSet<_> = y.read().into_iter().collect();
+                        g.bench_function(format!("{} (serial)", name), |b| {
+                            b.iter(|| s.intersection(&t))
+                        });
+                        g.bench_function(format!("{} (parallel)", name), |b| {
+                            b.iter(|| s.par_intersection(&t))
+                        });
+                    });
+                g.finish()
+            }
+
             fn compute_union(c: &mut Criterion) {
                 let mut g = c.benchmark_group("union computation");
                 g.measurement_time(Duration::from_secs(30));
@@ -174,12 +213,87 @@ benchmarks! {
     ]
 }
 
+macro_rules! sorted_construction {
+    ( $id:ident: $t:ty => [ $( $name:literal ),* $(,)? ] ) => {
+        mod $id {
+            use super::*;
+
+            static DATA_SETS: &[DataSet<$t>] = &[ $( utils::data_set($name, 0, 0) ),* ];
+
+            pub fn benches(c: &mut Criterion) {
+                let mut g = c.benchmark_group("construction from sorted input");
+                g.measurement_time(Duration::from_secs(20));
+                g.sample_size(20);
+
+                for ds in DATA_SETS {
+                    let mut ranges = ds.read();
+                    ranges.sort_by_key(|range| (range.prefix().network(), range.prefix().length()));
+                    g.throughput(Throughput::Elements(ranges.len() as u64));
+                    g.bench_function(format!("{} (insert_from)", ds.name()), |b| {
+                        b.iter(|| PrefixSet::new().insert_from(ranges.clone()).to_owned())
+                    });
+                    g.bench_function(format!("{} (insert_sorted)", ds.name()), |b| {
+                        b.iter(|| PrefixSet::new().insert_sorted(ranges.clone()).to_owned())
+                    });
+                }
+                g.finish()
+            }
+        }
+    };
+}
+
+sorted_construction! {
+    ipv4_sorted: PrefixRange<Ipv4> => ["AS-WOLCOMM-ipv4-ranges"]
+}
+
+sorted_construction! {
+    ipv6_sorted: PrefixRange<Ipv6> => ["AS-WOLCOMM-ipv6-ranges"]
+}
+
+macro_rules! lookup {
+    ( $id:ident: $t:ty => [ $( $name:literal ),* $(,)? ] ) => {
+        mod $id {
+            use super::*;
+
+            static DATA_SETS: &[DataSet<$t>] = &[ $( utils::data_set($name, 0, 0) ),* ];
+
+            pub fn benches(c: &mut Criterion) {
+                let mut g = c.benchmark_group("prefix lookup");
+                g.measurement_time(Duration::from_secs(20));
+                g.sample_size(20);
+
+                for ds in DATA_SETS {
+                    let prefixes = ds.read();
+                    let set: PrefixSet<_> = prefixes.iter().copied().collect();
+                    g.throughput(Throughput::Elements(prefixes.len() as u64));
+                    g.bench_function(ds.name(), |b| {
+                        b.iter(|| assert!(prefixes.iter().all(|prefix| set.contains(*prefix))))
+                    });
+                }
+                g.finish()
+            }
+        }
+    };
+}
+
+lookup! {
+    ipv4_lookup: Prefix<Ipv4> => ["AS-HURRICANE-ipv4-prefixes"]
+}
+
+lookup! {
+    ipv6_lookup: Prefix<Ipv6> => ["AS-HURRICANE-ipv6-prefixes"]
+}
+
 fn benches() {
     let mut c = Criterion::default().configure_from_args();
     ipv4_prefixes::benches(&mut c);
     ipv6_prefixes::benches(&mut c);
     ipv4_ranges::benches(&mut c);
     ipv6_ranges::benches(&mut c);
+    ipv4_sorted::benches(&mut c);
+    ipv6_sorted::benches(&mut c);
+    ipv4_lookup::benches(&mut c);
+    ipv6_lookup::benches(&mut c);
 }
 
 criterion_main!(benches);
@@ -1,5 +1,8 @@
-use std::fmt;
-use std::num::ParseIntError;
+use alloc::{boxed::Box, string::String};
+use core::fmt;
+use core::num::ParseIntError;
+#[cfg(feature = "std")]
+use std::io;
 
 /// Errors returned by construction and parsing operations.
 #[derive(Debug)]
@@ -12,17 +15,80 @@ pub enum Error {
     PrefixLen(ip::Error),
     /// The IP prefix range couldn't be parsed.
     RangeParse {
+        /// The range being parsed.
+        input: String,
+        /// Which field of the range is invalid.
+        field: RangeField,
+        /// The offending field of the range.
+        token: String,
         /// The error returned during parsing, if any.
         source: Option<ParseIntError>,
     },
+    /// An entry in a textual list of prefixes and prefix ranges couldn't be
+    /// parsed.
+    EntryParse {
+        /// The offending entry.
+        token: String,
+        /// The error returned while parsing the entry.
+        source: ip::Error,
+    },
+    /// A line of textual input couldn't be parsed.
+    Line {
+        /// The 1-based number of the offending line.
+        number: usize,
+        /// The error returned while parsing the line.
+        source: Box<Error>,
+    },
+    /// An I/O error occurred while reading input.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// An encoded [`PrefixSet<A>`](crate::PrefixSet) couldn't be decoded.
+    Decode {
+        /// A description of the problem encountered.
+        reason: &'static str,
+    },
+    /// An item's address family differs from that of the collection it was
+    /// used with.
+    AfiMismatch {
+        /// The address family of the collection.
+        expected: ip::concrete::Afi,
+        /// The address family of the offending item.
+        found: ip::concrete::Afi,
+    },
 }
 
+/// A field of a textual prefix range, of the form `prefix,lower,upper`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeField {
+    /// The base prefix of the range.
+    Prefix,
+    /// The lower bound of the range's prefix lengths.
+    Lower,
+    /// The upper bound of the range's prefix lengths.
+    Upper,
+}
+
+impl fmt::Display for RangeField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Prefix => f.write_str("prefix"),
+            Self::Lower => f.write_str("lower bound"),
+            Self::Upper => f.write_str("upper bound"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::IpAddr(err) => Some(err),
+            Self::EntryParse { source, .. } => Some(source),
+            Self::Line { source, .. } => Some(source.as_ref()),
+            Self::Io(err) => Some(err),
             Self::RangeParse {
                 source: Some(source),
+                ..
             } => Some(source),
             _ => None,
         }
@@ -35,7 +101,30 @@ impl fmt::Display for Error {
             Self::IpAddr(ref err) => write!(f, "IP address handling error: {}", err),
             Self::AddrParse(ref err) => err.fmt(f),
             Self::PrefixLen(ref err) => err.fmt(f),
-            Self::RangeParse { .. } => f.write_str("invalid IP prefix range"),
+            Self::RangeParse {
+                input,
+                field,
+                token,
+                ..
+            } => write!(
+                f,
+                "invalid IP prefix range '{}': bad {} '{}'",
+                input, field, token
+            ),
+            Self::EntryParse { token, source } => {
+                write!(f, "invalid prefix set entry '{}': {}", token, source)
+            }
+            Self::Line { number, source } => write!(f, "line {}: {}", number, source),
+            #[cfg(feature = "std")]
+            Self::Io(ref err) => write!(f, "I/O error: {}", err),
+            Self::Decode { reason } => write!(f, "invalid encoded prefix set: {}", reason),
+            Self::AfiMismatch { expected, found } => {
+                write!(
+                    f,
+                    "address family mismatch: expected {}, found {}",
+                    expected, found
+                )
+            }
         }
     }
 }
@@ -46,10 +135,28 @@ impl From<ip::Error> for Error {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Converts a failure to parse a prefix length bound into
+/// [`Error::RangeParse`].
+///
+/// The range and the bound that failed to parse are unknown here, so `input`
+/// and `token` are left empty, and `field` is reported as
+/// [`RangeField::Lower`], the first of the integer fields of a range.
 impl From<ParseIntError> for Error {
     fn from(err: ParseIntError) -> Self {
-        Self::RangeParse { source: Some(err) }
+        Self::RangeParse {
+            input: String::new(),
+            field: RangeField::Lower,
+            token: String::new(),
+            source: Some(err),
+        }
     }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
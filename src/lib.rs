@@ -7,6 +7,9 @@
 //! - Iteration over either prefixes or ranges of prefixes, and
 //! - Self aggregation on each operation.
 //!
+//! [`PrefixMap`] builds on the same structure to associate a value with each
+//! prefix, supporting longest-match lookups.
+//!
 //! This is a Rust implementation derived in large part from the internal
 //! data-structure used in the widely used [`bgpq3`] tool by Alexandre Snarskii,
 //! packaged as a library, and with set-theoretic operations added.
@@ -39,11 +42,34 @@
 //! }
 //! ```
 //!
+//! # Features
+//!
+//! - `std` (enabled by default): depend on the Rust standard library. Without
+//!   it the crate is `#![no_std]`, and requires only [`alloc`]. Reading and
+//!   writing sets via `std::io`, and `PrefixSet::overlay()`, are only
+//!   available with `std`.
+//! - `serde`: implement `Serialize` and `Deserialize` for [`PrefixSet`],
+//!   as a sequence of prefix ranges.
+//! - `rayon`: add parallel set operations, such as
+//!   `PrefixSet::par_intersection()`, using [`rayon`](https://docs.rs/rayon).
+//!   Implies `std`.
+//! - `ipnet`: conversions between [`PrefixSet`] and the prefix types of the
+//!   [`ipnet`](https://docs.rs/ipnet) crate. Implies `std`.
+//! - `rand`: add uniform random sampling of the prefixes in a [`PrefixSet`],
+//!   using [`rand`](https://docs.rs/rand).
+//! - `proptest`: implement `Arbitrary` for [`PrefixSet`], to generate sets
+//!   in property tests using [`proptest`](https://docs.rs/proptest). Implies
+//!   `std`.
+//!
 //! [`bgpq3`]: https://github.com/snar/bgpq3
 //!
 #![doc(html_root_url = "https://docs.rs/prefixset/0.1.0-rc.2")]
 #![warn(missing_docs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
+
+pub mod map;
 pub mod set;
 
 mod error;
@@ -52,6 +78,8 @@ mod node;
 #[cfg(test)]
 mod tests;
 
-pub use crate::error::Error;
+pub use crate::error::{Error, RangeField};
+#[doc(inline)]
+pub use crate::map::PrefixMap;
 #[doc(inline)]
 pub use crate::set::PrefixSet;
@@ -0,0 +1,222 @@
+//! A map from IP prefixes to values, with longest-match lookups.
+use alloc::collections::BTreeMap;
+
+use ip::{
+    concrete::{self, Address, PrefixLength},
+    traits::Prefix as _,
+    Afi, Prefix,
+};
+
+use crate::set::PrefixSet;
+
+/// A map associating a value of type `V` with each of a collection of IP
+/// prefixes, such as the origin ASN of a route, or a tag.
+///
+/// The prefixes are held in a [`PrefixSet<A>`], which is used to find the
+/// covering prefixes of a lookup, while the values are stored separately,
+/// keyed by prefix.
+///
+/// ``` rust
+/// # use ip::{Address, Ipv4};
+/// # use prefixset::{Error, PrefixMap};
+/// # fn main() -> Result<(), Error> {
+/// let mut origins = PrefixMap::<Ipv4, _>::new();
+/// origins.insert("192.0.2.0/24".parse()?, 64496_u32);
+/// origins.insert("192.0.2.128/25".parse()?, 64497);
+/// assert_eq!(
+///     origins.longest_match("192.0.2.200".parse::<Address<Ipv4>>()?),
+///     Some(("192.0.2.128/25".parse()?, &64497))
+/// );
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefixMap<A: Afi, V> {
+    prefixes: PrefixSet<A>,
+    values: BTreeMap<(Address<A>, PrefixLength<A>), V>,
+}
+
+impl<A: Afi, V> PrefixMap<A, V> {
+    /// Construct a new, empty [`PrefixMap<A, V>`].
+    pub fn new() -> Self {
+        PrefixMap {
+            prefixes: PrefixSet::new(),
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Associate `value` with `prefix`, returning the value previously
+    /// associated with `prefix`, if any.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv6, Prefix};
+    /// # use prefixset::{Error, PrefixMap};
+    /// # fn main() -> Result<(), Error> {
+    /// let prefix = "2001:db8::/32".parse::<Prefix<Ipv6>>()?;
+    /// let mut map = PrefixMap::<Ipv6, _>::new();
+    /// assert_eq!(map.insert(prefix, "documentation"), None);
+    /// assert_eq!(map.insert(prefix, "example"), Some("documentation"));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn insert(&mut self, prefix: Prefix<A>, value: V) -> Option<V> {
+        let old = self.values.insert(Self::key(prefix), value);
+        if old.is_none() {
+            self.prefixes.insert(prefix);
+        }
+        old
+    }
+
+    /// Remove `prefix` from `self`, returning its associated value, if
+    /// any.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixMap};
+    /// # fn main() -> Result<(), Error> {
+    /// let prefix = "192.0.2.0/24".parse::<Prefix<Ipv4>>()?;
+    /// let mut map = PrefixMap::<Ipv4, _>::new();
+    /// map.insert(prefix, 64496);
+    /// assert_eq!(map.remove(prefix), Some(64496));
+    /// assert!(map.is_empty());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn remove(&mut self, prefix: Prefix<A>) -> Option<V> {
+        let old = self.values.remove(&Self::key(prefix));
+        if old.is_some() {
+            self.prefixes.remove(prefix);
+        }
+        old
+    }
+
+    /// Get a reference to the value associated with exactly `prefix`.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixMap};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut map = PrefixMap::<Ipv4, _>::new();
+    /// map.insert("192.0.2.0/24".parse()?, 64496);
+    /// assert_eq!(map.get("192.0.2.0/24".parse()?), Some(&64496));
+    /// assert_eq!(map.get("192.0.2.0/25".parse()?), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn get(&self, prefix: Prefix<A>) -> Option<&V> {
+        self.values.get(&Self::key(prefix))
+    }
+
+    /// Get a mutable reference to the value associated with exactly
+    /// `prefix`.
+    pub fn get_mut(&mut self, prefix: Prefix<A>) -> Option<&mut V> {
+        self.values.get_mut(&Self::key(prefix))
+    }
+
+    /// Test whether `prefix` has an associated value in `self`.
+    pub fn contains_key(&self, prefix: Prefix<A>) -> bool {
+        self.values.contains_key(&Self::key(prefix))
+    }
+
+    /// Find the most specific prefix in `self` covering `addr`, together
+    /// with its associated value.
+    ///
+    /// ``` rust
+    /// # use ip::{Address, Ipv4};
+    /// # use prefixset::{Error, PrefixMap};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut map = PrefixMap::<Ipv4, _>::new();
+    /// map.insert("10.0.0.0/8".parse()?, "a");
+    /// map.insert("10.1.0.0/16".parse()?, "b");
+    /// assert_eq!(
+    ///     map.longest_match("10.2.3.4".parse::<Address<Ipv4>>()?),
+    ///     Some(("10.0.0.0/8".parse()?, &"a"))
+    /// );
+    /// assert_eq!(map.longest_match("11.0.0.1".parse::<Address<Ipv4>>()?), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn longest_match(&self, addr: Address<A>) -> Option<(Prefix<A>, &V)> {
+        self.longest_match_prefix(concrete::Prefix::new(addr, PrefixLength::MAX))
+    }
+
+    /// Find the most specific prefix in `self` that is equal to or covers
+    /// `prefix`, together with its associated value.
+    pub fn longest_match_prefix(&self, prefix: Prefix<A>) -> Option<(Prefix<A>, &V)> {
+        self.prefixes
+            .longest_match(prefix)
+            .and_then(|prefix| self.get(prefix).map(|value| (prefix, value)))
+    }
+
+    /// Get an iterator over the prefixes in `self` and their associated
+    /// values, ordered by network address and then by length.
+    pub fn iter(&self) -> impl Iterator<Item = (Prefix<A>, &V)> {
+        self.values
+            .iter()
+            .map(|(&(addr, length), value)| (concrete::Prefix::new(addr, length), value))
+    }
+
+    /// Get the set of prefixes that have an associated value in `self`.
+    pub fn prefixes(&self) -> &PrefixSet<A> {
+        &self.prefixes
+    }
+
+    /// Get the number of prefixes in `self`.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Test whether `self` is empty.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Remove all prefixes and values from `self`.
+    pub fn clear(&mut self) {
+        self.prefixes.clear();
+        self.values.clear();
+    }
+
+    fn key(prefix: Prefix<A>) -> (Address<A>, PrefixLength<A>) {
+        (prefix.network(), prefix.length())
+    }
+}
+
+impl<A: Afi, V> Default for PrefixMap<A, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Afi, V> Extend<(Prefix<A>, V)> for PrefixMap<A, V> {
+    /// Associate each value yielded by `iter` with its prefix, as by
+    /// [`PrefixMap::insert()`], but aggregating the underlying set only once,
+    /// after all of the prefixes new to `self` have been added to it.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (Prefix<A>, V)>,
+    {
+        let values = &mut self.values;
+        self.prefixes
+            .insert_from(iter.into_iter().filter_map(|(prefix, value)| {
+                values
+                    .insert(Self::key(prefix), value)
+                    .is_none()
+                    .then_some(prefix)
+            }));
+    }
+}
+
+impl<A: Afi, V> FromIterator<(Prefix<A>, V)> for PrefixMap<A, V> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (Prefix<A>, V)>,
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests;
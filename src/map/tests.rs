@@ -0,0 +1,189 @@
+use ip::{Address, Ipv4, Ipv6, Prefix};
+
+use crate::tests::TestResult;
+
+use super::PrefixMap;
+
+mod origin_lookup {
+    use super::*;
+
+    fn setup() -> Result<PrefixMap<Ipv4, u32>, ip::Error> {
+        [
+            ("10.0.0.0/8", 64496),
+            ("10.1.0.0/16", 64497),
+            ("10.1.2.0/24", 64498),
+            ("192.0.2.0/24", 64499),
+        ]
+        .into_iter()
+        .map(|(s, asn)| Ok((s.parse()?, asn)))
+        .collect()
+    }
+
+    fn origin(map: &PrefixMap<Ipv4, u32>, addr: &str) -> Result<Option<u32>, ip::Error> {
+        Ok(map
+            .longest_match(addr.parse::<Address<Ipv4>>()?)
+            .map(|(_, &asn)| asn))
+    }
+
+    #[test]
+    fn has_all_entries() -> TestResult {
+        let map = setup()?;
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.prefixes().len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn gets_exact_matches_only() -> TestResult {
+        let map = setup()?;
+        assert_eq!(map.get("10.1.0.0/16".parse()?), Some(&64497));
+        assert_eq!(map.get("10.1.0.0/17".parse()?), None);
+        assert!(!map.contains_key("10.0.0.0/7".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn finds_most_specific_origin() -> TestResult {
+        let map = setup()?;
+        assert_eq!(origin(&map, "10.1.2.3")?, Some(64498));
+        assert_eq!(origin(&map, "10.1.3.3")?, Some(64497));
+        assert_eq!(origin(&map, "10.2.0.1")?, Some(64496));
+        assert_eq!(origin(&map, "192.0.2.1")?, Some(64499));
+        assert_eq!(origin(&map, "198.51.100.1")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn returns_matched_prefix() -> TestResult {
+        let map = setup()?;
+        assert_eq!(
+            map.longest_match("10.1.200.1".parse()?),
+            Some(("10.1.0.0/16".parse::<Prefix<Ipv4>>()?, &64497))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn matches_covering_prefix() -> TestResult {
+        let map = setup()?;
+        assert_eq!(
+            map.longest_match_prefix("10.1.2.0/23".parse()?),
+            Some(("10.1.0.0/16".parse::<Prefix<Ipv4>>()?, &64497))
+        );
+        assert_eq!(map.longest_match_prefix("10.0.0.0/7".parse()?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn falls_back_after_removal() -> TestResult {
+        let mut map = setup()?;
+        assert_eq!(map.remove("10.1.2.0/24".parse()?), Some(64498));
+        assert_eq!(map.remove("10.1.2.0/24".parse()?), None);
+        assert_eq!(origin(&map, "10.1.2.3")?, Some(64497));
+        assert_eq!(map.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn replaces_existing_value() -> TestResult {
+        let mut map = setup()?;
+        assert_eq!(map.insert("10.0.0.0/8".parse()?, 65000), Some(64496));
+        assert_eq!(origin(&map, "10.2.0.1")?, Some(65000));
+        assert_eq!(map.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn updates_value_in_place() -> TestResult {
+        let mut map = setup()?;
+        if let Some(asn) = map.get_mut("192.0.2.0/24".parse()?) {
+            *asn = 65001;
+        }
+        assert_eq!(origin(&map, "192.0.2.1")?, Some(65001));
+        Ok(())
+    }
+
+    #[test]
+    fn iterates_in_address_order() -> TestResult {
+        let map = setup()?;
+        let asns: Vec<_> = map.iter().map(|(_, &asn)| asn).collect();
+        assert_eq!(asns, vec![64496, 64497, 64498, 64499]);
+        Ok(())
+    }
+
+    #[test]
+    fn clears_all_entries() -> TestResult {
+        let mut map = setup()?;
+        map.clear();
+        assert!(map.is_empty());
+        assert!(map.prefixes().is_empty());
+        assert_eq!(origin(&map, "10.0.0.1")?, None);
+        Ok(())
+    }
+}
+
+mod ipv6 {
+    use super::*;
+
+    #[test]
+    fn matches_default_route() -> TestResult {
+        let mut map = PrefixMap::<Ipv6, _>::new();
+        map.insert("::/0".parse()?, "default");
+        map.insert("2001:db8::/32".parse()?, "documentation");
+        assert_eq!(
+            map.longest_match("2001:db8::1".parse()?),
+            Some(("2001:db8::/32".parse::<Prefix<Ipv6>>()?, &"documentation"))
+        );
+        assert_eq!(
+            map.longest_match("2001:db9::1".parse()?),
+            Some(("::/0".parse::<Prefix<Ipv6>>()?, &"default"))
+        );
+        Ok(())
+    }
+}
+
+mod bulk_load {
+    use super::*;
+
+    fn entries() -> Vec<(Prefix<Ipv4>, u32)> {
+        (0..=255u32)
+            .flat_map(|i| {
+                [
+                    (format!("10.{i}.0.0/16"), 64496),
+                    (format!("10.{i}.{i}.0/24"), 64497 + i),
+                ]
+            })
+            .chain([("10.0.0.0/8".to_string(), 64500)])
+            .map(|(s, asn)| (s.parse().unwrap(), asn))
+            .collect()
+    }
+
+    #[test]
+    fn matches_repeated_insertion() {
+        let collected: PrefixMap<Ipv4, u32> = entries().into_iter().collect();
+        let mut inserted = PrefixMap::new();
+        entries().into_iter().for_each(|(prefix, asn)| {
+            inserted.insert(prefix, asn);
+        });
+        assert_eq!(collected, inserted);
+        assert_eq!(collected.len(), 2 * 256 + 1);
+        assert_eq!(collected.prefixes().len(), collected.len());
+        assert!(collected.prefixes().is_aggregated());
+    }
+
+    #[test]
+    fn later_values_replace_earlier() -> TestResult {
+        let mut map: PrefixMap<Ipv4, u32> = PrefixMap::new();
+        map.extend([
+            ("192.0.2.0/24".parse()?, 64496),
+            ("192.0.2.0/25".parse()?, 64497),
+            ("192.0.2.0/24".parse()?, 64498),
+        ]);
+        map.extend([("192.0.2.0/25".parse()?, 64499)]);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.prefixes().len(), 2);
+        assert_eq!(map.get("192.0.2.0/24".parse()?), Some(&64498));
+        assert_eq!(map.get("192.0.2.0/25".parse()?), Some(&64499));
+        Ok(())
+    }
+}
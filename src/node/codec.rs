@@ -0,0 +1,304 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::borrow::Borrow;
+
+use ip::{
+    concrete::{Address, Prefix, PrefixLength, PrefixOrdering, PrefixRange},
+    traits::Prefix as _,
+    Afi,
+};
+
+use crate::error::{Error, Result};
+
+use super::{branch_mask, Direction, GlueMap, Node};
+
+// layout of the header byte written for each node. the delta length is only
+// used by the delta encoding.
+const HAS_LEFT: u8 = 0b1000_0000;
+const HAS_RIGHT: u8 = 0b0100_0000;
+const GLUEMAP_KIND: u8 = 0b0011_0000;
+const DELTA_LENGTH: u8 = 0b0000_1111;
+
+// kinds of gluemap encoding.
+const GLUE: u8 = 0b0000_0000;
+const SINGLETON: u8 = 0b0001_0000;
+const RANGES: u8 = 0b0010_0000;
+
+fn take<'a>(buf: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    if buf.len() < n {
+        return Err(Error::Decode {
+            reason: "unexpected end of input",
+        });
+    }
+    let (head, tail) = buf.split_at(n);
+    *buf = tail;
+    Ok(head)
+}
+
+fn take_byte(buf: &mut &[u8]) -> Result<u8> {
+    take(buf, 1).map(|bytes| bytes[0])
+}
+
+fn length_to_byte<A: Afi>(length: PrefixLength<A>) -> u8 {
+    // lengths are at most 128, so this never truncates.
+    Into::<usize>::into(length.into_primitive()) as u8
+}
+
+fn length_from_byte<A: Afi>(byte: u8) -> Result<PrefixLength<A>> {
+    PrefixLength::try_from(usize::from(byte)).map_err(|_| Error::Decode {
+        reason: "prefix length out of bounds",
+    })
+}
+
+impl<A: Afi> Node<A> {
+    /// Append the plain encoding of the tree rooted at `self` to `buf`.
+    ///
+    /// Nodes are written in pre-order, each as:
+    ///
+    /// - a header byte, containing flags for the presence of left and right
+    ///   children and the kind of gluemap encoding used;
+    /// - the node's prefix length;
+    /// - the node's full network address;
+    /// - for gluemaps other than a glue node or a single prefix of the
+    ///   node's own length, a count of ranges followed by the lower and upper
+    ///   bound of each.
+    pub fn encode_plain(&self, buf: &mut Vec<u8>) {
+        let (header, ranges) = self.header();
+        buf.extend([header, length_to_byte(self.prefix.length())]);
+        buf.extend_from_slice(self.prefix.network().octets().borrow());
+        write_ranges(ranges, buf);
+        if let Some(child) = &self.left {
+            child.encode_plain(buf);
+        }
+        if let Some(child) = &self.right {
+            child.encode_plain(buf);
+        }
+    }
+
+    /// Decode a tree previously written by [`Node::encode_plain()`],
+    /// advancing `buf` past the consumed bytes.
+    pub fn decode_plain(buf: &mut &[u8]) -> Result<Box<Self>> {
+        Self::decode_plain_subtree(None, buf)
+    }
+
+    fn decode_plain_subtree(
+        parent: Option<(&Prefix<A>, Direction)>,
+        buf: &mut &[u8],
+    ) -> Result<Box<Self>> {
+        let header = take_byte(buf)?;
+        if header & DELTA_LENGTH != 0 {
+            return Err(Error::Decode {
+                reason: "invalid node header",
+            });
+        }
+        let length = length_from_byte(take_byte(buf)?)?;
+        // ok to unwrap, because `take()` returns exactly `A::Octets::LENGTH`
+        // bytes.
+        let network =
+            Address::<A>::from_slice(take(buf, Address::<A>::ZEROS.octets().borrow().len())?)
+                .unwrap();
+        let prefix = Prefix::new(network, length);
+        // check that child nodes are strict subprefixes of their parent, on
+        // the side that the parent's header claimed.
+        if let Some((parent, direction)) = parent {
+            if !matches!(parent.compare(&prefix), PrefixOrdering::Subprefix(_)) {
+                return Err(Error::Decode {
+                    reason: "child prefix is not a subprefix of its parent",
+                });
+            }
+            match (direction, Self::new_glue(prefix).branch_direction(parent)) {
+                (Direction::Left, Direction::Left) | (Direction::Right, Direction::Right) => (),
+                _ => {
+                    return Err(Error::Decode {
+                        reason: "child prefix is on the wrong branch",
+                    })
+                }
+            }
+        }
+        let gluemap = read_gluemap(header, prefix, buf)?;
+        let mut node = Self::new(prefix, gluemap).boxed();
+        if header & HAS_LEFT != 0 {
+            node.left = Some(Self::decode_plain_subtree(
+                Some((&prefix, Direction::Left)),
+                buf,
+            )?);
+        }
+        if header & HAS_RIGHT != 0 {
+            node.right = Some(Self::decode_plain_subtree(
+                Some((&prefix, Direction::Right)),
+                buf,
+            )?);
+        }
+        Ok(node)
+    }
+
+    /// Append the delta encoding of the tree rooted at `self` to `buf`.
+    ///
+    /// This differs from [`Node::encode_plain()`] in the encoding of each
+    /// node's prefix:
+    ///
+    /// - the difference between the node's prefix length and its parent's is
+    ///   stored in the low bits of the header byte (or `0xf`, followed by a
+    ///   byte containing the difference, if it doesn't fit);
+    /// - in place of the network address, the bits of the XOR of the node's
+    ///   network address with its parent's, starting after the branching
+    ///   bit, are packed into the minimum number of octets.
+    ///
+    /// Because every node shares the leading bits of its parent, and the
+    /// branching bit is implied by which child it is, typically only a few
+    /// bits of each prefix need to be stored. The root node is encoded
+    /// relative to the default prefix.
+    pub fn encode_delta(&self, buf: &mut Vec<u8>) {
+        self.encode_delta_subtree(&Prefix::DEFAULT, 0, buf)
+    }
+
+    fn encode_delta_subtree(&self, parent: &Prefix<A>, skip: u8, buf: &mut Vec<u8>) {
+        let (header, ranges) = self.header();
+        let parent_length = length_to_byte(parent.length());
+        let delta_length = length_to_byte(self.prefix.length()) - parent_length;
+        if delta_length < DELTA_LENGTH {
+            buf.push(header | delta_length);
+        } else {
+            buf.extend([header | DELTA_LENGTH, delta_length]);
+        }
+        let bits = usize::from(delta_length - skip);
+        if bits > 0 {
+            // ok to unwrap and shift, because `parent_length + skip` is less
+            // than the length of `self`.
+            let delta = Address::<A>::new(
+                (self.prefix.network() ^ parent.network())
+                    << length_from_byte::<A>(parent_length + skip)
+                        .unwrap()
+                        .into_primitive(),
+            )
+            .octets();
+            buf.extend_from_slice(&delta.borrow()[..bits.div_ceil(8)]);
+        }
+        write_ranges(ranges, buf);
+        if let Some(child) = &self.left {
+            child.encode_delta_subtree(&self.prefix, 1, buf);
+        }
+        if let Some(child) = &self.right {
+            child.encode_delta_subtree(&self.prefix, 1, buf);
+        }
+    }
+
+    /// Decode a tree previously written by [`Node::encode_delta()`],
+    /// advancing `buf` past the consumed bytes.
+    pub fn decode_delta(buf: &mut &[u8]) -> Result<Box<Self>> {
+        Self::decode_delta_subtree(&Prefix::DEFAULT, None, buf)
+    }
+
+    fn decode_delta_subtree(
+        parent: &Prefix<A>,
+        direction: Option<Direction>,
+        buf: &mut &[u8],
+    ) -> Result<Box<Self>> {
+        let header = take_byte(buf)?;
+        let delta_length = match header & DELTA_LENGTH {
+            DELTA_LENGTH => take_byte(buf)?,
+            delta_length => delta_length,
+        };
+        let parent_length = length_to_byte(parent.length());
+        let length = length_from_byte(parent_length.saturating_add(delta_length))?;
+        let mut network = parent.network();
+        let skip = match direction {
+            Some(_) if delta_length == 0 => {
+                return Err(Error::Decode {
+                    reason: "child prefix is not a subprefix of its parent",
+                })
+            }
+            Some(Direction::Left) => 1,
+            Some(Direction::Right) => {
+                network = network | branch_mask(parent);
+                1
+            }
+            None => 0,
+        };
+        let bits = usize::from(delta_length - skip);
+        if bits > 0 {
+            // ok to unwrap, because `bits` is at most the maximum prefix
+            // length, so no more than `A::Octets::LENGTH` bytes are taken.
+            let delta = Address::<A>::from_slice(take(buf, bits.div_ceil(8))?)
+                .unwrap()
+                .into_primitive();
+            // ok to unwrap and shift, because `parent_length + skip` is less
+            // than `length`.
+            network = Address::new(
+                network.into_primitive()
+                    | (delta
+                        >> length_from_byte::<A>(parent_length + skip)
+                            .unwrap()
+                            .into_primitive()),
+            );
+        }
+        let prefix = Prefix::new(network, length);
+        let gluemap = read_gluemap(header, prefix, buf)?;
+        let mut node = Self::new(prefix, gluemap).boxed();
+        if header & HAS_LEFT != 0 {
+            node.left = Some(Self::decode_delta_subtree(
+                &prefix,
+                Some(Direction::Left),
+                buf,
+            )?);
+        }
+        if header & HAS_RIGHT != 0 {
+            node.right = Some(Self::decode_delta_subtree(
+                &prefix,
+                Some(Direction::Right),
+                buf,
+            )?);
+        }
+        Ok(node)
+    }
+
+    // get the header byte common to both encodings, along with the ranges
+    // that need to be written for the node's gluemap.
+    fn header(&self) -> (u8, Vec<PrefixRange<A>>) {
+        let mut header = 0;
+        if self.left.is_some() {
+            header |= HAS_LEFT;
+        }
+        if self.right.is_some() {
+            header |= HAS_RIGHT;
+        }
+        if self.is_glue() {
+            (header | GLUE, Vec::new())
+        } else if self.gluemap == GlueMap::singleton(self.prefix.length()) {
+            (header | SINGLETON, Vec::new())
+        } else {
+            (header | RANGES, self.ranges().collect())
+        }
+    }
+}
+
+fn write_ranges<A: Afi>(ranges: Vec<PrefixRange<A>>, buf: &mut Vec<u8>) {
+    if !ranges.is_empty() {
+        // there can be at most `(MAX_LENGTH + 2) / 2` disjoint ranges.
+        buf.push(ranges.len() as u8);
+        ranges.into_iter().for_each(|range| {
+            buf.extend([length_to_byte(range.lower()), length_to_byte(range.upper())]);
+        });
+    }
+}
+
+fn read_gluemap<A: Afi>(header: u8, prefix: Prefix<A>, buf: &mut &[u8]) -> Result<GlueMap<A>> {
+    match header & GLUEMAP_KIND {
+        GLUE => Ok(GlueMap::ZERO),
+        SINGLETON => Ok(GlueMap::singleton(prefix.length())),
+        RANGES => {
+            let mut gluemap = GlueMap::ZERO;
+            for _ in 0..take_byte(buf)? {
+                let lower = length_from_byte(take_byte(buf)?)?;
+                let upper = length_from_byte(take_byte(buf)?)?;
+                let range = PrefixRange::new(prefix, lower..=upper).map_err(|_| Error::Decode {
+                    reason: "invalid prefix length range",
+                })?;
+                gluemap |= range.into();
+            }
+            Ok(gluemap)
+        }
+        _ => Err(Error::Decode {
+            reason: "invalid gluemap encoding",
+        }),
+    }
+}
@@ -0,0 +1,26 @@
+use ip::{Afi, Prefix};
+
+/// A structural change made to the tree underlying a
+/// [`PrefixSet<A>`](crate::PrefixSet) during aggregation, reported by
+/// [`PrefixSet::insert_from_instrumented()`](crate::PrefixSet::insert_from_instrumented).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AggregateEvent<A: Afi> {
+    /// Prefix lengths present in both children of the node at `prefix` were
+    /// merged into the node itself.
+    Merge {
+        /// The prefix of the node that the lengths were merged into.
+        prefix: Prefix<A>,
+    },
+    /// Prefix lengths of the node at `prefix` were removed, because they
+    /// were already represented by a less specific node.
+    Deduplicate {
+        /// The prefix of the node that the lengths were removed from.
+        prefix: Prefix<A>,
+    },
+    /// The node at `prefix` no longer represented any prefixes, and was not
+    /// needed to join two subtrees, so was removed.
+    Clean {
+        /// The prefix of the node that was removed.
+        prefix: Prefix<A>,
+    },
+}
@@ -1,5 +1,5 @@
-use std::fmt;
-use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, RangeInclusive};
+use core::fmt;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, RangeInclusive};
 
 use ip::{
     concrete::{PrefixLength, PrefixRange},
@@ -73,6 +73,37 @@ impl<A: Afi> GlueMap<A> {
         Self { inner }
     }
 
+    // map of all lengths up to and including `length`.
+    pub fn up_to(length: PrefixLength<A>) -> Self {
+        Self::from_lengths(PrefixLength::MIN..=length)
+    }
+
+    // map of all lengths in `lengths`, which may be empty.
+    pub fn from_lengths(lengths: RangeInclusive<PrefixLength<A>>) -> Self {
+        let mut map = Self::ZERO;
+        if lengths.is_empty() {
+            return map;
+        }
+        let (mut length, upper) = lengths.into_inner();
+        loop {
+            map |= Self::singleton(length);
+            match length.increment() {
+                Ok(next) if next <= upper => length = next,
+                _ => break map,
+            }
+        }
+    }
+
+    pub fn lengths(self) -> Lengths<A> {
+        Lengths {
+            map: self,
+            next_length: Some(PrefixLength::MIN),
+        }
+    }
+
+    // number of distinct prefix lengths set in the map. this is exactly the
+    // number of lengths covered by a node with this gluemap, and is relied
+    // upon when counting prefixes without expanding them.
     pub fn count_ones(&self) -> usize {
         self.inner.count_ones()
     }
@@ -92,17 +123,45 @@ impl<A: Afi> GlueMap<A> {
     }
 }
 
+/// Iterator over the prefix lengths represented by a single node of the
+/// tree underlying a [`PrefixSet<A>`](crate::PrefixSet), in ascending order.
+///
+/// Returned by [`PrefixSet::nodes()`](crate::PrefixSet::nodes).
+#[derive(Clone, Debug)]
+pub struct Lengths<A: Afi> {
+    map: GlueMap<A>,
+    next_length: Option<PrefixLength<A>>,
+}
+
+impl<A: Afi> Iterator for Lengths<A> {
+    type Item = PrefixLength<A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let length = *self.map.next_range(self.next_length?)?.start();
+        self.next_length = length.increment().ok();
+        Some(length)
+    }
+}
+
+impl<A: Afi> GlueMap<A> {
+    pub fn prev_range(&self, to: PrefixLength<A>) -> Option<RangeInclusive<PrefixLength<A>>> {
+        let end = to.into_primitive().into();
+        let last = self.inner[..=end].last_one()?;
+        let first = match self.inner[..=last].last_zero() {
+            Some(zero) => zero + 1,
+            None => 0,
+        };
+        // Ok to unwrap because indices of Self are within the bounds
+        // of `PrefixLength<A>`
+        let lower = first.try_into().unwrap();
+        let upper = last.try_into().unwrap();
+        Some(lower..=upper)
+    }
+}
+
 impl<A: Afi> From<PrefixRange<A>> for GlueMap<A> {
     fn from(range: PrefixRange<A>) -> Self {
-        let mut map = Self::ZERO;
-        let mut length = range.lower();
-        loop {
-            map |= Self::singleton(length);
-            match length.increment() {
-                Ok(next) if next <= range.upper() => length = next,
-                _ => break map,
-            }
-        }
+        Self::from_lengths(range.lower()..=range.upper())
     }
 }
 
@@ -112,7 +171,7 @@ impl<A: Afi> fmt::Debug for GlueMap<A> {
             .field(&format_args!(
                 "{:#0w$b}",
                 &self.inner,
-                w = A::Primitive::MAX_LENGTH.into() as usize + 2
+                w = A::Primitive::MAX_LENGTH.into() + 2
             ))
             .finish()
     }
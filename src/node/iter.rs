@@ -1,3 +1,7 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::iter::FusedIterator;
+use core::ptr;
+
 use ip::{
     concrete::{PrefixLength, PrefixRange},
     traits::PrefixLength as _,
@@ -6,29 +10,30 @@ use ip::{
 
 use super::Node;
 
+// pre-order iterator over a tree, visiting right subtrees before left.
+//
+// both ends are driven by a single explicit work-stack of node references,
+// so that descending the tree never allocates more than the growth of the
+// stacks.
 #[derive(Debug)]
 pub struct Children<'a, A: Afi> {
-    this: Option<&'a Node<A>>,
-    parent: Option<Box<Children<'a, A>>>,
-    children: Vec<Option<&'a Node<A>>>,
-}
-
-impl<A: Afi> Default for Children<'_, A> {
-    fn default() -> Self {
-        Self {
-            this: None,
-            parent: None,
-            children: Vec::default(),
-        }
-    }
+    front: Vec<&'a Node<A>>,
+    back: Vec<(&'a Node<A>, bool)>,
+    // the nodes most recently yielded from each end, used to detect when the
+    // two ends meet.
+    front_last: Option<&'a Node<A>>,
+    back_last: Option<&'a Node<A>>,
+    done: bool,
 }
 
 impl<'a, A: Afi> From<&'a Node<A>> for Children<'a, A> {
     fn from(node: &'a Node<A>) -> Self {
         Self {
-            this: Some(node),
-            parent: None,
-            children: [node.left.as_deref(), node.right.as_deref()].into(),
+            front: vec![node],
+            back: vec![(node, false)],
+            front_last: None,
+            back_last: None,
+            done: false,
         }
     }
 }
@@ -37,33 +42,84 @@ impl<'a, A: Afi> Iterator for Children<'a, A> {
     type Item = &'a Node<A>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(this) = self.this.take() {
-            return Some(this);
+        if self.done {
+            return None;
         }
-        while let Some(maybe_child) = self.children.pop() {
-            if let Some(child) = maybe_child {
-                // construct new Children iterator from `child` replacing self,
-                // with current self as parent, and recurse over it
-                let mut child_iter = child.children();
-                child_iter.parent = Some(Box::new(std::mem::take(self)));
-                *self = child_iter;
-                return self.next();
+        match self.front.pop() {
+            Some(node) if !self.back_last.is_some_and(|last| ptr::eq(last, node)) => {
+                // push the left child first, so that the right subtree is
+                // visited first.
+                self.front.extend(node.left.as_deref());
+                self.front.extend(node.right.as_deref());
+                self.front_last = Some(node);
+                Some(node)
+            }
+            _ => {
+                self.done = true;
+                None
             }
         }
-        // No children left, recurse over parent
-        if let Some(parent) = self.parent.take() {
-            *self = *parent;
-            self.next()
-        } else {
-            None
+    }
+}
+
+impl<A: Afi> DoubleEndedIterator for Children<'_, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
         }
+        // nodes are yielded from the back in the reverse of the forward
+        // pre-order: the left subtree, then the right subtree, then the node
+        // itself.
+        while let Some((node, expanded)) = self.back.pop() {
+            if expanded {
+                if self.front_last.is_some_and(|last| ptr::eq(last, node)) {
+                    break;
+                }
+                self.back_last = Some(node);
+                return Some(node);
+            }
+            self.back.push((node, true));
+            self.back
+                .extend(node.right.as_deref().map(|child| (child, false)));
+            self.back
+                .extend(node.left.as_deref().map(|child| (child, false)));
+        }
+        self.done = true;
+        None
     }
 }
 
+impl<A: Afi> FusedIterator for Children<'_, A> {}
+
 #[derive(Debug)]
+pub struct IntoChildren<A: Afi> {
+    stack: Vec<Box<Node<A>>>,
+}
+
+impl<A: Afi> From<Box<Node<A>>> for IntoChildren<A> {
+    fn from(node: Box<Node<A>>) -> Self {
+        Self { stack: vec![node] }
+    }
+}
+
+impl<A: Afi> Iterator for IntoChildren<A> {
+    type Item = Box<Node<A>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut this = self.stack.pop()?;
+        // push the left child first, so that nodes are yielded in the same
+        // order as `Children`.
+        self.stack.extend(this.left.take());
+        self.stack.extend(this.right.take());
+        Some(this)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Ranges<'a, A: Afi> {
     this: &'a Node<A>,
     next_length: Option<PrefixLength<A>>,
+    prev_length: Option<PrefixLength<A>>,
 }
 
 impl<'a, A: Afi> From<&'a Node<A>> for Ranges<'a, A> {
@@ -71,6 +127,7 @@ impl<'a, A: Afi> From<&'a Node<A>> for Ranges<'a, A> {
         Self {
             this: node,
             next_length: Some(PrefixLength::MIN),
+            prev_length: Some(PrefixLength::MAX),
         }
     }
 }
@@ -78,6 +135,51 @@ impl<'a, A: Afi> From<&'a Node<A>> for Ranges<'a, A> {
 impl<'a, A: Afi> Iterator for Ranges<'a, A> {
     type Item = PrefixRange<A>;
 
+    fn next(&mut self) -> Option<Self::Item> {
+        let range = self.this.gluemap.next_range(self.next_length?)?;
+        // ranges are maximal, so any range starting at or before the lower
+        // bound of the last range yielded from the back is that same range.
+        if range.start() > &self.prev_length? {
+            return None;
+        }
+        self.next_length = range.end().increment().ok();
+        // unwrap is safe here as long as self.map doesn't have any
+        // bits set lower than self.this.prefix.length()
+        Some(PrefixRange::new(self.this.prefix, range).unwrap())
+    }
+}
+
+impl<A: Afi> DoubleEndedIterator for Ranges<'_, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let range = self.this.gluemap.prev_range(self.prev_length?)?;
+        if range.end() < &self.next_length? {
+            return None;
+        }
+        self.prev_length = range.start().decrement().ok();
+        // unwrap is safe here as long as self.map doesn't have any
+        // bits set lower than self.this.prefix.length()
+        Some(PrefixRange::new(self.this.prefix, range).unwrap())
+    }
+}
+
+#[derive(Debug)]
+pub struct IntoRanges<A: Afi> {
+    this: Box<Node<A>>,
+    next_length: Option<PrefixLength<A>>,
+}
+
+impl<A: Afi> From<Box<Node<A>>> for IntoRanges<A> {
+    fn from(node: Box<Node<A>>) -> Self {
+        Self {
+            this: node,
+            next_length: Some(PrefixLength::MIN),
+        }
+    }
+}
+
+impl<A: Afi> Iterator for IntoRanges<A> {
+    type Item = PrefixRange<A>;
+
     fn next(&mut self) -> Option<Self::Item> {
         let range = self.this.gluemap.next_range(self.next_length?)?;
         self.next_length = range.end().increment().ok();
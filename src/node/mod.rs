@@ -1,22 +1,43 @@
+use alloc::{borrow::ToOwned, boxed::Box, vec, vec::Vec};
+use core::ops::RangeInclusive;
+
 use ip::{
-    concrete::{Hostmask, Netmask, PrefixOrdering},
+    concrete::{Bitmask, Hostmask, Netmask, PrefixLength, PrefixOrdering},
     traits::{Address as _, Prefix as _, PrefixLength as _},
     Afi, Prefix,
 };
 
+mod codec;
+mod event;
 mod from;
 mod gluemap;
 mod iter;
 mod ops;
+#[cfg(feature = "rayon")]
+mod par;
 
+pub use self::event::AggregateEvent;
 use self::gluemap::GlueMap;
-pub use self::iter::{Children, Ranges};
+pub use self::gluemap::Lengths;
+pub use self::iter::{Children, IntoChildren, IntoRanges, Ranges};
 
 enum Direction {
     Left,
     Right,
 }
 
+// mask selecting the bit immediately following `from`, which determines the
+// side of `from` that a subprefix falls on.
+fn branch_mask<A: Afi>(from: &Prefix<A>) -> Bitmask<A> {
+    Hostmask::from(from.length())
+        & Netmask::from(
+            from.length()
+                .increment()
+                // ok to unwrap, because `from.length() < MAX_LENGTH`.
+                .unwrap(),
+        )
+}
+
 #[derive(Clone, Debug)]
 pub struct Node<A: Afi> {
     prefix: Prefix<A>,
@@ -51,97 +72,184 @@ impl<A: Afi> Node<A> {
         self.gluemap == GlueMap::ZERO
     }
 
-    pub fn add(mut self: Box<Self>, mut other: Box<Self>) -> Box<Self> {
-        match self.prefix().compare(other.prefix()) {
-            PrefixOrdering::Equal => {
-                self.gluemap |= other.gluemap;
-                if let Some(child) = other.left {
-                    self = self.add(child);
+    pub fn add(self: Box<Self>, other: Box<Self>) -> Box<Self> {
+        let mut root = Some(self);
+        Self::add_at(&mut root, other);
+        // ok to unwrap, because `add_at()` always leaves the slot occupied.
+        root.unwrap()
+    }
+
+    // add `other` to the subtree occupying `slot`, descending the tree in a
+    // loop rather than by recursion, so that deep chains of subprefixes
+    // can't overflow the stack. only merging the children of a node with an
+    // equal prefix recurses, to a depth bounded by that of `other`.
+    fn add_at(mut slot: &mut Option<Box<Self>>, mut other: Box<Self>) {
+        loop {
+            let Some(mut this) = slot.take() else {
+                *slot = Some(other);
+                return;
+            };
+            match this.prefix().compare(other.prefix()) {
+                PrefixOrdering::Equal => {
+                    this.gluemap |= other.gluemap;
+                    *slot = Some(this);
+                    [other.left, other.right]
+                        .into_iter()
+                        .flatten()
+                        .for_each(|child| Self::add_at(slot, child));
+                    return;
+                }
+                PrefixOrdering::Subprefix(common) => {
+                    // mask glue map for prefix lengths already present
+                    other.gluemap &= !this.gluemap;
+                    let direction = other.branch_direction(&common);
+                    let this = slot.insert(this);
+                    slot = match direction {
+                        Direction::Left => &mut this.left,
+                        Direction::Right => &mut this.right,
+                    };
                 }
-                if let Some(child) = other.right {
-                    self = self.add(child);
+                PrefixOrdering::Superprefix(common) => {
+                    this.gluemap &= !other.gluemap;
+                    let direction = this.branch_direction(&common);
+                    let parent = slot.insert(other);
+                    slot = match direction {
+                        Direction::Left => &mut parent.left,
+                        Direction::Right => &mut parent.right,
+                    };
+                    other = this;
                 }
-                self
-            }
-            PrefixOrdering::Subprefix(common) => {
-                // mask glue map for prefix lengths already present
-                other.gluemap &= !self.gluemap;
-                match other.branch_direction(&common) {
-                    Direction::Left => {
-                        if let Some(child) = self.left {
-                            let new_child = child.add(other);
-                            self.left = Some(new_child);
-                        } else {
-                            self.left = Some(other);
+                PrefixOrdering::Divergent(common) => {
+                    // unwrap is safe here because common < P::MAX_LENGTH
+                    let mut glue = Self::new_glue(common).boxed();
+                    match this.branch_direction(&common) {
+                        Direction::Left => {
+                            glue.left = Some(this);
+                            glue.right = Some(other);
                         }
-                    }
-                    Direction::Right => {
-                        if let Some(child) = self.right {
-                            let new_child = child.add(other);
-                            self.right = Some(new_child);
-                        } else {
-                            self.right = Some(other);
+                        Direction::Right => {
+                            glue.left = Some(other);
+                            glue.right = Some(this);
                         }
-                    }
-                };
-                self
+                    };
+                    *slot = Some(glue);
+                    return;
+                }
             }
-            PrefixOrdering::Superprefix(common) => {
-                self.gluemap &= !other.gluemap;
-                match self.branch_direction(&common) {
-                    Direction::Left => {
-                        if let Some(child) = other.left {
-                            let new_child = child.add(self);
-                            other.left = Some(new_child);
-                        } else {
-                            other.left = Some(self);
-                        }
-                    }
-                    Direction::Right => {
-                        if let Some(child) = other.right {
-                            let new_child = child.add(self);
-                            other.right = Some(new_child);
-                        } else {
-                            other.right = Some(self);
-                        }
-                    }
-                };
-                other
+        }
+    }
+
+    // add `other`, which has no children, to the subtree occupying `slot`,
+    // unless every prefix it represents is already present on the path to
+    // it. returns whether `other` was added.
+    //
+    // presence is checked on the way down, so the tree is descended only
+    // once. where `other` doesn't fall on an existing node, the rest of the
+    // insertion is handed off to `add_at()` at the point of divergence.
+    pub fn add_if_absent(mut slot: &mut Option<Box<Self>>, other: Box<Self>) -> bool {
+        loop {
+            let Some(mut this) = slot.take() else {
+                *slot = Some(other);
+                return true;
+            };
+            match this.prefix().compare(other.prefix()) {
+                PrefixOrdering::Equal | PrefixOrdering::Subprefix(_)
+                    if this.gluemap & other.gluemap == other.gluemap =>
+                {
+                    *slot = Some(this);
+                    return false;
+                }
+                PrefixOrdering::Equal => {
+                    this.gluemap |= other.gluemap;
+                    *slot = Some(this);
+                    return true;
+                }
+                PrefixOrdering::Subprefix(common) => {
+                    let direction = other.branch_direction(&common);
+                    let this = slot.insert(this);
+                    slot = match direction {
+                        Direction::Left => &mut this.left,
+                        Direction::Right => &mut this.right,
+                    };
+                }
+                PrefixOrdering::Superprefix(_) | PrefixOrdering::Divergent(_) => {
+                    *slot = Some(this);
+                    Self::add_at(slot, other);
+                    return true;
+                }
             }
-            PrefixOrdering::Divergent(common) => {
-                // unwrap is safe here because common < P::MAX_LENGTH
-                let mut glue = Self::new_glue(common).boxed();
-                match self.branch_direction(&common) {
-                    Direction::Left => {
-                        glue.left = Some(self);
-                        glue.right = Some(other);
+        }
+    }
+
+    /// Build a tree from `nodes`, which should be sorted by network address
+    /// and then prefix length.
+    ///
+    /// The subtrees along the path to the most recently added node are held
+    /// on a stack, so that each node is added close to its predecessor,
+    /// rather than by descending from the root. A subtree is merged into its
+    /// parent once a node is reached that it doesn't contain.
+    ///
+    /// The result is correct for unsorted input, but each node out of order
+    /// causes the stack to be unwound.
+    pub fn from_sorted<I>(nodes: I) -> Option<Box<Self>>
+    where
+        I: IntoIterator<Item = Box<Self>>,
+    {
+        fn unwind<A: Afi>(path: &mut Vec<Box<Node<A>>>) {
+            // ok to unwrap, because callers leave at least two subtrees on
+            // the stack.
+            let done = path.pop().unwrap();
+            let parent = path.pop().unwrap();
+            path.push(parent.add(done));
+        }
+        // the default prefix at the bottom of the stack contains every
+        // node, so it is never unwound.
+        let mut path = vec![Self::new_glue(Prefix::<A>::DEFAULT).boxed()];
+        for node in nodes {
+            loop {
+                // ok to unwrap, because the stack is never empty.
+                match path.last().unwrap().prefix().compare(node.prefix()) {
+                    PrefixOrdering::Equal => {
+                        let top = path.pop().unwrap();
+                        path.push(top.add(node));
+                        break;
                     }
-                    Direction::Right => {
-                        glue.left = Some(other);
-                        glue.right = Some(self);
+                    PrefixOrdering::Subprefix(_) => {
+                        path.push(node);
+                        break;
                     }
-                };
-                glue
+                    _ => unwind(&mut path),
+                }
             }
         }
+        while path.len() > 1 {
+            unwind(&mut path);
+        }
+        path.pop()
+            .filter(|root| !root.is_glue() || root.left.is_some() || root.right.is_some())
     }
 
-    pub fn remove(mut self: Box<Self>, other: &mut Self) -> Box<Self> {
-        if let Some(mut child) = other.left.take() {
-            self = self.remove(&mut child);
-        }
-        if let Some(mut child) = other.right.take() {
-            self = self.remove(&mut child);
-        }
+    // remove every prefix represented by the tree rooted at `other` from the
+    // tree rooted at `self`. `other` is left untouched.
+    pub fn remove(self: Box<Self>, other: &Self) -> Box<Self> {
+        other
+            .children()
+            .filter(|node| !node.is_glue())
+            .fold(self, |this, node| this.remove_node(node))
+    }
+
+    // remove the prefixes represented by the gluemap of `other` alone,
+    // ignoring its children.
+    fn remove_node(mut self: Box<Self>, other: &Self) -> Box<Self> {
         match self.prefix().compare(other.prefix()) {
             PrefixOrdering::Superprefix(_) | PrefixOrdering::Equal => {
                 // clear gluemap bits and recurse down
                 self.gluemap &= !other.gluemap;
                 if let Some(child) = self.left.take() {
-                    self.left = Some(child.remove(other));
+                    self.left = Some(child.remove_node(other));
                 };
                 if let Some(child) = self.right.take() {
-                    self.right = Some(child.remove(other));
+                    self.right = Some(child.remove_node(other));
                 };
             }
             PrefixOrdering::Subprefix(common) => {
@@ -159,12 +267,12 @@ impl<A: Afi> Node<A> {
                 match other.branch_direction(&common) {
                     Direction::Left => {
                         if let Some(child) = self.left.take() {
-                            self.left = Some(child.remove(other));
+                            self.left = Some(child.remove_node(other));
                         };
                     }
                     Direction::Right => {
                         if let Some(child) = self.right.take() {
-                            self.right = Some(child.remove(other));
+                            self.right = Some(child.remove_node(other));
                         };
                     }
                 }
@@ -174,7 +282,19 @@ impl<A: Afi> Node<A> {
         self
     }
 
-    pub fn aggregate(mut self: Box<Self>, mut mask: Option<GlueMap<A>>) -> Option<Box<Self>> {
+    pub fn aggregate(self: Box<Self>, mask: Option<GlueMap<A>>) -> Option<Box<Self>> {
+        self.aggregate_with(mask, &mut |_| ())
+    }
+
+    // aggregate, reporting each structural change to `report` as it happens.
+    pub fn aggregate_with<F>(
+        mut self: Box<Self>,
+        mut mask: Option<GlueMap<A>>,
+        report: &mut F,
+    ) -> Option<Box<Self>>
+    where
+        F: FnMut(AggregateEvent<A>),
+    {
         // set mask to zero if None given
         if mask.is_none() {
             mask = Some(GlueMap::ZERO)
@@ -182,17 +302,21 @@ impl<A: Afi> Node<A> {
         // mask is the union of gluemaps of all parent nodes.
         // if the intersection of mask and self.gluemap is not zero
         // then self represents one or more deduplicate prefixes.
-        //
+        if self.gluemap & mask.unwrap() != GlueMap::ZERO {
+            report(AggregateEvent::Deduplicate {
+                prefix: self.prefix,
+            });
+        }
         // unset mask bits in self.gluemap
         self.gluemap &= !mask.unwrap();
         // set remaining bits of self.gluemap in mask
         *mask.as_mut().unwrap() |= self.gluemap;
         // recurse child nodes
         if let Some(child) = self.left.take() {
-            self.left = child.aggregate(mask);
+            self.left = child.aggregate_with(mask, report);
         }
         if let Some(child) = self.right.take() {
-            self.right = child.aggregate(mask);
+            self.right = child.aggregate_with(mask, report);
         }
         // if both left and right child nodes exist, and have the same
         // length == self.prefix.length() + 1, then any bits set in both
@@ -215,26 +339,71 @@ impl<A: Afi> Node<A> {
                 self.gluemap |= aggr_bits;
                 // check whether any aggregation occured
                 if aggr_bits != GlueMap::ZERO {
+                    report(AggregateEvent::Merge {
+                        prefix: self.prefix,
+                    });
                     // left or right may now be unnecessary glue.
                     // also, since some aggregation into self.gluemap occured, self
                     // cannot be a glue node.
                     if let Some(child) = self.left.take() {
-                        self.left = child.clean();
+                        self.left = child.clean(report);
                     };
                     if let Some(child) = self.right.take() {
-                        self.right = child.clean();
+                        self.right = child.clean(report);
                     };
                     Some(self)
                 } else {
-                    self.clean()
+                    self.clean(report)
                 }
             }
-            _ => self.clean(),
+            _ => self.clean(report),
+        }
+    }
+
+    // check that the tree rooted at `self` is in the form produced by
+    // `aggregate()`: no gluemap bits duplicated by a parent, no bits shared
+    // by a pair of children that could be merged into their parent, and no
+    // glue nodes with fewer than two children.
+    pub fn is_aggregated(&self) -> bool {
+        self.is_aggregated_within(GlueMap::ZERO)
+    }
+
+    // as above, given the union `mask` of the gluemaps of the parents of
+    // `self`.
+    fn is_aggregated_within(&self, mask: GlueMap<A>) -> bool {
+        if self.gluemap & mask != GlueMap::ZERO {
+            return false;
+        }
+        if self.is_glue() && (self.left.is_none() || self.right.is_none()) {
+            return false;
+        }
+        if let (Ok(len), Some(l), Some(r)) =
+            (self.prefix().length().increment(), &self.left, &self.right)
+        {
+            if l.prefix().length() == len
+                && r.prefix().length() == len
+                && l.gluemap & r.gluemap != GlueMap::ZERO
+            {
+                return false;
+            }
         }
+        let mask = mask | self.gluemap;
+        [&self.left, &self.right]
+            .into_iter()
+            .flatten()
+            .all(|child| child.is_aggregated_within(mask))
     }
 
-    fn clean(self: Box<Self>) -> Option<Box<Self>> {
+    fn clean<F>(self: Box<Self>, report: &mut F) -> Option<Box<Self>>
+    where
+        F: FnMut(AggregateEvent<A>),
+    {
         if self.gluemap == GlueMap::ZERO {
+            if self.left.is_none() || self.right.is_none() {
+                report(AggregateEvent::Clean {
+                    prefix: self.prefix,
+                });
+            }
             match (&self.left, &self.right) {
                 (None, None) => None,
                 (Some(_), None) => self.left,
@@ -247,33 +416,145 @@ impl<A: Afi> Node<A> {
     }
 
     pub fn search(&self, qnode: &Self) -> Option<&Self> {
-        match self.prefix().compare(qnode.prefix()) {
-            PrefixOrdering::Equal | PrefixOrdering::Subprefix(_)
-                if self.gluemap & qnode.gluemap == qnode.gluemap =>
-            {
-                Some(self)
-            }
-            PrefixOrdering::Subprefix(common) => match qnode.branch_direction(&common) {
-                Direction::Left => {
-                    if let Some(child) = &self.left {
-                        child.search(qnode)
-                    } else {
-                        None
+        let mut node = self;
+        loop {
+            match node.prefix().compare(qnode.prefix()) {
+                PrefixOrdering::Equal | PrefixOrdering::Subprefix(_)
+                    if node.gluemap & qnode.gluemap == qnode.gluemap =>
+                {
+                    return Some(node)
+                }
+                PrefixOrdering::Subprefix(common) => {
+                    node = match qnode.branch_direction(&common) {
+                        Direction::Left => node.left.as_deref()?,
+                        Direction::Right => node.right.as_deref()?,
                     }
                 }
-                Direction::Right => {
-                    if let Some(child) = &self.right {
-                        child.search(qnode)
-                    } else {
-                        None
+                _ => return None,
+            }
+        }
+    }
+
+    // check whether any prefix represented by the aggregated tree rooted at
+    // `self` shares address space with `prefix`.
+    //
+    // every non-glue node represents all of the address space under its
+    // prefix, and aggregation ensures that every subtree contains a non-glue
+    // node.
+    pub fn overlaps(&self, prefix: &Prefix<A>) -> bool {
+        let qnode = Self::new_glue(*prefix);
+        let mut node = self;
+        loop {
+            match node.prefix().compare(prefix) {
+                PrefixOrdering::Equal | PrefixOrdering::Superprefix(_) => return true,
+                PrefixOrdering::Subprefix(_) if !node.is_glue() => return true,
+                PrefixOrdering::Subprefix(common) => {
+                    let child = match qnode.branch_direction(&common) {
+                        Direction::Left => node.left.as_deref(),
+                        Direction::Right => node.right.as_deref(),
+                    };
+                    match child {
+                        Some(child) => node = child,
+                        None => return false,
                     }
                 }
-            },
-            _ => None,
+                PrefixOrdering::Divergent(_) => return false,
+            }
+        }
+    }
+
+    // lengths `l` such that the length `l` supernet of `prefix` is contained
+    // in the tree rooted at `self`, in ascending order.
+    pub fn covering_lengths(&self, prefix: &Prefix<A>) -> Lengths<A> {
+        (self.path_gluemap(&Self::new_glue(*prefix)) & GlueMap::up_to(prefix.length())).lengths()
+    }
+
+    // check whether every prefix represented by `qnode` is contained in the
+    // tree rooted at `self`.
+    pub fn covers(&self, qnode: &Self) -> bool {
+        self.path_gluemap(qnode) & qnode.gluemap == qnode.gluemap
+    }
+
+    // check whether every prefix represented by `qnode` is contained in the
+    // tree rooted at `self`, including where those prefixes are spread over
+    // several nodes below the prefix of `qnode`.
+    pub fn covers_all(&self, qnode: &Self) -> bool {
+        match self.prefix().compare(qnode.prefix()) {
+            PrefixOrdering::Subprefix(common) => {
+                let needed = qnode.gluemap & !self.gluemap;
+                if needed == GlueMap::ZERO {
+                    return true;
+                }
+                let child = match qnode.branch_direction(&common) {
+                    Direction::Left => &self.left,
+                    Direction::Right => &self.right,
+                };
+                child
+                    .as_ref()
+                    .is_some_and(|child| child.covers_all(&Self::new(qnode.prefix, needed)))
+            }
+            PrefixOrdering::Equal => {
+                let needed = qnode.gluemap & !self.gluemap;
+                if needed == GlueMap::ZERO {
+                    return true;
+                }
+                if needed & GlueMap::up_to(qnode.prefix.length()) != GlueMap::ZERO {
+                    return false;
+                }
+                // the remaining lengths are all longer than `qnode`, and must
+                // be covered within both halves of its prefix.
+                let length = qnode.prefix.length().increment().unwrap(); // safe because a longer length remains.
+                qnode
+                    .prefix
+                    .subprefixes(length)
+                    .unwrap() // safe because `length` is longer than `qnode`.
+                    .zip([&self.left, &self.right])
+                    .all(|(half, child)| {
+                        child
+                            .as_ref()
+                            .is_some_and(|child| child.covers_all(&Self::new(half, needed)))
+                    })
+            }
+            _ => qnode.gluemap == GlueMap::ZERO,
         }
     }
 
-    fn intersect_nodes(&self, qnode: &Self) -> Option<Box<Self>> {
+    // union of the gluemaps of the nodes on the path from `self` to
+    // `qnode`, whose prefixes are equal to or contain that of `qnode`.
+    fn path_gluemap(&self, qnode: &Self) -> GlueMap<A> {
+        match self.prefix().compare(qnode.prefix()) {
+            PrefixOrdering::Equal => self.gluemap,
+            PrefixOrdering::Subprefix(common) => {
+                let child = match qnode.branch_direction(&common) {
+                    Direction::Left => &self.left,
+                    Direction::Right => &self.right,
+                };
+                match child {
+                    Some(child) => self.gluemap | child.path_gluemap(qnode),
+                    None => self.gluemap,
+                }
+            }
+            _ => GlueMap::ZERO,
+        }
+    }
+
+    // unset any lengths outside of `lengths` in the gluemaps of the tree
+    // rooted at `self`, leaving it to be cleaned up by aggregation.
+    pub fn retain_lengths(&mut self, lengths: RangeInclusive<PrefixLength<A>>) {
+        self.mask_lengths(GlueMap::from_lengths(lengths))
+    }
+
+    fn mask_lengths(&mut self, mask: GlueMap<A>) {
+        self.gluemap &= mask;
+        if let Some(child) = &mut self.left {
+            child.mask_lengths(mask);
+        }
+        if let Some(child) = &mut self.right {
+            child.mask_lengths(mask);
+        }
+    }
+
+    pub fn intersect_nodes(&self, qnode: &Self) -> Option<Box<Self>> {
         match self.prefix().compare(qnode.prefix()) {
             PrefixOrdering::Divergent(_) => None,
             cmp => {
@@ -298,28 +579,108 @@ impl<A: Afi> Node<A> {
         }
     }
 
+    // the part of the tree rooted at `self` that lies within `space`.
+    //
+    // descends only along the path to `space`, collecting the gluemap bits
+    // of covering nodes at lengths no shorter than `space` onto a new node
+    // at `space`, and then clones the subtree found within `space`. the
+    // result is not aggregated.
+    pub fn trim_to(&self, space: &Prefix<A>) -> Option<Box<Self>> {
+        let qnode = Self::new_glue(*space);
+        let mask = GlueMap::from_lengths(space.length()..=PrefixLength::MAX);
+        let mut gluemap = GlueMap::ZERO;
+        let mut next = Some(self);
+        let subtree = loop {
+            let node = match next {
+                Some(node) => node,
+                None => break None,
+            };
+            match node.prefix().compare(space) {
+                PrefixOrdering::Subprefix(common) => {
+                    gluemap |= node.gluemap & mask;
+                    next = match qnode.branch_direction(&common) {
+                        Direction::Left => node.left.as_deref(),
+                        Direction::Right => node.right.as_deref(),
+                    };
+                }
+                PrefixOrdering::Equal | PrefixOrdering::Superprefix(_) => {
+                    break Some(node.clone().boxed())
+                }
+                PrefixOrdering::Divergent(_) => break None,
+            }
+        };
+        let root = Self::new(*space, gluemap).boxed();
+        match subtree {
+            Some(subtree) => Some(root.add(subtree)),
+            None if root.is_glue() => None,
+            None => Some(root),
+        }
+    }
+
+    // number of prefixes represented by the gluemap of this node alone,
+    // saturating at `u128::MAX`.
+    pub fn prefix_count(&self) -> u128 {
+        let length: usize = self.prefix.length().into_primitive().into();
+        self.ranges()
+            .flat_map(|range| {
+                let lower: usize = range.lower().into_primitive().into();
+                let upper: usize = range.upper().into_primitive().into();
+                lower..=upper
+            })
+            .map(|l| 1u128.checked_shl((l - length) as u32).unwrap_or(u128::MAX))
+            .fold(0, u128::saturating_add)
+    }
+
+    pub fn depth(&self) -> usize {
+        [&self.left, &self.right]
+            .into_iter()
+            .flatten()
+            .map(|child| child.depth() + 1)
+            .max()
+            .unwrap_or_default()
+    }
+
     fn branch_direction(&self, from: &Prefix<A>) -> Direction {
-        let mask = Hostmask::from(from.length())
-            & Netmask::from(
-                from.length()
-                    .increment()
-                    // ok to unwrap, because `from.length() < MAX_LENGTH`.
-                    .unwrap(),
-            );
-        if (self.prefix().network() & mask).is_unspecified() {
+        if (self.prefix().network() & branch_mask(from)).is_unspecified() {
             Direction::Left
         } else {
             Direction::Right
         }
     }
 
-    pub fn ranges(&self) -> Ranges<A> {
+    pub fn lengths(&self) -> Lengths<A> {
+        self.gluemap.lengths()
+    }
+
+    // the shortest length set in the gluemap of this node, if any.
+    pub fn min_length(&self) -> Option<PrefixLength<A>> {
+        self.gluemap
+            .next_range(PrefixLength::MIN)
+            .map(|range| *range.start())
+    }
+
+    // the longest length set in the gluemap of this node, if any.
+    pub fn max_length(&self) -> Option<PrefixLength<A>> {
+        self.gluemap
+            .prev_range(PrefixLength::MAX)
+            .map(|range| *range.end())
+    }
+
+    pub fn ranges(&self) -> Ranges<'_, A> {
         self.into()
     }
 
     pub fn children(&self) -> Children<'_, A> {
         self.into()
     }
+
+    pub fn into_ranges(self: Box<Self>) -> IntoRanges<A> {
+        self.into()
+    }
+
+    pub fn into_children(self: Box<Self>) -> IntoChildren<A> {
+        self.into()
+    }
 }
 
 #[cfg(test)]
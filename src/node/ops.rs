@@ -1,4 +1,5 @@
-use std::ops::{BitAnd, BitOr, Sub};
+use alloc::boxed::Box;
+use core::ops::{BitAnd, BitOr, Sub};
 
 use ip::Afi;
 
@@ -10,10 +11,8 @@ impl<A: Afi> PartialEq for Node<A> {
     }
 }
 
-impl<A: Afi> BitAnd for Box<Node<A>> {
-    type Output = Option<Self>;
-
-    fn bitand(self, rhs: Self) -> Self::Output {
+impl<A: Afi> Node<A> {
+    pub fn intersect(&self, rhs: &Self) -> Option<Box<Self>> {
         self.children()
             .fold(None, |root, node| match rhs.intersect_nodes(node) {
                 Some(new) => {
@@ -28,6 +27,14 @@ impl<A: Afi> BitAnd for Box<Node<A>> {
     }
 }
 
+impl<A: Afi> BitAnd for Box<Node<A>> {
+    type Output = Option<Self>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersect(&rhs)
+    }
+}
+
 impl<A: Afi> BitOr for Box<Node<A>> {
     type Output = Option<Self>;
 
@@ -39,7 +46,7 @@ impl<A: Afi> BitOr for Box<Node<A>> {
 impl<A: Afi> Sub for Box<Node<A>> {
     type Output = Option<Self>;
 
-    fn sub(self, mut rhs: Self) -> Self::Output {
-        Some(self.remove(&mut rhs))
+    fn sub(self, rhs: Self) -> Self::Output {
+        Some(self.remove(&rhs))
     }
 }
@@ -0,0 +1,51 @@
+use ip::{concrete::PrefixLength, Afi};
+
+use super::{GlueMap, Node};
+
+impl<A: Afi> Node<A> {
+    /// Split the tree rooted at `self` into at least `n` disjoint pieces,
+    /// if it has enough nodes.
+    ///
+    /// The tree is expanded breadth first, one level at a time. The first
+    /// element of the returned pair holds the subtrees at the frontier of
+    /// the expansion. The second holds the expanded nodes above them, each
+    /// of which must be treated as a single node without its children.
+    pub fn split(&self, n: usize) -> (Vec<&Self>, Vec<&Self>) {
+        let mut frontier = vec![self];
+        let mut expanded = Vec::new();
+        while frontier.len() < n {
+            let mut next = Vec::with_capacity(frontier.len() * 2);
+            let mut progress = false;
+            frontier.into_iter().for_each(|node| {
+                if node.left.is_none() && node.right.is_none() {
+                    next.push(node);
+                } else {
+                    progress = true;
+                    next.extend(node.left.as_deref());
+                    next.extend(node.right.as_deref());
+                    expanded.push(node);
+                }
+            });
+            frontier = next;
+            if !progress {
+                break;
+            }
+        }
+        (frontier, expanded)
+    }
+
+    /// Copy `self` without its children.
+    pub fn detached(&self) -> Box<Self> {
+        Self::new(self.prefix, self.gluemap).boxed()
+    }
+
+    /// Get the subtree containing the prefixes of the tree rooted at `self`
+    /// that are covered by the prefix of `region`.
+    pub fn restrict_to(&self, region: &Self) -> Option<Box<Self>> {
+        let all = Self::new(
+            region.prefix,
+            GlueMap::from_lengths(region.prefix.length()..=PrefixLength::MAX),
+        );
+        self.intersect_nodes(&all)
+    }
+}
@@ -95,7 +95,7 @@ mod new_ipv4_singleton {
     #[test]
     fn becomes_glue_after_removal() -> TestResult {
         let n = setup();
-        assert!(is_glue(&n.remove(&mut "192.0.2.0/24".parse().unwrap())));
+        assert!(is_glue(&n.remove(&"192.0.2.0/24".parse().unwrap())));
         Ok(())
     }
 
@@ -165,7 +165,7 @@ mod new_ipv4_singleton {
         #[test]
         fn becomes_glue_after_removal() -> TestResult {
             let n = setup();
-            assert!(is_glue(&n.remove(&mut "192.0.2.0/24".parse().unwrap())));
+            assert!(is_glue(&n.remove(&"192.0.2.0/24".parse().unwrap())));
             Ok(())
         }
     }
@@ -250,7 +250,7 @@ mod new_ipv4_singleton {
         #[test]
         fn is_unchanged_after_subprefix_removal() -> TestResult {
             let n = setup();
-            let m = n.clone().remove(&mut "192.0.2.0/24".parse().unwrap());
+            let m = n.clone().remove(&"192.0.2.0/24".parse().unwrap());
             println!("{:#?}", m);
             assert_eq!(m, n);
             Ok(())
@@ -309,6 +309,18 @@ mod new_ipv4_singleton {
             Ok(())
         }
 
+        #[test]
+        fn children_stay_exhausted() -> TestResult {
+            let n = setup();
+            let mut children = n.children();
+            assert_eq!(children.by_ref().count(), n.children().count());
+            for _ in 0..3 {
+                assert!(children.next().is_none());
+                assert!(children.next_back().is_none());
+            }
+            Ok(())
+        }
+
         mod after_aggregation {
             use super::*;
 
@@ -653,3 +665,165 @@ mod new_ipv6_singleton {
         }
     }
 }
+
+mod singleton_gluemap {
+    use ip::concrete::PrefixLength;
+
+    use super::*;
+
+    #[test]
+    fn ipv4_singleton_has_one_bit_set() -> TestResult {
+        for length in [0, 24, 32] {
+            let gluemap = GlueMap::<Ipv4>::singleton(PrefixLength::from_primitive(length)?);
+            assert_eq!(gluemap.count_ones(), 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_singleton_has_one_bit_set() -> TestResult {
+        for length in [0, 24, 128] {
+            let gluemap = GlueMap::<Ipv6>::singleton(PrefixLength::from_primitive(length)?);
+            assert_eq!(gluemap.count_ones(), 1);
+        }
+        Ok(())
+    }
+}
+
+mod gluemap_count_ones {
+    use ip::concrete::PrefixRange;
+
+    use super::*;
+
+    fn count_ones<A: Afi>(range: &str) -> TestResult<usize> {
+        Ok(GlueMap::from(range.parse::<PrefixRange<A>>()?).count_ones())
+    }
+
+    #[test]
+    fn zero_has_no_bits_set() {
+        assert_eq!(GlueMap::<Ipv4>::ZERO.count_ones(), 0);
+        assert_eq!(GlueMap::<Ipv6>::ZERO.count_ones(), 0);
+    }
+
+    #[test]
+    fn ipv4_ranges_count_distinct_lengths() -> TestResult {
+        for (range, expected) in [
+            // single length
+            ("192.0.2.0/24,24,24", 1),
+            ("192.0.2.0/24,28,28", 1),
+            // partial span
+            ("10.0.0.0/8,16,24", 9),
+            // full-length span
+            ("0.0.0.0/0,0,32", 33),
+            // host-only
+            ("192.0.2.1/32,32,32", 1),
+            ("192.0.2.0/24,32,32", 1),
+        ] {
+            assert_eq!(count_ones::<Ipv4>(range)?, expected, "{range}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_ranges_count_distinct_lengths() -> TestResult {
+        for (range, expected) in [
+            // single length
+            ("2001:db8::/32,32,32", 1),
+            ("2001:db8::/32,48,48", 1),
+            // partial span
+            ("2001:db8::/32,48,64", 17),
+            // full-length span
+            ("::/0,0,128", 129),
+            // host-only
+            ("2001:db8::1/128,128,128", 1),
+            ("2001:db8::/32,128,128", 1),
+        ] {
+            assert_eq!(count_ones::<Ipv6>(range)?, expected, "{range}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn union_counts_each_length_once() -> TestResult {
+        let a = GlueMap::from("10.0.0.0/8,16,24".parse::<PrefixRange<Ipv4>>()?);
+        let b = GlueMap::from("10.0.0.0/8,20,28".parse::<PrefixRange<Ipv4>>()?);
+        assert_eq!((a | b).count_ones(), 13);
+        assert_eq!((a & b).count_ones(), 5);
+        Ok(())
+    }
+}
+
+mod children_order {
+    use core::ptr;
+
+    use super::*;
+
+    fn setup() -> Box<Node<Ipv4>> {
+        [
+            "10.0.0.0/8",
+            "10.1.0.0/16",
+            "10.1.2.0/24",
+            "10.2.0.0/16",
+            "10.3.3.0/24",
+            "192.0.2.0/24",
+            "192.0.2.128/25",
+            "198.51.100.0/24",
+        ]
+        .into_iter()
+        .map(|s| s.parse::<Box<Node<_>>>().unwrap())
+        .reduce(|root, node| root.add(node))
+        .unwrap()
+    }
+
+    // reference pre-order traversal, visiting right subtrees before left.
+    fn pre_order<'a, A: Afi>(node: &'a Node<A>, acc: &mut Vec<&'a Node<A>>) {
+        acc.push(node);
+        if let Some(right) = node.right.as_deref() {
+            pre_order(right, acc);
+        }
+        if let Some(left) = node.left.as_deref() {
+            pre_order(left, acc);
+        }
+    }
+
+    fn expected(n: &Node<Ipv4>) -> Vec<&Node<Ipv4>> {
+        let mut acc = Vec::new();
+        pre_order(n, &mut acc);
+        acc
+    }
+
+    fn same_nodes(a: &[&Node<Ipv4>], b: &[&Node<Ipv4>]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| ptr::eq(*x, *y))
+    }
+
+    #[test]
+    fn forward_is_pre_order() {
+        let n = setup();
+        assert!(same_nodes(&n.children().collect::<Vec<_>>(), &expected(&n)));
+    }
+
+    #[test]
+    fn backward_is_reverse_pre_order() {
+        let n = setup();
+        let mut expected = expected(&n);
+        expected.reverse();
+        assert!(same_nodes(
+            &n.children().rev().collect::<Vec<_>>(),
+            &expected
+        ));
+    }
+
+    #[test]
+    fn ends_meet_without_repeating_nodes() {
+        let n = setup();
+        let expected = expected(&n);
+        for split in 0..=expected.len() {
+            let mut children = n.children();
+            let mut front: Vec<_> = children.by_ref().take(split).collect();
+            let mut back: Vec<_> = children.rev().collect();
+            back.reverse();
+            front.extend(back);
+            assert!(same_nodes(&front, &expected), "split at {split}");
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+
+use ip::{concrete, traits::PrefixRange as _, Any, Ipv4, Ipv6, Prefix, PrefixRange};
+
+use crate::error::Error;
+
+use super::{PrefixSet, Ranges};
+
+/// A [`PrefixSet<A>`] of either address family, with the family selected at
+/// run-time.
+///
+/// Items of the wrong address family are rejected with
+/// [`Error::AfiMismatch`], rather than being silently ignored.
+///
+/// ``` rust
+/// # use ip::{concrete::Afi, Any, Prefix};
+/// # use prefixset::{set::AnyPrefixSet, Error};
+/// # fn main() -> Result<(), Error> {
+/// let mut set = AnyPrefixSet::new(Afi::Ipv6);
+/// set.insert("2001:db8::/32".parse::<Prefix<Any>>()?)?;
+/// assert!(set.contains("2001:db8::/32".parse()?)?);
+/// assert!(set.insert("192.0.2.0/24".parse::<Prefix<Any>>()?).is_err());
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnyPrefixSet {
+    /// A set of IPv4 prefixes.
+    V4(PrefixSet<Ipv4>),
+    /// A set of IPv6 prefixes.
+    V6(PrefixSet<Ipv6>),
+}
+
+impl AnyPrefixSet {
+    /// Construct a new, empty [`AnyPrefixSet`] for address family `afi`.
+    pub fn new(afi: concrete::Afi) -> Self {
+        match afi {
+            concrete::Afi::Ipv4 => Self::V4(PrefixSet::new()),
+            concrete::Afi::Ipv6 => Self::V6(PrefixSet::new()),
+        }
+    }
+
+    /// Get the address family of `self`.
+    pub fn afi(&self) -> concrete::Afi {
+        match self {
+            Self::V4(_) => concrete::Afi::Ipv4,
+            Self::V6(_) => concrete::Afi::Ipv6,
+        }
+    }
+
+    /// Insert a prefix or prefix range into `self`.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::AfiMismatch`] is returned if the address family of `item`
+    /// differs from that of `self`.
+    pub fn insert<T>(&mut self, item: T) -> Result<&mut Self, Error>
+    where
+        T: Into<PrefixRange<Any>>,
+    {
+        match (&mut *self, item.into()) {
+            (Self::V4(s), PrefixRange::<Any>::Ipv4(range)) => _ = s.insert(range),
+            (Self::V6(s), PrefixRange::<Any>::Ipv6(range)) => _ = s.insert(range),
+            (_, range) => return Err(self.mismatch(range.prefix())),
+        };
+        Ok(self)
+    }
+
+    /// Test whether `prefix` is contained in `self`.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::AfiMismatch`] is returned if the address family of
+    /// `prefix` differs from that of `self`.
+    pub fn contains(&self, prefix: Prefix<Any>) -> Result<bool, Error> {
+        match (self, prefix) {
+            (Self::V4(s), Prefix::<Any>::Ipv4(prefix)) => Ok(s.contains(prefix)),
+            (Self::V6(s), Prefix::<Any>::Ipv6(prefix)) => Ok(s.contains(prefix)),
+            (_, prefix) => Err(self.mismatch(prefix)),
+        }
+    }
+
+    /// Get an iterator over the prefix ranges contained in `self`.
+    pub fn ranges(&self) -> AnyRanges<'_> {
+        match self {
+            Self::V4(s) => AnyRanges::V4(s.ranges()),
+            Self::V6(s) => AnyRanges::V6(s.ranges()),
+        }
+    }
+
+    /// Construct a pair of [`PrefixSet<A>`]s from an iterator of prefixes or
+    /// prefix ranges of mixed address families, routing each item to the set
+    /// of its family.
+    ///
+    /// ``` rust
+    /// # use ip::{Any, Prefix};
+    /// # use prefixset::{set::AnyPrefixSet, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let (ipv4, ipv6) = AnyPrefixSet::partition(
+    ///     ["192.0.2.0/24", "2001:db8::/32"]
+    ///         .into_iter()
+    ///         .map(|s| s.parse::<Prefix<Any>>())
+    ///         .collect::<Result<Vec<_>, _>>()?,
+    /// );
+    /// assert_eq!(ipv4.len(), 1);
+    /// assert_eq!(ipv6.len(), 1);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn partition<I, T>(iter: I) -> (PrefixSet<Ipv4>, PrefixSet<Ipv6>)
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<PrefixRange<Any>>,
+    {
+        let (mut ipv4, mut ipv6) = (Vec::new(), Vec::new());
+        iter.into_iter().for_each(|item| match item.into() {
+            PrefixRange::<Any>::Ipv4(range) => ipv4.push(range),
+            PrefixRange::<Any>::Ipv6(range) => ipv6.push(range),
+        });
+        (ipv4.into_iter().collect(), ipv6.into_iter().collect())
+    }
+
+    fn mismatch(&self, found: Prefix<Any>) -> Error {
+        Error::AfiMismatch {
+            expected: self.afi(),
+            found: match found {
+                Prefix::<Any>::Ipv4(_) => concrete::Afi::Ipv4,
+                Prefix::<Any>::Ipv6(_) => concrete::Afi::Ipv6,
+            },
+        }
+    }
+}
+
+impl From<PrefixSet<Ipv4>> for AnyPrefixSet {
+    fn from(s: PrefixSet<Ipv4>) -> Self {
+        Self::V4(s)
+    }
+}
+
+impl From<PrefixSet<Ipv6>> for AnyPrefixSet {
+    fn from(s: PrefixSet<Ipv6>) -> Self {
+        Self::V6(s)
+    }
+}
+
+/// Non-consuming iterator returned by [`AnyPrefixSet::ranges()`].
+#[derive(Debug)]
+pub enum AnyRanges<'a> {
+    /// Ranges of an IPv4 set.
+    V4(Ranges<'a, Ipv4>),
+    /// Ranges of an IPv6 set.
+    V6(Ranges<'a, Ipv6>),
+}
+
+impl Iterator for AnyRanges<'_> {
+    type Item = PrefixRange<Any>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::V4(ranges) => ranges.next().map(Self::Item::from),
+            Self::V6(ranges) => ranges.next().map(Self::Item::from),
+        }
+    }
+}
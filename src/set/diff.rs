@@ -0,0 +1,123 @@
+use core::fmt;
+
+use ip::Afi;
+
+use super::{PrefixSet, Ranges};
+
+/// Summary of the changes between two [`PrefixSet<A>`]s, returned by
+/// [`PrefixSet::change_summary()`].
+///
+/// Counts saturate at [`u128::MAX`], which can only be reached by IPv6 sets
+/// covering (almost) every possible prefix.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ChangeSummary {
+    /// The number of prefixes present in the current set but not in the
+    /// previous one.
+    pub added_prefixes: u128,
+    /// The number of prefixes present in the previous set but not in the
+    /// current one.
+    pub removed_prefixes: u128,
+    /// The number of prefixes present in both sets.
+    pub unchanged_prefixes: u128,
+}
+
+/// The prefixes added and removed between two [`PrefixSet<A>`]s, returned
+/// by [`PrefixSet::diff()`].
+///
+/// The [`Display`](fmt::Display) implementation prints one line per range,
+/// prefixed with `+` for added ranges and `-` for removed ranges.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetDiff<A: Afi> {
+    added: PrefixSet<A>,
+    removed: PrefixSet<A>,
+}
+
+impl<A: Afi> SetDiff<A> {
+    /// Get an iterator over the prefix ranges present in the new set but not
+    /// in the old one.
+    pub fn added(&self) -> Ranges<'_, A> {
+        self.added.ranges()
+    }
+
+    /// Get an iterator over the prefix ranges present in the old set but not
+    /// in the new one.
+    pub fn removed(&self) -> Ranges<'_, A> {
+        self.removed.ranges()
+    }
+
+    /// Test whether the two sets were equal.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl<A: Afi> fmt::Display for SetDiff<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.added()
+            .try_for_each(|range| writeln!(f, "+ {range}"))?;
+        self.removed()
+            .try_for_each(|range| writeln!(f, "- {range}"))
+    }
+}
+
+impl<A: Afi> PrefixSet<A> {
+    /// Compute the prefix ranges added and removed in going from `self` to
+    /// `other`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let old = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let new = PrefixSet::new()
+    ///     .insert("198.51.100.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let diff = old.diff(&new);
+    /// assert_eq!(
+    ///     diff.to_string(),
+    ///     "+ 198.51.100.0/24^24-24\n- 192.0.2.0/24^24-24\n",
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn diff(&self, other: &Self) -> SetDiff<A> {
+        SetDiff {
+            added: other.clone() - self.clone(),
+            removed: self.clone() - other.clone(),
+        }
+    }
+
+    /// Summarize the changes from `previous` to `self`, as counts of added,
+    /// removed and unchanged prefixes.
+    ///
+    /// The counts are computed analytically from the ranges in `self -
+    /// previous`, `previous - self` and `self & previous`, without
+    /// enumerating the prefixes themselves.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let previous = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,25".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let current = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,24,25".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let summary = current.change_summary(&previous);
+    /// assert_eq!(summary.added_prefixes, 1);
+    /// assert_eq!(summary.removed_prefixes, 0);
+    /// assert_eq!(summary.unchanged_prefixes, 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn change_summary(&self, previous: &Self) -> ChangeSummary {
+        ChangeSummary {
+            added_prefixes: (self - previous).count_prefixes(),
+            removed_prefixes: (previous - self).count_prefixes(),
+            unchanged_prefixes: (self & previous).count_prefixes(),
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+#[cfg(feature = "std")]
+use ip::concrete;
+use ip::{
+    concrete::{Prefix, PrefixLength, PrefixRange},
+    traits::Prefix as _,
+    Afi,
+};
+
+use super::PrefixSet;
+
+// a prefix range, displayed as `prefix,lower,upper`.
+//
+// this is the form accepted by `PrefixRange::from_str()`, unlike the
+// `prefix^lower-upper` form written by `PrefixRange`'s own `Display`
+// implementation.
+struct Canonical<A: Afi>(PrefixRange<A>);
+
+impl<A: Afi> fmt::Display for Canonical<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{}",
+            self.0.prefix(),
+            self.0.lower(),
+            self.0.upper()
+        )
+    }
+}
+
+// write each item yielded by `iter` on its own line, with no trailing newline.
+fn write_lines<I>(f: &mut fmt::Formatter<'_>, iter: I) -> fmt::Result
+where
+    I: IntoIterator,
+    I::Item: fmt::Display,
+{
+    iter.into_iter().enumerate().try_for_each(|(i, item)| {
+        if i > 0 {
+            writeln!(f)?;
+        }
+        write!(f, "{}", item)
+    })
+}
+
+impl<A: Afi> fmt::Display for PrefixSet<A> {
+    /// Format `self` as a newline separated list of prefix ranges, in the
+    /// form `prefix,lower,upper`, as accepted by [`PrefixSet`]'s
+    /// [`FromStr`](core::str::FromStr) implementation.
+    ///
+    /// The alternate form (`{:#}`) lists the individual prefixes in `self`
+    /// instead.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(format!("{}", set), "192.0.2.0/24,25,25");
+    /// assert_eq!(format!("{:#}", set), "192.0.2.0/25\n192.0.2.128/25");
+    /// #     Ok(())
+    /// # }
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write_lines(f, self.prefixes())
+        } else {
+            write_lines(f, self.ranges().map(Canonical))
+        }
+    }
+}
+
+/// A prefix filter entry, as written by [`bgpq3`] and accepted by most
+/// router configuration syntaxes.
+///
+/// The `ge` and `le` bounds are omitted when they are equal to the length of
+/// `prefix`, so an entry matching only its base prefix has neither.
+///
+/// [`bgpq3`]: https://github.com/snar/bgpq3
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PermitEntry<A: Afi> {
+    /// The base prefix of the entry.
+    pub prefix: Prefix<A>,
+    /// The shortest prefix length matched, if longer than `prefix`.
+    pub ge: Option<PrefixLength<A>>,
+    /// The longest prefix length matched, if longer than `prefix`.
+    pub le: Option<PrefixLength<A>>,
+}
+
+impl<A: Afi> From<PrefixRange<A>> for PermitEntry<A> {
+    fn from(range: PrefixRange<A>) -> Self {
+        let prefix = range.prefix();
+        let longer = |length| Some(length).filter(|&length| length > prefix.length());
+        Self {
+            prefix,
+            ge: longer(range.lower()),
+            le: longer(range.upper()),
+        }
+    }
+}
+
+impl<A: Afi> PrefixSet<A> {
+    /// Get an iterator over the ranges in `self` as [`PermitEntry`]s,
+    /// ordered by base prefix.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24 198.51.100.0/24,24,25".parse()?;
+    /// let entries: Vec<_> = set.permit_entries().collect();
+    /// assert_eq!(entries[0].ge, None);
+    /// assert_eq!(entries[0].le, None);
+    /// assert_eq!(entries[1].ge, None);
+    /// assert_eq!(entries[1].le, Some(25.try_into()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn permit_entries(&self) -> impl Iterator<Item = PermitEntry<A>> {
+        sorted_ranges(self).into_iter().map(PermitEntry::from)
+    }
+}
+
+/// Router configuration syntaxes supported by
+/// [`PrefixSet::write_prefix_list()`].
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vendor {
+    /// Cisco IOS style `ip prefix-list` statements.
+    Cisco,
+    /// A Juniper JunOS `route-filter-list`.
+    Juniper,
+    /// A BIRD prefix set definition.
+    Bird,
+}
+
+#[cfg(feature = "std")]
+impl<A: Afi> PrefixSet<A> {
+    /// Write `self` to `w` as a prefix filter named `name`, in the
+    /// configuration syntax of `vendor`, in the manner of [`bgpq3`].
+    ///
+    /// One filter entry is written for each range in `self`, ordered by
+    /// base prefix, with a range containing only its base prefix written as
+    /// an exact match.
+    ///
+    /// An empty Cisco prefix-list would permit everything, so a single
+    /// entry denying the default prefix is written instead.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{set::Vendor, PrefixSet};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25,27,27".parse::<PrefixRange<Ipv4>>()?)
+    ///     .insert("192.0.2.128/26,26,27".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut buf = Vec::new();
+    /// set.write_prefix_list(&mut buf, "RS-EXAMPLE", Vendor::Cisco)?;
+    /// assert_eq!(
+    ///     String::from_utf8(buf)?,
+    ///     "ip prefix-list RS-EXAMPLE permit 192.0.2.0/25 ge 27 le 27\n\
+    ///      ip prefix-list RS-EXAMPLE permit 192.0.2.128/26 le 27\n",
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`bgpq3`]: https://github.com/snar/bgpq3
+    pub fn write_prefix_list<W: Write>(
+        &self,
+        w: &mut W,
+        name: &str,
+        vendor: Vendor,
+    ) -> io::Result<()> {
+        match vendor {
+            Vendor::Cisco => {
+                let keyword = match A::as_afi() {
+                    concrete::Afi::Ipv4 => "ip",
+                    concrete::Afi::Ipv6 => "ipv6",
+                };
+                if self.is_empty() {
+                    return writeln!(
+                        w,
+                        "{} prefix-list {} deny {}",
+                        keyword,
+                        name,
+                        Prefix::<A>::DEFAULT
+                    );
+                }
+                self.permit_entries().try_for_each(|entry| {
+                    write!(
+                        w,
+                        "{} prefix-list {} permit {}",
+                        keyword, name, entry.prefix
+                    )?;
+                    if let Some(ge) = entry.ge {
+                        write!(w, " ge {}", ge)?;
+                    }
+                    if let Some(le) = entry.le {
+                        write!(w, " le {}", le)?;
+                    }
+                    writeln!(w)
+                })
+            }
+            Vendor::Juniper => {
+                writeln!(
+                    w,
+                    "policy-options {{\nreplace:\n route-filter-list {} {{",
+                    name
+                )?;
+                sorted_ranges(self).into_iter().try_for_each(|range| {
+                    write!(w, "    {}", range.prefix())?;
+                    if is_exact(&range) {
+                        writeln!(w, " exact;")
+                    } else if range.lower() == range.prefix().length() {
+                        writeln!(w, " upto /{};", range.upper())
+                    } else {
+                        writeln!(
+                            w,
+                            " prefix-length-range /{}-/{};",
+                            range.lower(),
+                            range.upper()
+                        )
+                    }
+                })?;
+                writeln!(w, " }}\n}}")
+            }
+            Vendor::Bird => {
+                writeln!(w, "{} = [", name)?;
+                let mut ranges = sorted_ranges(self).into_iter().peekable();
+                while let Some(range) = ranges.next() {
+                    write!(w, "    {}", range.prefix())?;
+                    if !is_exact(&range) {
+                        write!(w, "{{{},{}}}", range.lower(), range.upper())?;
+                    }
+                    if ranges.peek().is_some() {
+                        write!(w, ",")?;
+                    }
+                    writeln!(w)?;
+                }
+                writeln!(w, "];")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: Afi> PrefixSet<A> {
+    /// Write the ranges in `self` to `w` as JSON lines, ordered by base
+    /// prefix.
+    ///
+    /// Each range is written as an object of the form
+    /// `{"prefix":"192.0.2.0/24","ge":25,"le":26}`, with the `ge` and `le`
+    /// fields omitted when they are equal to the length of the base prefix.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix, PrefixRange};
+    /// # use prefixset::PrefixSet;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("198.51.100.0/24,24,25".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut buf = Vec::new();
+    /// set.write_jsonl(&mut buf)?;
+    /// assert_eq!(
+    ///     String::from_utf8(buf)?,
+    ///     "{\"prefix\":\"192.0.2.0/24\"}\n\
+    ///      {\"prefix\":\"198.51.100.0/24\",\"le\":25}\n",
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_jsonl<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.permit_entries().try_for_each(|entry| {
+            write!(w, "{{\"prefix\":\"{}\"", entry.prefix)?;
+            if let Some(ge) = entry.ge {
+                write!(w, ",\"ge\":{}", ge)?;
+            }
+            if let Some(le) = entry.le {
+                write!(w, ",\"le\":{}", le)?;
+            }
+            writeln!(w, "}}")
+        })
+    }
+
+    /// Write the prefixes in `self` to `w` as RPSL `mp-members` attributes,
+    /// suitable for inclusion in a `route-set` object.
+    ///
+    /// Each range is expanded to its individual prefixes, with the ranges
+    /// ordered by base prefix.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::PrefixSet;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,24,25".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut buf = Vec::new();
+    /// set.write_rpsl_members(&mut buf)?;
+    /// assert_eq!(
+    ///     String::from_utf8(buf)?,
+    ///     "mp-members: 192.0.2.0/24\n\
+    ///      mp-members: 192.0.2.0/25\n\
+    ///      mp-members: 192.0.2.128/25\n",
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write_rpsl_members<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        sorted_ranges(self)
+            .into_iter()
+            .flatten()
+            .try_for_each(|prefix| writeln!(w, "mp-members: {}", prefix))
+    }
+}
+
+// collect the ranges in `s`, ordered by network address and then length.
+fn sorted_ranges<A: Afi>(s: &PrefixSet<A>) -> Vec<PrefixRange<A>> {
+    let mut ranges: Vec<_> = s.ranges().collect();
+    ranges.sort_by_key(|range| {
+        let prefix = range.prefix();
+        (prefix.network(), prefix.length(), range.lower())
+    });
+    ranges
+}
+
+// check whether `range` contains only its base prefix.
+#[cfg(feature = "std")]
+fn is_exact<A: Afi>(range: &PrefixRange<A>) -> bool {
+    range.lower() == range.prefix().length() && range.upper() == range.prefix().length()
+}
@@ -0,0 +1,58 @@
+use core::mem;
+
+use ip::Afi;
+
+use crate::node::{AggregateEvent, Node};
+
+use super::PrefixSet;
+
+impl<A: Afi> PrefixSet<A> {
+    /// Insert items into `self` from an iterator, as by
+    /// [`PrefixSet::insert_from()`], calling `f` with an
+    /// [`AggregateEvent<A>`] for each structural change made while
+    /// aggregating the result.
+    ///
+    /// The tree is aggregated only once, after every item has been inserted,
+    /// so this can be used to profile the aggregation of sets built from
+    /// very large inputs.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{set::AggregateEvent, Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut merged = Vec::new();
+    /// let set = PrefixSet::<Ipv4>::new()
+    ///     .insert_from_instrumented(
+    ///         ["192.0.2.0/25", "192.0.2.128/25"]
+    ///             .into_iter()
+    ///             .map(|s| s.parse::<Prefix<Ipv4>>())
+    ///             .collect::<Result<Vec<_>, _>>()?,
+    ///         |event| {
+    ///             if let AggregateEvent::Merge { prefix } = event {
+    ///                 merged.push(prefix);
+    ///             }
+    ///         },
+    ///     )
+    ///     .to_owned();
+    /// assert_eq!(merged, vec!["192.0.2.0/24".parse::<Prefix<Ipv4>>()?]);
+    /// assert_eq!(set.len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn insert_from_instrumented<I, T, F>(&mut self, iter: I, mut f: F) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Node<A>>,
+        F: FnMut(AggregateEvent<A>),
+    {
+        iter.into_iter().for_each(|item| {
+            self.insert_node(item.into().boxed());
+        });
+        self.ranges_cache = None;
+        if let Some(root) = mem::take(&mut self.root) {
+            self.root = root.aggregate_with(None, &mut f)
+        }
+        self.prefix_count = self.walk_prefix_count();
+        self
+    }
+}
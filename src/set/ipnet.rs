@@ -0,0 +1,105 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use ip::{
+    concrete::{self, Address, Prefix, PrefixLength},
+    traits::Prefix as _,
+    Afi, Ipv4, Ipv6,
+};
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+
+use crate::error::Error;
+use crate::node::Node;
+
+use super::{PrefixSet, Prefixes};
+
+/// Iterator returned by [`PrefixSet<A>::ipnets()`].
+///
+/// Yields each prefix in the set as an [`IpNet`].
+#[derive(Debug)]
+pub struct IpNets<'a, A: Afi> {
+    prefixes: Prefixes<'a, A>,
+}
+
+impl<A: Afi> PrefixSet<A> {
+    /// Get an iterator over the prefixes contained in `self`, converted to
+    /// [`IpNet`]s.
+    ///
+    /// The prefixes are yielded in the same order as by
+    /// [`prefixes()`](Self::prefixes).
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let nets: Vec<ipnet::IpNet> = set.ipnets().collect();
+    /// assert_eq!(nets, vec!["192.0.2.0/24".parse::<ipnet::IpNet>().unwrap()]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn ipnets(&self) -> IpNets<'_, A> {
+        IpNets {
+            prefixes: self.prefixes(),
+        }
+    }
+}
+
+macro_rules! impl_ipnet {
+    ( $( $afi:ty => $net:ident, $addr:ty, $variant:ident ),* $(,)? ) => { $(
+        impl From<$net> for Node<$afi> {
+            fn from(net: $net) -> Self {
+                // ok to unwrap, because the length of a valid `$net` is
+                // within bounds.
+                let length = PrefixLength::<$afi>::from_primitive(net.prefix_len()).unwrap();
+                Prefix::new(Address::<$afi>::from(net.network()), length).into()
+            }
+        }
+
+        impl Iterator for IpNets<'_, $afi> {
+            type Item = IpNet;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.prefixes.next().map(|prefix| {
+                    let addr = <$addr>::from(prefix.network().into_primitive());
+                    // ok to unwrap, because the length of a valid prefix is
+                    // within bounds.
+                    $net::new(addr, prefix.length().into_primitive())
+                        .unwrap()
+                        .into()
+                })
+            }
+        }
+
+        /// Construct a set from a slice of [`IpNet`]s.
+        ///
+        /// # Errors
+        ///
+        /// An [`Error::AfiMismatch`] is returned if any element of the slice
+        /// is of the wrong address family.
+        impl TryFrom<&[IpNet]> for PrefixSet<$afi> {
+            type Error = Error;
+
+            fn try_from(nets: &[IpNet]) -> Result<Self, Self::Error> {
+                nets.iter()
+                    .map(|net| match net {
+                        IpNet::$variant(net) => Ok(*net),
+                        _ => Err(Error::AfiMismatch {
+                            expected: <$afi>::as_afi(),
+                            found: match net {
+                                IpNet::V4(_) => concrete::Afi::Ipv4,
+                                IpNet::V6(_) => concrete::Afi::Ipv6,
+                            },
+                        }),
+                    })
+                    .collect()
+            }
+        }
+    )* };
+}
+
+impl_ipnet! {
+    Ipv4 => Ipv4Net, Ipv4Addr, V4,
+    Ipv6 => Ipv6Net, Ipv6Addr, V6,
+}
@@ -1,4 +1,10 @@
-use ip::{Afi, PrefixRange};
+use core::iter::FusedIterator;
+
+use ip::{
+    concrete::{Address, Bitmask, Hostmask, Netmask, Prefix, PrefixLength, PrefixRange},
+    traits::{Prefix as _, PrefixLength as _},
+    Afi,
+};
 
 use crate::node;
 
@@ -9,6 +15,7 @@ use super::PrefixSet;
 pub struct Ranges<'a, A: Afi> {
     tree_iter: Option<node::Children<'a, A>>,
     ranges_iter: Option<node::Ranges<'a, A>>,
+    back_ranges_iter: Option<node::Ranges<'a, A>>,
 }
 
 impl<'a, A: Afi> From<&'a PrefixSet<A>> for Ranges<'a, A> {
@@ -16,6 +23,7 @@ impl<'a, A: Afi> From<&'a PrefixSet<A>> for Ranges<'a, A> {
         Self {
             tree_iter: s.root.as_ref().map(|root| root.children()),
             ranges_iter: None,
+            back_ranges_iter: None,
         }
     }
 }
@@ -23,6 +31,356 @@ impl<'a, A: Afi> From<&'a PrefixSet<A>> for Ranges<'a, A> {
 impl<'a, A: Afi> Iterator for Ranges<'a, A> {
     type Item = <node::Ranges<'a, A> as Iterator>::Item;
 
+    /// Only the ranges of the nodes already reached from either end are
+    /// counted, so the lower bound is exact only once the tree has been
+    /// fully traversed.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let started = self.started();
+        match self.tree_iter {
+            Some(_) => (started, None),
+            None => (started, Some(started)),
+        }
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut ranges_iter) = self.ranges_iter {
+                match ranges_iter.next() {
+                    range @ Some(_) => return range,
+                    None => self.ranges_iter = None,
+                }
+            }
+            match self
+                .tree_iter
+                .as_mut()
+                .and_then(|tree_iter| tree_iter.next())
+            {
+                Some(node) => self.ranges_iter = Some(node.ranges()),
+                // the remaining ranges, if any, belong to the node currently
+                // being iterated from the back.
+                None => return self.back_ranges_iter.as_mut()?.next(),
+            }
+        }
+    }
+}
+
+impl<A: Afi> DoubleEndedIterator for Ranges<'_, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut ranges_iter) = self.back_ranges_iter {
+                match ranges_iter.next_back() {
+                    range @ Some(_) => return range,
+                    None => self.back_ranges_iter = None,
+                }
+            }
+            match self
+                .tree_iter
+                .as_mut()
+                .and_then(|tree_iter| tree_iter.next_back())
+            {
+                Some(node) => self.back_ranges_iter = Some(node.ranges()),
+                None => return self.ranges_iter.as_mut()?.next_back(),
+            }
+        }
+    }
+}
+
+impl<A: Afi> FusedIterator for Ranges<'_, A> {}
+
+impl<A: Afi> Ranges<'_, A> {
+    // count the ranges remaining in the nodes currently being iterated over
+    // from either end.
+    fn started(&self) -> usize {
+        [&self.ranges_iter, &self.back_ranges_iter]
+            .into_iter()
+            .flatten()
+            .map(|ranges_iter| ranges_iter.clone().count())
+            .sum()
+    }
+}
+
+/// Non-consuming iterator returned by [`PrefixSet<A>::prefixes()`].
+#[derive(Debug)]
+pub struct Prefixes<'a, A: Afi> {
+    set: &'a PrefixSet<A>,
+    ranges_iter: Ranges<'a, A>,
+    prefix_range_iter: Option<RangePrefixes<A>>,
+    back_prefix_range_iter: Option<RangePrefixes<A>>,
+    // the number of prefixes yielded from either end so far.
+    taken: u128,
+}
+
+impl<'a, A: Afi> From<&'a PrefixSet<A>> for Prefixes<'a, A> {
+    fn from(s: &'a PrefixSet<A>) -> Self {
+        Self {
+            set: s,
+            ranges_iter: s.into(),
+            prefix_range_iter: None,
+            back_prefix_range_iter: None,
+            taken: 0,
+        }
+    }
+}
+
+impl<A: Afi> Iterator for Prefixes<'_, A> {
+    type Item = Prefix<A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.next_from_front();
+        self.taken += u128::from(next.is_some());
+        next
+    }
+
+    /// The bounds are exact, unless the number of prefixes remaining is
+    /// greater than `usize::MAX`.
+    ///
+    /// The number of prefixes in the set is maintained as it is modified, so
+    /// this does not walk the tree.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.set.count_prefixes().saturating_sub(self.taken);
+        match usize::try_from(remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl<A: Afi> FusedIterator for Prefixes<'_, A> {}
+
+impl<A: Afi> Prefixes<'_, A> {
+    fn next_from_front(&mut self) -> Option<Prefix<A>> {
+        loop {
+            if let Some(ref mut prefix_range_iter) = self.prefix_range_iter {
+                match prefix_range_iter.next() {
+                    p @ Some(_) => return p,
+                    None => self.prefix_range_iter = None,
+                }
+            }
+            match self.ranges_iter.next() {
+                Some(range) => self.prefix_range_iter = Some(range.into()),
+                None => return self.back_prefix_range_iter.as_mut()?.next(),
+            }
+        }
+    }
+}
+
+impl<A: Afi> DoubleEndedIterator for Prefixes<'_, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let next = self.next_from_back();
+        self.taken += u128::from(next.is_some());
+        next
+    }
+}
+
+impl<A: Afi> Prefixes<'_, A> {
+    fn next_from_back(&mut self) -> Option<Prefix<A>> {
+        loop {
+            if let Some(ref mut prefix_range_iter) = self.back_prefix_range_iter {
+                match prefix_range_iter.next_back() {
+                    p @ Some(_) => return p,
+                    None => self.back_prefix_range_iter = None,
+                }
+            }
+            match self.ranges_iter.next_back() {
+                Some(range) => self.back_prefix_range_iter = Some(range.into()),
+                None => return self.prefix_range_iter.as_mut()?.next_back(),
+            }
+        }
+    }
+}
+
+// double-ended iterator over the prefixes contained in a single
+// `PrefixRange<A>`, in the same order as `PrefixRange::into_iter()`: by
+// length, and then by network address.
+#[derive(Debug)]
+pub(super) struct RangePrefixes<A: Afi> {
+    base: Prefix<A>,
+    lower: PrefixLength<A>,
+    upper: PrefixLength<A>,
+    front: Option<Prefix<A>>,
+    back: Option<Prefix<A>>,
+}
+
+impl<A: Afi> From<PrefixRange<A>> for RangePrefixes<A> {
+    fn from(range: PrefixRange<A>) -> Self {
+        let base = range.prefix();
+        let (lower, upper) = (range.lower(), range.upper());
+        let window = Hostmask::from(base.length()) & Netmask::from(upper);
+        Self {
+            base,
+            lower,
+            upper,
+            front: Some(Prefix::new(base.network(), lower)),
+            back: Some(Prefix::new(base.network() | window, upper)),
+        }
+    }
+}
+
+impl<A: Afi> RangePrefixes<A> {
+    // the difference between the network addresses of adjacent prefixes of
+    // length `length`.
+    fn step(length: PrefixLength<A>) -> Option<Bitmask<A>> {
+        length
+            .decrement()
+            .ok()
+            .map(|prev| Hostmask::from(prev) & Netmask::from(length))
+    }
+
+    // the `n`th subprefix of `base` of length `length`, in order of network
+    // address, assuming that there are more than `n` of them.
+    pub(super) fn nth_subprefix(
+        base: Prefix<A>,
+        length: PrefixLength<A>,
+        mut n: u128,
+    ) -> Prefix<A> {
+        let mut network = base.network();
+        let mut bit = length;
+        while n != 0 {
+            if n & 1 == 1 {
+                // ok to unwrap, because `bit` is longer than `base`.
+                network = network | Self::step(bit).unwrap();
+            }
+            n >>= 1;
+            match bit.decrement() {
+                Ok(prev) => bit = prev,
+                Err(_) => break,
+            }
+        }
+        Prefix::new(network, length)
+    }
+
+    // the bits of the network addresses of prefixes of length `length` that
+    // vary within `self.base`.
+    fn window(&self, length: PrefixLength<A>) -> Bitmask<A> {
+        Hostmask::from(self.base.length()) & Netmask::from(length)
+    }
+
+    fn successor(&self, prefix: Prefix<A>) -> Option<Prefix<A>> {
+        let length = prefix.length();
+        Self::step(length)
+            .and_then(|step| prefix.network() + step)
+            .map(|network| Prefix::new(network, length))
+            .filter(|next| self.base.contains(next))
+            .or_else(|| {
+                length
+                    .increment()
+                    .ok()
+                    .filter(|next| next <= &self.upper)
+                    .map(|next| Prefix::new(self.base.network(), next))
+            })
+    }
+
+    fn predecessor(&self, prefix: Prefix<A>) -> Option<Prefix<A>> {
+        let length = prefix.length();
+        if prefix.network() == self.base.network() {
+            length
+                .decrement()
+                .ok()
+                .filter(|prev| prev >= &self.lower)
+                .map(|prev| Prefix::new(self.base.network() | self.window(prev), prev))
+        } else {
+            // reflect the network address within the window, so that the
+            // predecessor can be found by addition.
+            let window = self.window(length);
+            let mirror =
+                |addr: Address<A>| Address::new(addr.into_primitive() ^ window.into_primitive());
+            // `prefix` is not the first prefix of its length, so its
+            // reflection is not the last, and the addition can't overflow.
+            Self::step(length)
+                .and_then(|step| mirror(prefix.network()) + step)
+                .map(|network| Prefix::new(mirror(network), length))
+        }
+    }
+
+    // take `prefix` from one end, ending the iteration if it has passed the
+    // other end.
+    fn take(&mut self, prefix: Prefix<A>, other: Prefix<A>, forward: bool) -> Option<Prefix<A>> {
+        let key = |p: &Prefix<A>| (p.length(), p.network());
+        let passed = if forward {
+            key(&prefix) > key(&other)
+        } else {
+            key(&prefix) < key(&other)
+        };
+        if passed {
+            self.front = None;
+            self.back = None;
+            None
+        } else {
+            Some(prefix)
+        }
+    }
+}
+
+impl<A: Afi> Iterator for RangePrefixes<A> {
+    type Item = Prefix<A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (front, back) = (self.front?, self.back?);
+        let prefix = self.take(front, back, true)?;
+        if prefix == back {
+            self.back = None;
+        }
+        self.front = self.successor(prefix);
+        Some(prefix)
+    }
+}
+
+impl<A: Afi> DoubleEndedIterator for RangePrefixes<A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (front, back) = (self.front?, self.back?);
+        let prefix = self.take(back, front, false)?;
+        if prefix == front {
+            self.front = None;
+        }
+        self.back = self.predecessor(prefix);
+        Some(prefix)
+    }
+}
+
+/// Iterator returned by [`PrefixSet<A>::covering()`].
+#[derive(Debug)]
+pub struct Covering<A: Afi> {
+    prefix: Prefix<A>,
+    lengths: Option<node::Lengths<A>>,
+}
+
+impl<A: Afi> Covering<A> {
+    pub(super) fn new(s: &PrefixSet<A>, prefix: Prefix<A>) -> Self {
+        Self {
+            prefix,
+            lengths: s.root.as_ref().map(|root| root.covering_lengths(&prefix)),
+        }
+    }
+}
+
+impl<A: Afi> Iterator for Covering<A> {
+    type Item = Prefix<A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let length = self.lengths.as_mut()?.next()?;
+        Some(Prefix::new(self.prefix.network(), length))
+    }
+}
+
+/// Consuming iterator returned by [`PrefixSet<A>::into_ranges()`].
+#[derive(Debug)]
+pub struct IntoRanges<A: Afi> {
+    tree_iter: Option<node::IntoChildren<A>>,
+    ranges_iter: Option<node::IntoRanges<A>>,
+}
+
+impl<A: Afi> From<PrefixSet<A>> for IntoRanges<A> {
+    fn from(s: PrefixSet<A>) -> Self {
+        Self {
+            tree_iter: s.root.map(|root| root.into_children()),
+            ranges_iter: None,
+        }
+    }
+}
+
+impl<A: Afi> Iterator for IntoRanges<A> {
+    type Item = PrefixRange<A>;
+
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             if let Some(ref mut ranges_iter) = self.ranges_iter {
@@ -33,7 +391,7 @@ impl<'a, A: Afi> Iterator for Ranges<'a, A> {
             }
             if let Some(ref mut tree_iter) = self.tree_iter {
                 match tree_iter.next() {
-                    Some(node) => self.ranges_iter = Some(node.ranges()),
+                    Some(node) => self.ranges_iter = Some(node.into_ranges()),
                     None => return None,
                 }
             } else {
@@ -43,15 +401,15 @@ impl<'a, A: Afi> Iterator for Ranges<'a, A> {
     }
 }
 
-/// Non-consuming iterator returned by [`PrefixSet<A>::prefixes()`].
+/// Consuming iterator returned by [`PrefixSet<A>::into_iter()`].
 #[derive(Debug)]
-pub struct Prefixes<'a, A: Afi> {
-    ranges_iter: Ranges<'a, A>,
+pub struct IntoPrefixes<A: Afi> {
+    ranges_iter: IntoRanges<A>,
     prefix_range_iter: Option<<PrefixRange<A> as IntoIterator>::IntoIter>,
 }
 
-impl<'a, A: Afi> From<&'a PrefixSet<A>> for Prefixes<'a, A> {
-    fn from(s: &'a PrefixSet<A>) -> Self {
+impl<A: Afi> From<PrefixSet<A>> for IntoPrefixes<A> {
+    fn from(s: PrefixSet<A>) -> Self {
         Self {
             ranges_iter: s.into(),
             prefix_range_iter: None,
@@ -59,7 +417,7 @@ impl<'a, A: Afi> From<&'a PrefixSet<A>> for Prefixes<'a, A> {
     }
 }
 
-impl<'a, A: Afi> Iterator for Prefixes<'a, A> {
+impl<A: Afi> Iterator for IntoPrefixes<A> {
     type Item = <PrefixRange<A> as IntoIterator>::Item;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -77,3 +435,29 @@ impl<'a, A: Afi> Iterator for Prefixes<'a, A> {
         }
     }
 }
+
+impl<A: Afi> IntoIterator for PrefixSet<A> {
+    type Item = <PrefixRange<A> as IntoIterator>::Item;
+    type IntoIter = IntoPrefixes<A>;
+
+    /// Get a consuming iterator over the
+    /// [`Prefix<A>`](ip::concrete::Prefix)s contained in `self`, in the
+    /// same order as yielded by [`PrefixSet::prefixes()`].
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let prefixes: Vec<_> = set.into_iter().collect();
+    /// assert_eq!(prefixes.len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.into()
+    }
+}
@@ -0,0 +1,114 @@
+use ip::{
+    concrete::{Address, Prefix, PrefixLength, PrefixRange},
+    traits::Prefix as _,
+    Ipv4, Ipv6,
+};
+
+use super::PrefixSet;
+
+// length of the `::ffff:0:0/96` prefix containing the IPv4-mapped addresses.
+const MAPPED_LENGTH: usize = 96;
+
+fn mapped_space() -> Prefix<Ipv6> {
+    Prefix::new(
+        Address::<Ipv4>::ZEROS.to_ipv6_mapped(),
+        mapped_length(PrefixLength::MIN),
+    )
+}
+
+fn mapped_length(length: PrefixLength<Ipv4>) -> PrefixLength<Ipv6> {
+    // at most 32 + 96 = 128, so this never fails.
+    PrefixLength::try_from(Into::<usize>::into(length.into_primitive()) + MAPPED_LENGTH).unwrap()
+}
+
+fn unmapped_length(length: PrefixLength<Ipv6>) -> PrefixLength<Ipv4> {
+    // callers only pass lengths within the mapped space, i.e. at least 96.
+    PrefixLength::try_from(Into::<usize>::into(length.into_primitive()) - MAPPED_LENGTH).unwrap()
+}
+
+impl PrefixSet<Ipv4> {
+    /// Construct a new [`PrefixSet<Ipv6>`] containing the IPv4-mapped form
+    /// of each prefix in `self`.
+    ///
+    /// Each prefix `a.b.c.d/n` becomes `::ffff:a.b.c.d/(n + 96)`, so that
+    /// the result lies entirely within `::ffff:0:0/96`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Ipv6};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24,24,25".parse()?;
+    /// assert_eq!(
+    ///     set.to_v4_mapped(),
+    ///     "::ffff:192.0.2.0/120,120,121".parse::<PrefixSet<Ipv6>>()?
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn to_v4_mapped(&self) -> PrefixSet<Ipv6> {
+        self.ranges()
+            .map(|range| {
+                let prefix = range.prefix();
+                PrefixRange::new(
+                    Prefix::new(
+                        prefix.network().to_ipv6_mapped(),
+                        mapped_length(prefix.length()),
+                    ),
+                    mapped_length(range.lower())..=mapped_length(range.upper()),
+                )
+                // shifting every length by the same offset keeps the bounds
+                // ordered and no shorter than the prefix.
+                .unwrap()
+            })
+            .collect()
+    }
+}
+
+impl PrefixSet<Ipv6> {
+    /// Construct a new [`PrefixSet<Ipv4>`] from the IPv4-mapped prefixes in
+    /// `self`.
+    ///
+    /// This is the inverse of [`PrefixSet::to_v4_mapped()`]: each prefix
+    /// `::ffff:a.b.c.d/n` with `n >= 96` becomes `a.b.c.d/(n - 96)`. Any
+    /// prefixes outside of `::ffff:0:0/96`, including shorter prefixes that
+    /// cover it, are ignored.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Ipv6};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv6> = "::ffff:192.0.2.0/120 2001:db8::/32".parse()?;
+    /// assert_eq!(
+    ///     set.extract_v4_mapped(),
+    ///     "192.0.2.0/24".parse::<PrefixSet<Ipv4>>()?
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn extract_v4_mapped(&self) -> PrefixSet<Ipv4> {
+        let space = mapped_space();
+        self.intersect_range(PrefixRange::new(space, space.length()..=PrefixLength::MAX).unwrap())
+            .ranges()
+            .map(|range| {
+                // a range based on a prefix covering the mapped space can only
+                // contain prefixes within it after the intersection, so its
+                // prefix can be narrowed to the mapped space.
+                let prefix = if range.prefix().length() < space.length() {
+                    space
+                } else {
+                    range.prefix()
+                };
+                PrefixRange::new(
+                    Prefix::new(
+                        // every address within the mapped space is mapped.
+                        prefix.network().to_ipv4_mapped().unwrap(),
+                        unmapped_length(prefix.length()),
+                    ),
+                    unmapped_length(range.lower().max(prefix.length()))
+                        ..=unmapped_length(range.upper()),
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+}
@@ -1,14 +1,63 @@
 //! [`PrefixSet<A>`] and related types.
-use std::mem;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::BTreeMap,
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+use core::mem;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
 
-use ip::{Afi, Prefix};
+use ip::{
+    concrete::{self, Address, PrefixLength, PrefixRange},
+    traits::{Prefix as _, PrefixLength as _},
+    Afi, Prefix,
+};
 
+use crate::error::Error;
 use crate::node::Node;
+pub use crate::node::{AggregateEvent, Lengths};
 
+mod any;
+mod diff;
+mod format;
+mod instrument;
+#[cfg(feature = "ipnet")]
+mod ipnet;
 mod iter;
+mod mapped;
 mod ops;
+#[cfg(feature = "std")]
+mod overlay;
+#[cfg(feature = "rayon")]
+mod par;
+mod parse;
+mod policy;
+#[cfg(feature = "proptest")]
+mod proptest;
+#[cfg(feature = "rand")]
+mod rand;
+#[cfg(feature = "serde")]
+mod serde;
+mod shard;
+mod stats;
 
-pub use self::iter::{Prefixes, Ranges};
+pub use self::any::{AnyPrefixSet, AnyRanges};
+pub use self::diff::{ChangeSummary, SetDiff};
+pub use self::format::PermitEntry;
+#[cfg(feature = "std")]
+pub use self::format::Vendor;
+#[cfg(feature = "ipnet")]
+pub use self::ipnet::IpNets;
+use self::iter::RangePrefixes;
+pub use self::iter::{Covering, IntoPrefixes, IntoRanges, Prefixes, Ranges};
+pub use self::ops::SetRelation;
+pub use self::policy::{PolicyViolation, SetPolicy};
+pub use self::stats::SetStats;
 
 /// A collection of IP prefixes, providing fast insertion and iteration,
 /// and set-theorectic arithmetic.
@@ -26,19 +75,106 @@ pub use self::iter::{Prefixes, Ranges};
 /// #     Ok(())
 /// # }
 /// ```
-#[derive(Clone, Debug)]
+///
+/// Sets form a semiring, with union as addition and intersection as
+/// multiplication. The empty set is the additive identity returned by
+/// [`Zero::zero()`](num::Zero::zero), and the set containing every prefix,
+/// [`PrefixSet::universe()`], is the multiplicative identity returned by
+/// [`One::one()`](num::One::one).
+#[derive(Clone)]
 pub struct PrefixSet<A: Afi> {
     root: Option<Box<Node<A>>>,
+    ranges_cache: Option<Vec<PrefixRange<A>>>,
+    // the number of prefixes in the tree, saturating at `u128::MAX`, kept up
+    // to date by `aggregate()` so that it can be read without a walk.
+    prefix_count: u128,
 }
 
 impl<A: Afi> PrefixSet<A> {
     /// Construct a new, empty [`PrefixSet<A>`].
     pub fn new() -> Self {
-        PrefixSet { root: None }
+        Self::new_with_root(None)
+    }
+
+    /// Construct a new [`PrefixSet<A>`] containing only `item`.
+    ///
+    /// `T` can be either a [`Prefix<A>`](ip::concrete::Prefix) or a
+    /// [`PrefixRange<A>`](ip::concrete::PrefixRange).
+    ///
+    /// This is equivalent to `PrefixSet::new().insert(item).to_owned()`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::singleton("192.0.2.0/24".parse::<Prefix<Ipv4>>()?);
+    /// assert_eq!(set.len(), 1);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn singleton<T>(item: T) -> Self
+    where
+        T: Into<Node<A>>,
+    {
+        let mut set = Self::new_with_root(Some(item.into().boxed()));
+        set.aggregate();
+        set
+    }
+
+    /// Construct a new [`PrefixSet<A>`] covering exactly the addresses from
+    /// `start` to `end` inclusive, with the minimal number of prefixes.
+    ///
+    /// The range need not be aligned to a prefix boundary. If `start` is
+    /// greater than `end` then the range is empty, and so is the set.
+    ///
+    /// ``` rust
+    /// # use ip::{Address, Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::from_address_range(
+    ///     "192.0.2.0".parse::<Address<Ipv4>>()?,
+    ///     "192.0.2.255".parse()?,
+    /// );
+    /// assert_eq!(set, PrefixSet::singleton("192.0.2.0/24".parse::<Prefix<Ipv4>>()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn from_address_range(start: Address<A>, end: Address<A>) -> Self {
+        let mut prefixes = Vec::new();
+        Self::cover_address_range(concrete::Prefix::DEFAULT, &start, &end, &mut prefixes);
+        Self::new().insert_from(prefixes).to_owned()
+    }
+
+    // push the largest subprefixes of `prefix` lying between `start` and
+    // `end` onto `prefixes`.
+    fn cover_address_range(
+        prefix: Prefix<A>,
+        start: &Address<A>,
+        end: &Address<A>,
+        prefixes: &mut Vec<Prefix<A>>,
+    ) {
+        if prefix.network() >= *start && prefix.broadcast() <= *end {
+            prefixes.push(prefix);
+        } else if prefix.broadcast() >= *start && prefix.network() <= *end {
+            // ok to unwrap, because a prefix that is only partially within
+            // the range contains more than one address, so is shorter than
+            // `MAX_LENGTH`.
+            let length = prefix.length().increment().unwrap();
+            prefix
+                .subprefixes(length)
+                .unwrap()
+                .for_each(|subprefix| Self::cover_address_range(subprefix, start, end, prefixes));
+        }
     }
 
+    // every caller aggregates the new set, which establishes its prefix
+    // count.
     fn new_with_root(root: Option<Box<Node<A>>>) -> Self {
-        PrefixSet { root }
+        PrefixSet {
+            root,
+            ranges_cache: None,
+            prefix_count: 0,
+        }
     }
 
     fn insert_node(&mut self, new: Box<Node<A>>) -> &mut Self {
@@ -77,6 +213,92 @@ impl<A: Afi> PrefixSet<A> {
         self.insert_node(item.into().boxed()).aggregate()
     }
 
+    /// Insert the host-length prefix (`/32` or `/128`) of `addr` into
+    /// `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Address, Ipv4};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert_host("192.0.2.1".parse::<Address<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(set.contains("192.0.2.1/32".parse()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn insert_host(&mut self, addr: Address<A>) -> &mut Self {
+        self.insert(Self::host(addr))
+    }
+
+    /// Insert `prefix` into `self`, unless it is already present.
+    ///
+    /// Returns `true` if `prefix` was inserted. This is equivalent to
+    /// testing [`contains()`](Self::contains) and then calling
+    /// [`insert()`](Self::insert), but descends the tree only once, and
+    /// doesn't re-aggregate `self` if `prefix` was already present.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,25".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(!set.get_or_insert("192.0.2.128/25".parse()?));
+    /// assert!(set.get_or_insert("192.0.2.0/24".parse()?));
+    /// assert_eq!(set.len(), 3);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn get_or_insert(&mut self, prefix: Prefix<A>) -> bool {
+        let inserted = Node::add_if_absent(&mut self.root, Node::from(prefix).boxed());
+        if inserted {
+            self.aggregate();
+        }
+        inserted
+    }
+
+    /// Insert a new `item` into `self`, unless the base prefix of `item` is
+    /// already covered by an equal or less specific prefix in `self`.
+    ///
+    /// Returns `true` if `item` was inserted.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("192.0.0.0/16".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(!set.insert_if_not_covered("192.0.2.0/24".parse::<Prefix<Ipv4>>()?));
+    /// assert!(set.insert_if_not_covered("198.51.100.0/24".parse::<Prefix<Ipv4>>()?));
+    /// assert_eq!(set.len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn insert_if_not_covered<T>(&mut self, item: T) -> bool
+    where
+        T: Into<Node<A>>,
+    {
+        let node = item.into();
+        if self.covered(node.prefix()) {
+            false
+        } else {
+            self.insert_node(node.boxed()).aggregate();
+            true
+        }
+    }
+
+    // check whether `prefix`, or any of its supernets, is contained in
+    // `self`.
+    fn covered(&self, prefix: &Prefix<A>) -> bool {
+        match &self.root {
+            Some(root) => root.covering_lengths(prefix).next().is_some(),
+            None => false,
+        }
+    }
+
     /// Insert items into `self` from an iterator yielding either
     /// [`Prefix<A>`](ip::concrete::Prefix) or
     /// [`PrefixRange<A>`](ip::concrete::PrefixRange).
@@ -109,9 +331,114 @@ impl<A: Afi> PrefixSet<A> {
             .aggregate()
     }
 
-    fn remove_node(&mut self, mut old: Box<Node<A>>) -> &mut Self {
+    /// Insert items into `self` from an iterator yielding fallible results,
+    /// such as a stream of parsed prefixes, stopping at the first error.
+    ///
+    /// The items are collected before any are inserted, so if an error is
+    /// encountered it is returned and `self` is left unchanged. Otherwise,
+    /// this is equivalent to [`PrefixSet::insert_from()`].
+    ///
+    /// # Errors
+    ///
+    /// The first error yielded by `iter` is returned.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new();
+    /// set.try_insert_from(
+    ///     ["192.0.2.0/26", "192.0.2.64/26"]
+    ///         .into_iter()
+    ///         .map(|s| s.parse::<Prefix<Ipv4>>()),
+    /// )?;
+    /// assert_eq!(set.len(), 2);
+    /// assert!(set
+    ///     .try_insert_from(["198.51.100.0/24", "bad"].into_iter().map(str::parse::<Prefix<Ipv4>>))
+    ///     .is_err());
+    /// assert_eq!(set.len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn try_insert_from<I, T, E>(&mut self, iter: I) -> Result<&mut Self, E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+        T: Into<Node<A>>,
+    {
+        let items = iter.into_iter().collect::<Result<Vec<_>, _>>()?;
+        Ok(self.insert_from(items))
+    }
+
+    /// Insert items into `self` from an iterator yielding either
+    /// [`Prefix<A>`](ip::concrete::Prefix) or
+    /// [`PrefixRange<A>`](ip::concrete::PrefixRange), sorted by network
+    /// address and then prefix length.
+    ///
+    /// This is equivalent to [`PrefixSet::insert_from()`], but exploits the
+    /// ordering of the input to insert each item near the previous one,
+    /// rather than descending the tree from the root for each. Unsorted
+    /// input produces the same result, but more slowly.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let prefixes: Vec<_> = vec!["192.0.2.0/24", "192.0.2.0/26", "192.0.2.64/26"]
+    ///     .into_iter()
+    ///     .map(|s| s.parse::<Prefix<Ipv4>>())
+    ///     .collect::<Result<_, _>>()?;
+    /// let set = PrefixSet::new()
+    ///     .insert_sorted(prefixes.clone())
+    ///     .to_owned();
+    /// assert_eq!(set, PrefixSet::new().insert_from(prefixes).to_owned());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn insert_sorted<I, T>(&mut self, iter: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Node<A>>,
+    {
+        if let Some(new) = Node::from_sorted(iter.into_iter().map(|item| item.into().boxed())) {
+            self.insert_node(new);
+        }
+        self.aggregate()
+    }
+
+    /// Move all of the prefixes in `other` into `self`, leaving `other`
+    /// empty.
+    ///
+    /// The tree of `other` is merged into that of `self` directly, so
+    /// neither tree is cloned, and aggregation occurs only once.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut other = PrefixSet::new()
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// set.append(&mut other);
+    /// assert_eq!(set.len(), 2);
+    /// assert!(other.is_empty());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn append(&mut self, other: &mut Self) -> &mut Self {
+        other.ranges_cache = None;
+        other.prefix_count = 0;
+        match mem::take(&mut other.root) {
+            Some(root) => self.insert_node(root).aggregate(),
+            None => self,
+        }
+    }
+
+    fn remove_node(&mut self, old: Box<Node<A>>) -> &mut Self {
         if let Some(root) = mem::take(&mut self.root) {
-            self.root = Some(root.remove(&mut old));
+            self.root = Some(root.remove(&old));
         };
         self
     }
@@ -177,133 +504,1904 @@ impl<A: Afi> PrefixSet<A> {
             .aggregate()
     }
 
-    fn aggregate(&mut self) -> &mut Self {
-        if let Some(root) = mem::take(&mut self.root) {
-            self.root = root.aggregate(None)
-        }
-        self
+    /// Retain only the prefixes in `self` for which `f` returns `true`.
+    ///
+    /// `f` is called once for each [`Prefix<A>`](ip::concrete::Prefix) in
+    /// `self`, rather than for each range, so that filtering on properties
+    /// of individual prefixes, such as their length, is exact. The rejected
+    /// prefixes are removed together, followed by a single aggregation.
+    ///
+    /// ``` rust
+    /// # use ip::{traits::Prefix as _, Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,24,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// set.retain(|p| p.length() <= 25.try_into().unwrap());
+    /// assert_eq!(set.len(), 3);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Prefix<A>) -> bool,
+    {
+        let rejected: Vec<_> = self.prefixes().filter(|p| !f(p)).collect();
+        self.remove_from(rejected);
     }
 
-    /// Test whether `prefix` is contained in `self`.
+    /// Construct a new [`PrefixSet<A>`] containing only the prefixes in
+    /// `self` with a length within `lengths`.
+    ///
+    /// This operates directly on the prefix lengths stored in each node of
+    /// the tree, without expanding ranges into prefixes.
     ///
     /// ``` rust
-    /// # use ip::{Ipv4, Prefix, PrefixRange};
+    /// # use ip::{Ipv4, PrefixLength, PrefixRange};
     /// # use prefixset::{Error, PrefixSet};
     /// # fn main() -> Result<(), Error> {
     /// let set = PrefixSet::new()
-    ///     .insert("192.0.2.0/24,26,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .insert("10.0.0.0/8,8,24".parse::<PrefixRange<Ipv4>>()?)
     ///     .to_owned();
-    /// assert!(set.contains("192.0.2.128/26".parse()?));
+    /// let lower = PrefixLength::<Ipv4>::from_primitive(24)?;
+    /// let upper = PrefixLength::<Ipv4>::from_primitive(24)?;
+    /// let filtered = set.filter_length(lower..=upper);
+    /// assert_eq!(filtered.ranges().collect::<Vec<_>>(), vec!["10.0.0.0/8,24,24".parse()?]);
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn contains(&self, prefix: Prefix<A>) -> bool {
-        match &self.root {
-            Some(root) => root.search(&prefix.into()).is_some(),
-            None => false,
+    pub fn filter_length(&self, lengths: RangeInclusive<PrefixLength<A>>) -> Self {
+        let mut root = self.root.clone();
+        if let Some(root) = &mut root {
+            root.retain_lengths(lengths);
         }
+        Self::new_with_root(root).aggregate().to_owned()
     }
 
-    /// Get the number of prefixes in `self`.
+    /// Construct a new [`PrefixSet<A>`] in which no prefix is longer than
+    /// `max`, by replacing each prefix longer than `max` with its length
+    /// `max` supernet.
+    ///
+    /// This is useful for targets that can only express exact prefixes and
+    /// need a bound on their number.
     ///
     /// ``` rust
-    /// # use ip::{Ipv4, PrefixRange};
+    /// # use ip::{Ipv4, Prefix, PrefixLength, PrefixRange};
     /// # use prefixset::{Error, PrefixSet};
     /// # fn main() -> Result<(), Error> {
     /// let set = PrefixSet::new()
-    ///     .insert("192.0.2.0/24,26,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .insert("10.0.0.0/8,16,24".parse::<PrefixRange<Ipv4>>()?)
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
     ///     .to_owned();
-    /// assert_eq!(set.len(), 4);
+    /// let split = set.split_to_max_length(PrefixLength::<Ipv4>::from_primitive(16)?);
+    /// assert!(split.contains_range("10.0.0.0/8,16,16".parse()?));
+    /// assert!(split.contains("192.0.0.0/16".parse()?));
+    /// assert_eq!(split.len(), 257);
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn len(&self) -> usize {
-        self.prefixes().count()
+    pub fn split_to_max_length(&self, max: PrefixLength<A>) -> Self {
+        let supernets: Vec<PrefixRange<A>> = self
+            .ranges()
+            .filter(|range| range.upper() > max)
+            .map(|range| {
+                let prefix = range.prefix();
+                if prefix.length() <= max {
+                    // every subprefix of `prefix` longer than `max` is
+                    // covered by one of its length `max` subprefixes.
+                    PrefixRange::new(prefix, max..=max).unwrap() // safe because `prefix.length() <= max`.
+                } else {
+                    concrete::Prefix::new(prefix.network(), max).into()
+                }
+            })
+            .collect();
+        self.filter_length(PrefixLength::MIN..=max)
+            .insert_from(supernets)
+            .to_owned()
     }
 
-    /// Test whether `self` is empty.
+    /// Construct a new [`PrefixSet<A>`] in which every prefix has a length
+    /// between `min` and `max`, by moving each prefix of `self` to the
+    /// nearest bound:
+    ///
+    /// - a prefix longer than `max` is replaced by its length `max`
+    ///   supernet, as by [`split_to_max_length()`](Self::split_to_max_length),
+    /// - a prefix shorter than `min` is replaced by all of its length `min`
+    ///   subprefixes, and
+    /// - a prefix of length between `min` and `max` is retained.
+    ///
+    /// The address space covered by the set is therefore unchanged, except
+    /// that prefixes longer than `max` extend it to their supernets. Prefixes
+    /// that map to the same result are merged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is longer than `max`.
     ///
     /// ``` rust
-    /// # use ip::Ipv4;
+    /// # use ip::{Ipv4, PrefixLength};
     /// # use prefixset::{Error, PrefixSet};
     /// # fn main() -> Result<(), Error> {
-    /// assert!(PrefixSet::<Ipv4>::new().is_empty());
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/15 192.0.2.0/30 192.0.2.4/30".parse()?;
+    /// let clamped = set.clamp_length(
+    ///     PrefixLength::<Ipv4>::from_primitive(16)?,
+    ///     PrefixLength::<Ipv4>::from_primitive(24)?,
+    /// );
+    /// let expected: PrefixSet<Ipv4> = "10.0.0.0/15,16,16 192.0.2.0/24".parse()?;
+    /// assert_eq!(clamped, expected);
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.ranges().count() == 0
+    pub fn clamp_length(&self, min: PrefixLength<A>, max: PrefixLength<A>) -> Self {
+        assert!(min <= max, "minimum length {} exceeds maximum {}", min, max);
+        let clamp = |length: PrefixLength<A>| length.clamp(min, max);
+        self.ranges()
+            .map(|range| {
+                let prefix = range.prefix();
+                if prefix.length() > max {
+                    concrete::Prefix::new(prefix.network(), max).into()
+                } else {
+                    // clamping can't shorten either bound below the length
+                    // of `prefix`, which is at most `max`.
+                    PrefixRange::new(prefix, clamp(range.lower())..=clamp(range.upper())).unwrap()
+                }
+            })
+            .collect()
     }
 
-    /// Clear the contents of `self`
+    /// Consume `self`, constructing a new [`PrefixSet<A>`] in which every
+    /// prefix shorter than `max` is replaced by all of its length `max`
+    /// subprefixes.
+    ///
+    /// This complements [`split_to_max_length()`](Self::split_to_max_length),
+    /// deaggregating short prefixes rather than aggregating long ones.
+    /// Prefixes of length `max` or longer are retained, so the address
+    /// space covered by the set is unchanged.
+    ///
+    /// Each range is deaggregated by raising its lower bound, so the
+    /// resulting set is no larger in memory than `self`. However, the number
+    /// of prefixes it contains grows exponentially in the difference
+    /// between `max` and the length of the shortest prefix. Consider
+    /// restricting `self` with [`trim_to()`](Self::trim_to) first,
+    /// especially for short IPv6 prefixes.
     ///
     /// ``` rust
-    /// # use ip::{Ipv6, Prefix};
+    /// # use ip::{Ipv4, PrefixLength};
     /// # use prefixset::{Error, PrefixSet};
     /// # fn main() -> Result<(), Error> {
-    /// let mut set = PrefixSet::new()
-    ///     .insert("2001:db8::/32".parse::<Prefix<Ipv6>>()?)
-    ///     .to_owned();
-    /// assert!(!set.is_empty());
-    /// set.clear();
-    /// assert!(set.is_empty());
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/15 192.0.2.0/25,25,26".parse()?;
+    /// let deaggregated = set.into_deaggregated(PrefixLength::<Ipv4>::from_primitive(16)?);
+    /// let expected: PrefixSet<Ipv4> = "10.0.0.0/15,16,16 192.0.2.0/25,25,26".parse()?;
+    /// assert_eq!(deaggregated, expected);
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn clear(&mut self) {
-        self.root = None
+    pub fn into_deaggregated(self, max: PrefixLength<A>) -> Self {
+        self.into_ranges()
+            .map(|range| {
+                // raising the bounds can't make them shorter than the length
+                // of the range's prefix.
+                PrefixRange::new(
+                    range.prefix(),
+                    range.lower().max(max)..=range.upper().max(max),
+                )
+                .unwrap()
+            })
+            .collect()
     }
 
-    /// Get an iterator over the [`PrefixRange<A>`](ip::concrete::PrefixRange)s
-    /// contained in `self`.
+    /// Consume `self`, returning an iterator over every host-length prefix
+    /// covered by `self`, i.e. every `/32` for IPv4 or `/128` for IPv6.
+    ///
+    /// This is equivalent to deaggregating `self` to the maximum prefix
+    /// length with [`into_deaggregated()`](Self::into_deaggregated), and is
+    /// useful for generating per-host entries, e.g. for ACLs. The iterator
+    /// yields one item per address covered, so the same caveats about
+    /// exponential growth apply.
     ///
     /// ``` rust
     /// # use ip::{Ipv4, Prefix};
     /// # use prefixset::{Error, PrefixSet};
     /// # fn main() -> Result<(), Error> {
-    /// let set = PrefixSet::new()
-    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
-    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/31 192.0.2.0/32".parse()?;
+    /// let hosts: Vec<_> = set.into_host_prefixes().collect();
+    /// assert_eq!(
+    ///     hosts,
+    ///     vec![
+    ///         "192.0.2.0/32".parse::<Prefix<Ipv4>>()?,
+    ///         "192.0.2.1/32".parse()?,
+    ///     ]
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn into_host_prefixes(self) -> impl Iterator<Item = Prefix<A>> {
+        self.into_deaggregated(PrefixLength::MAX).into_iter()
+    }
+
+    /// Aggregate the tree underlying `self` into its minimal form.
+    ///
+    /// Every public method that modifies `self` already aggregates the tree
+    /// before returning, so calling this is only necessary after lower level
+    /// manipulation of the tree. Aggregating an already aggregated set has
+    /// no effect.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
     ///     .to_owned();
-    /// let mut ranges = set.ranges();
-    /// assert_eq!(ranges.next(), Some("192.0.2.0/24,25,25".parse()?));
-    /// assert_eq!(ranges.next(), None);
+    /// let before = set.clone();
+    /// assert_eq!(set.aggregate(), &before);
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn ranges(&self) -> Ranges<A> {
-        self.into()
+    pub fn aggregate(&mut self) -> &mut Self {
+        // every mutation finishes by aggregating, so this is where any
+        // cached state derived from the tree is invalidated.
+        self.ranges_cache = None;
+        if let Some(root) = mem::take(&mut self.root) {
+            self.root = root.aggregate(None)
+        }
+        self.prefix_count = self.walk_prefix_count();
+        #[cfg(test)]
+        assert!(self.is_aggregated(), "not aggregated: {:?}", self);
+        self
     }
 
-    /// Get an iterator over the [`Prefix<A>`](ip::concrete::Prefix)s
-    /// contained in `self`.
+    /// Test whether the tree underlying `self` is in the minimal form
+    /// produced by [`aggregate()`](Self::aggregate).
+    ///
+    /// This is `false` only if some opportunity for aggregation remains,
+    /// which can happen only after lower level manipulation of the tree.
+    /// It is mostly useful as a debugging aid.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.3.0/24".parse()?;
+    /// assert!(set.is_aggregated());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn is_aggregated(&self) -> bool {
+        match &self.root {
+            Some(root) => root.is_aggregated(),
+            None => true,
+        }
+    }
+
+    /// Get an iterator over the ranges of `self`, without grouping sibling
+    /// prefixes under a covering prefix shorter than `floor`.
+    ///
+    /// The prefixes covered are the same as those of
+    /// [`ranges()`](Self::ranges), only grouped differently. Where `ranges()`
+    /// would express two sibling `/24`s as a single `/23` range, a `floor` of
+    /// `/24` yields them as two separate ranges. This is useful when the
+    /// ranges are used to generate policy that must not reference supernets
+    /// shorter than `floor`.
+    ///
+    /// Each range with a base prefix shorter than `floor` is split into
+    /// single-length ranges for its lengths below `floor`, and ranges based
+    /// on its subprefixes of length `floor` for the rest. Ranges that are
+    /// already based on a prefix of length `floor` or longer are yielded
+    /// unchanged. The split ranges are generated lazily, but their number
+    /// may grow exponentially in the difference between the two lengths.
+    /// A split range may share its base prefix with another range yielded,
+    /// so the result is not necessarily the fewest ranges possible.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.3.0/24".parse()?;
+    /// assert_eq!(set.ranges().count(), 1);
+    /// assert_eq!(set.bounded_ranges(24.try_into()?).count(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn bounded_ranges(
+        &self,
+        floor: PrefixLength<A>,
+    ) -> impl Iterator<Item = PrefixRange<A>> + '_ {
+        self.ranges()
+            .flat_map(move |range| Self::split_below(range, floor))
+    }
+
+    // lazily split `range` into ranges whose base prefix is no shorter than
+    // `floor`, unless they contain only a single prefix length below it.
+    fn split_below(
+        range: PrefixRange<A>,
+        floor: PrefixLength<A>,
+    ) -> impl Iterator<Item = PrefixRange<A>> {
+        let base = range.prefix();
+        let (lower, upper) = (range.lower(), range.upper());
+        // the length of the subprefixes of `base` that the lengths from
+        // `floor` upwards are grouped under.
+        let split = base.length().max(floor);
+        // ok to unwrap the `PrefixRange::new()` calls below, because each
+        // subprefix is of length `length` or `split`, which are within the
+        // bounds of the ranges constructed from them.
+        let short = core::iter::successors(Some(lower), |length| length.increment().ok())
+            .take_while(move |length| length <= &upper && length < &floor)
+            .flat_map(move |length| {
+                RangePrefixes::from(PrefixRange::new(base, length..=length).unwrap())
+                    .map(move |subprefix| PrefixRange::new(subprefix, length..=length).unwrap())
+            });
+        let long = (upper >= split)
+            .then(|| {
+                RangePrefixes::from(PrefixRange::new(base, split..=split).unwrap()).map(
+                    move |subprefix| PrefixRange::new(subprefix, lower.max(split)..=upper).unwrap(),
+                )
+            })
+            .into_iter()
+            .flatten();
+        short.chain(long)
+    }
+
+    /// Construct a new aggregated [`PrefixSet<A>`] with the same contents
+    /// as `self`.
+    ///
+    /// See [`PrefixSet::aggregate()`].
     ///
     /// ``` rust
     /// # use ip::{Ipv4, Prefix};
     /// # use prefixset::{Error, PrefixSet};
     /// # fn main() -> Result<(), Error> {
     /// let set = PrefixSet::new()
-    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
-    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
     ///     .to_owned();
-    /// let mut prefixes = set.prefixes();
-    /// assert_eq!(prefixes.next(), Some("192.0.2.0/25".parse()?));
-    /// assert_eq!(prefixes.next(), Some("192.0.2.128/25".parse()?));
-    /// assert_eq!(prefixes.next(), None);
+    /// assert_eq!(set.aggregated(), set);
     /// #     Ok(())
     /// # }
     /// ```
-    pub fn prefixes(&self) -> Prefixes<A> {
-        self.into()
+    pub fn aggregated(&self) -> Self {
+        self.clone().aggregate().to_owned()
     }
-}
 
-impl<A: Afi> Default for PrefixSet<A> {
+    /// Get the aggregated form of `self`, borrowing `self` if it is already
+    /// aggregated, and only cloning it otherwise.
+    ///
+    /// Every public constructor and mutating method leaves a set aggregated,
+    /// so this avoids the clone made by [`aggregated()`](Self::aggregated).
+    ///
+    /// ``` rust
+    /// # use std::borrow::Cow;
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(matches!(set.aggregated_cow(), Cow::Borrowed(_)));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn aggregated_cow(&self) -> Cow<'_, Self> {
+        if self.is_aggregated() {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.aggregated())
+        }
+    }
+
+    /// Test whether `prefix` is contained in `self`.
+    ///
+    /// This is a test for exact membership: `prefix` itself, with the same
+    /// length, must be a member of `self`. To test whether `prefix` falls
+    /// within the address space of some member of `self`, use
+    /// [`covers()`](Self::covers).
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,26,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(set.contains("192.0.2.128/26".parse()?));
+    /// assert!(!set.contains("192.0.2.128/27".parse()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn contains(&self, prefix: Prefix<A>) -> bool {
+        match &self.root {
+            Some(root) => root.search(&prefix.into()).is_some(),
+            None => false,
+        }
+    }
+
+    /// Test whether the host-length prefix (`/32` or `/128`) of `addr` is
+    /// contained in `self`.
+    ///
+    /// Like [`contains()`](Self::contains), this tests for exact
+    /// membership. To test whether `addr` falls within any member of `self`,
+    /// pass its host prefix to [`covers()`](Self::covers).
+    ///
+    /// ``` rust
+    /// # use ip::{Address, Ipv6, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("2001:db8::1/128".parse::<Prefix<Ipv6>>()?)
+    ///     .to_owned();
+    /// assert!(set.contains_host("2001:db8::1".parse::<Address<Ipv6>>()?));
+    /// assert!(!set.contains_host("2001:db8::2".parse::<Address<Ipv6>>()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn contains_host(&self, addr: Address<A>) -> bool {
+        self.contains(Self::host(addr))
+    }
+
+    // the host-length prefix of `addr`.
+    fn host(addr: Address<A>) -> Prefix<A> {
+        concrete::Prefix::new(addr, PrefixLength::MAX)
+    }
+
+    /// Test whether `prefix` is covered by `self`, i.e. whether some member
+    /// of `self` is equal to or shorter than `prefix` and contains its
+    /// address space.
+    ///
+    /// Unlike [`contains()`](Self::contains), `prefix` need not itself be
+    /// a member of `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(set.covers("192.0.2.128/26".parse()?));
+    /// assert!(!set.contains("192.0.2.128/26".parse()?));
+    /// assert!(!set.covers("192.0.0.0/16".parse()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn covers(&self, prefix: Prefix<A>) -> bool {
+        self.covering(prefix).next().is_some()
+    }
+
+    /// Test whether the address `addr` is covered by some member of `self`.
+    ///
+    /// This is equivalent to testing [`covers()`](Self::covers) with the
+    /// host-length prefix of `addr`.
+    ///
+    /// ``` rust
+    /// # use ip::{Address, Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::singleton("10.0.0.0/8".parse::<Prefix<Ipv4>>()?);
+    /// assert!(set.contains_address("10.1.2.3".parse::<Address<Ipv4>>()?));
+    /// assert!(!set.contains_address("11.0.0.1".parse::<Address<Ipv4>>()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn contains_address(&self, addr: Address<A>) -> bool {
+        self.covers(Self::host(addr))
+    }
+
+    /// Get an iterator over the gaps in the address space covered by
+    /// `self`, as the largest prefixes containing no address covered by any
+    /// member of `self`, in address order.
+    ///
+    /// Only the span from the first to the last address covered by `self`
+    /// is considered, rather than the whole address family: no gap is
+    /// reported before the lowest member or after the highest. To find the
+    /// uncovered prefixes within a given space, use
+    /// [`complement_within()`](Self::complement_within).
+    ///
+    /// Each gap is yielded as a [`PrefixRange<A>`] containing only its
+    /// base prefix.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "1.0.0.0/24 1.0.2.0/24".parse()?;
+    /// let gaps: Vec<_> = set.gaps().collect();
+    /// assert_eq!(gaps, vec!["1.0.1.0/24,24,24".parse::<PrefixRange<Ipv4>>()?]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn gaps(&self) -> impl Iterator<Item = PrefixRange<A>> {
+        let mut blocks = Vec::new();
+        if let Some(root) = &self.root {
+            self.find_gaps(root, *root.prefix(), &mut blocks);
+        }
+        // only keep the gaps between the first and last covered blocks.
+        let start = blocks
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or(blocks.len());
+        let end = blocks
+            .iter()
+            .rposition(Option::is_none)
+            .map_or(0, |i| i + 1);
+        blocks
+            .into_iter()
+            .take(end)
+            .skip(start)
+            .flatten()
+            .map(PrefixRange::from)
+    }
+
+    // split `prefix` into blocks that are either covered by `self`, pushed
+    // as `None`, or contain no covered address, pushed as `Some`, in address
+    // order.
+    fn find_gaps(&self, root: &Node<A>, prefix: Prefix<A>, blocks: &mut Vec<Option<Prefix<A>>>) {
+        if self.covered(&prefix) {
+            blocks.push(None);
+        } else if !root.overlaps(&prefix) {
+            blocks.push(Some(prefix));
+        } else {
+            // ok to unwrap, because a prefix that overlaps `self` without
+            // being covered has a covered subprefix, so is shorter than
+            // `MAX_LENGTH`.
+            let length = prefix.length().increment().unwrap();
+            prefix
+                .subprefixes(length)
+                .unwrap()
+                .for_each(|subprefix| self.find_gaps(root, subprefix, blocks));
+        }
+    }
+
+    /// Test whether every prefix yielded by `iter` is contained in `self`.
+    ///
+    /// Returns `true` for an empty iterator. Iteration stops at the first
+    /// prefix not contained in `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let queries: Vec<Prefix<Ipv4>> =
+    ///     vec!["192.0.2.0/25".parse()?, "192.0.2.192/26".parse()?];
+    /// assert!(set.contains_all(queries));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn contains_all<I>(&self, iter: I) -> bool
+    where
+        I: IntoIterator<Item = Prefix<A>>,
+    {
+        iter.into_iter().all(|prefix| self.contains(prefix))
+    }
+
+    /// Test whether any prefix yielded by `iter` is contained in `self`.
+    ///
+    /// Returns `false` for an empty iterator. Iteration stops at the first
+    /// prefix contained in `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let queries: Vec<Prefix<Ipv4>> =
+    ///     vec!["192.0.2.0/24".parse()?, "192.0.2.192/26".parse()?];
+    /// assert!(set.contains_any(queries));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn contains_any<I>(&self, iter: I) -> bool
+    where
+        I: IntoIterator<Item = Prefix<A>>,
+    {
+        iter.into_iter().any(|prefix| self.contains(prefix))
+    }
+
+    /// Test whether every prefix in `range` is contained in `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,27".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(set.contains_range("192.0.2.128/25,26,27".parse()?));
+    /// assert!(!set.contains_range("192.0.2.0/24,24,25".parse()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn contains_range(&self, range: PrefixRange<A>) -> bool {
+        match &self.root {
+            Some(root) => root.covers(&range.into()),
+            None => false,
+        }
+    }
+
+    /// Test whether every prefix in `range` is contained in `self`, however
+    /// those prefixes are distributed over the nodes of the underlying tree.
+    ///
+    /// [`contains_range()`](Self::contains_range) only inspects the nodes on
+    /// the path to the base prefix of `range`, which is sufficient for a
+    /// fully aggregated set. This method also descends below that prefix, so
+    /// it does not depend on the prefixes of `range` being held by a single
+    /// node. For the aggregated sets maintained by the public API, the two
+    /// methods agree.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/25 192.0.2.128/25".parse()?;
+    /// assert!(set.covers_all_of("192.0.2.0/24,25,25".parse()?));
+    /// assert!(!set.covers_all_of("192.0.2.0/24,24,25".parse()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn covers_all_of(&self, range: PrefixRange<A>) -> bool {
+        match &self.root {
+            Some(root) => root.covers_all(&range.into()),
+            None => false,
+        }
+    }
+
+    /// Find the most specific prefix in `self` that is equal to or covers
+    /// `prefix`.
+    ///
+    /// To look up a single address, pass a host-length prefix.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "1.0.0.0/8 1.2.0.0/16 1.2.3.0/24".parse()?;
+    /// assert_eq!(
+    ///     set.longest_match("1.2.3.4/32".parse()?),
+    ///     Some("1.2.3.0/24".parse()?)
+    /// );
+    /// assert_eq!(set.longest_match("2.0.0.0/8".parse()?), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn longest_match(&self, prefix: Prefix<A>) -> Option<Prefix<A>> {
+        self.covering(prefix).last()
+    }
+
+    /// Get an iterator over the prefixes in `self` that are equal to or
+    /// cover `prefix`, from least to most specific.
+    ///
+    /// To look up a single address, pass a host-length prefix.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "1.0.0.0/8 1.2.0.0/16,16,20".parse()?;
+    /// let covering: Vec<_> = set.covering("1.2.3.4/32".parse()?).collect();
+    /// assert_eq!(
+    ///     covering,
+    ///     vec![
+    ///         "1.0.0.0/8".parse::<Prefix<Ipv4>>()?,
+    ///         "1.2.0.0/16".parse()?,
+    ///         "1.2.0.0/17".parse()?,
+    ///         "1.2.0.0/18".parse()?,
+    ///         "1.2.0.0/19".parse()?,
+    ///         "1.2.0.0/20".parse()?,
+    ///     ]
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn covering(&self, prefix: Prefix<A>) -> Covering<A> {
+        Covering::new(self, prefix)
+    }
+
+    /// Get an iterator over the prefixes in `self` that are strict
+    /// supernets of `prefix`, in ascending order of length.
+    ///
+    /// This is [`covering()`](Self::covering), excluding `prefix` itself.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8 10.1.0.0/16 10.1.1.0/24".parse()?;
+    /// let mut supernets = set.supernets("10.1.1.0/24".parse()?);
+    /// assert_eq!(supernets.next(), Some("10.0.0.0/8".parse()?));
+    /// assert_eq!(supernets.next(), Some("10.1.0.0/16".parse()?));
+    /// assert_eq!(supernets.next(), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn supernets(&self, prefix: Prefix<A>) -> impl Iterator<Item = Prefix<A>> {
+        self.covering(prefix)
+            .take_while(move |supernet| supernet.length() < prefix.length())
+    }
+
+    /// Get an iterator over the prefixes in `self` that are strict subnets
+    /// of `prefix`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8 10.1.0.0/16 10.1.1.0/24".parse()?;
+    /// let mut subnets = set.subnets("10.1.0.0/16".parse()?);
+    /// assert_eq!(subnets.next(), Some("10.1.1.0/24".parse()?));
+    /// assert_eq!(subnets.next(), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn subnets(&self, prefix: Prefix<A>) -> impl Iterator<Item = Prefix<A>> {
+        self.matching(prefix, prefix.length(), PrefixLength::MAX)
+            .filter(move |subnet| subnet.length() > prefix.length())
+    }
+
+    /// Construct the set of prefixes in `self` that lie within the address
+    /// space of `space`, including `space` itself.
+    ///
+    /// This is equivalent to intersecting `self` with the range
+    /// `space,space.length(),MAX`, but only the path from the root to
+    /// `space` and the subtree below it are visited, rather than the whole
+    /// of `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8,8,16 192.0.2.0/24".parse()?;
+    /// let trimmed = set.trim_to("10.1.0.0/16".parse()?);
+    /// assert_eq!(trimmed.len(), 1);
+    /// assert!(trimmed.contains("10.1.0.0/16".parse()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn trim_to(&self, space: Prefix<A>) -> Self {
+        let root = self.root.as_ref().and_then(|root| root.trim_to(&space));
+        Self::new_with_root(root).aggregate().to_owned()
+    }
+
+    /// Get an iterator over the prefixes in `self` that are also in
+    /// `range`, i.e. that lie within the address space of its base prefix
+    /// and have a length between its bounds.
+    ///
+    /// As with [`trim_to()`](Self::trim_to), only the path to the base
+    /// prefix of `range` and the subtree below it are visited, and lengths
+    /// outside the bounds of `range` are discarded before any prefixes are
+    /// expanded.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8,8,24".parse()?;
+    /// let mut prefixes = set.prefixes_in("10.1.2.0/23,24,24".parse()?);
+    /// assert_eq!(prefixes.next(), Some("10.1.2.0/24".parse()?));
+    /// assert_eq!(prefixes.next(), Some("10.1.3.0/24".parse()?));
+    /// assert_eq!(prefixes.next(), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn prefixes_in(&self, range: PrefixRange<A>) -> impl Iterator<Item = Prefix<A>> {
+        self.intersect_range(range).into_iter()
+    }
+
+    /// Construct the intersection of `self` with the single prefix range
+    /// `range`.
+    ///
+    /// This is equivalent to `self & PrefixSet::singleton(range)`, but
+    /// avoids the general intersection of two trees: the subtree of `self`
+    /// below the base prefix of `range` is extracted as by
+    /// [`trim_to()`](Self::trim_to), and the lengths outside the bounds of
+    /// `range` are then masked out of each of its nodes.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8,8,24 192.0.2.0/24".parse()?;
+    /// let range = "10.1.0.0/16,20,22".parse::<PrefixRange<Ipv4>>()?;
+    /// assert_eq!(set.intersect_range(range.clone()), PrefixSet::singleton(range));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn intersect_range(&self, range: PrefixRange<A>) -> Self {
+        let mut root = self
+            .root
+            .as_ref()
+            .and_then(|root| root.trim_to(&range.prefix()));
+        if let Some(root) = &mut root {
+            root.retain_lengths(range.lower()..=range.upper());
+        }
+        Self::new_with_root(root).aggregate().to_owned()
+    }
+
+    /// Get an iterator over the prefixes in `self` of exactly `length`.
+    ///
+    /// Only the nodes of the underlying tree that contain prefixes of
+    /// `length` are expanded, so this is cheaper than filtering the output
+    /// of [`prefixes()`](Self::prefixes).
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("10.0.0.0/8,8,10".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(set.prefixes_with_length(9.try_into()?).count(), 2);
+    /// assert_eq!(set.prefixes_with_length(16.try_into()?).count(), 0);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn prefixes_with_length(
+        &self,
+        length: PrefixLength<A>,
+    ) -> impl Iterator<Item = Prefix<A>> + '_ {
+        self.root
+            .iter()
+            .flat_map(|root| root.children())
+            .filter(move |node| node.lengths().any(|l| l == length))
+            .flat_map(move |node| {
+                // ok to unwrap, because every length in a node's gluemap is
+                // at least the length of its prefix.
+                RangePrefixes::from(PrefixRange::new(*node.prefix(), length..=length).unwrap())
+            })
+    }
+
+    /// Test whether every prefix in `self` is also contained in `other`.
+    ///
+    /// Each node of the tree underlying `self` is looked up in `other` in
+    /// a single walk from the root, so this takes time proportional to the
+    /// number of nodes rather than the number of prefixes in `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let outer = PrefixSet::new()
+    ///     .insert("1.0.0.0/8,8,16".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let inner = PrefixSet::new()
+    ///     .insert("1.0.0.0/12,12,16".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(inner.is_subset(&outer));
+    /// assert!(!outer.is_subset(&inner));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn is_subset(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(r), Some(s)) => r.children().all(|node| s.covers(node)),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    /// Test whether every prefix in `other` is also contained in `self`.
+    ///
+    /// See [`PrefixSet::is_subset()`].
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let outer = PrefixSet::new()
+    ///     .insert("1.0.0.0/8,8,16".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(outer.is_superset(&PrefixSet::new()));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Test whether `self` and `other` have no prefixes in common.
+    ///
+    /// The trees underlying `self` and `other` are intersected one node of
+    /// `self` at a time, returning as soon as a common prefix length is
+    /// found, without enumerating the prefixes of either set.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let short = PrefixSet::new()
+    ///     .insert("1.0.0.0/8,8,11".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let long = PrefixSet::new()
+    ///     .insert("1.0.0.0/8,12,15".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(short.is_disjoint(&long));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(r), Some(s)) => !r.children().any(|node| {
+                s.intersect_nodes(node)
+                    .is_some_and(|new| new.children().any(|n| !n.is_glue()))
+            }),
+            _ => true,
+        }
+    }
+
+    /// Test whether any prefix in `self` covers, or is covered by, any
+    /// prefix in `other`.
+    ///
+    /// Unlike [`is_disjoint()`](Self::is_disjoint), which only considers
+    /// identical prefixes, this tests whether the two sets share any address
+    /// space.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let short = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let long = PrefixSet::new()
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert!(short.is_disjoint(&long));
+    /// assert!(short.overlaps(&long));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(r), Some(s)) => r
+                .children()
+                .filter(|node| !node.is_glue())
+                .any(|node| s.overlaps(node.prefix())),
+            _ => false,
+        }
+    }
+
+    /// Get the number of prefixes in `self`, saturating at `usize::MAX`.
+    ///
+    /// The count is maintained as `self` is modified, so this does not walk
+    /// the underlying tree.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,26,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(set.len(), 4);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn len(&self) -> usize {
+        self.count_prefixes().try_into().unwrap_or(usize::MAX)
+    }
+
+    /// Test whether `self` is empty.
+    ///
+    /// Aggregation removes any node that doesn't represent a prefix, unless
+    /// it is needed to join two subtrees, so this doesn't need to inspect
+    /// the tree.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// assert!(PrefixSet::<Ipv4>::new().is_empty());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Get the number of [`PrefixRange<A>`](ip::concrete::PrefixRange)s
+    /// in the aggregated representation of `self`.
+    ///
+    /// This is equal to `self.ranges().count()`, but uses the ranges cached
+    /// by [`materialize_ranges()`](Self::materialize_ranges) when present.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("198.51.100.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(set.count_ranges(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn count_ranges(&self) -> usize {
+        match (&self.ranges_cache, &self.root) {
+            (Some(ranges), _) => ranges.len(),
+            (None, Some(root)) => root.children().map(|node| node.ranges().count()).sum(),
+            (None, None) => 0,
+        }
+    }
+
+    /// Get the number of prefixes of each length contained in `self`.
+    ///
+    /// Counts are of individual prefixes, not of ranges: a range
+    /// `p/l,m,n` contributes `2^(k - l)` prefixes of each length
+    /// `m <= k <= n`. Counts saturate at `usize::MAX`. Lengths with no
+    /// prefixes are omitted.
+    ///
+    /// ``` rust
+    /// # use ip::{concrete::PrefixLength, Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let histogram = set.length_histogram();
+    /// assert_eq!(histogram.get(&PrefixLength::from_primitive(25)?), Some(&2));
+    /// assert_eq!(histogram.get(&PrefixLength::from_primitive(26)?), Some(&4));
+    /// assert_eq!(histogram.len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn length_histogram(&self) -> BTreeMap<PrefixLength<A>, usize> {
+        let mut histogram = BTreeMap::new();
+        if let Some(root) = &self.root {
+            root.children().for_each(|node| {
+                let base: usize = node.prefix().length().into_primitive().into();
+                node.lengths().for_each(|length| {
+                    let length_bits: usize = length.into_primitive().into();
+                    let offset = length_bits - base;
+                    let count = 1usize.checked_shl(offset as u32).unwrap_or(usize::MAX);
+                    histogram
+                        .entry(length)
+                        .and_modify(|n: &mut usize| *n = n.saturating_add(count))
+                        .or_insert(count);
+                })
+            })
+        }
+        histogram
+    }
+
+    /// Get the length of the shortest prefix contained in `self`, or `None`
+    /// if `self` is empty.
+    ///
+    /// The lengths present at each node are read directly from the tree,
+    /// without expanding any ranges.
+    ///
+    /// ``` rust
+    /// # use ip::{concrete::PrefixLength, Ipv4};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8,16,24 192.0.2.0/24".parse()?;
+    /// assert_eq!(set.min_length(), Some(PrefixLength::from_primitive(16)?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn min_length(&self) -> Option<PrefixLength<A>> {
+        self.root
+            .iter()
+            .flat_map(|root| root.children())
+            .filter_map(|node| node.min_length())
+            .min()
+    }
+
+    /// Get the length of the longest prefix contained in `self`, or `None`
+    /// if `self` is empty.
+    ///
+    /// See [`min_length()`](Self::min_length).
+    ///
+    /// ``` rust
+    /// # use ip::{concrete::PrefixLength, Ipv4};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8,16,24 192.0.2.0/24".parse()?;
+    /// assert_eq!(set.max_length(), Some(PrefixLength::from_primitive(24)?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn max_length(&self) -> Option<PrefixLength<A>> {
+        self.root
+            .iter()
+            .flat_map(|root| root.children())
+            .filter_map(|node| node.max_length())
+            .max()
+    }
+
+    /// Clear the contents of `self`
+    ///
+    /// Each node of the tree is a separate allocation, so no capacity is
+    /// retained: all of the memory held by `self` is released immediately.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv6, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("2001:db8::/32".parse::<Prefix<Ipv6>>()?)
+    ///     .to_owned();
+    /// assert!(!set.is_empty());
+    /// set.clear();
+    /// assert!(set.is_empty());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.ranges_cache = None;
+        self.prefix_count = 0;
+    }
+
+    /// Remove every prefix from `self`, returning them in an owning
+    /// iterator.
+    ///
+    /// The prefixes are yielded in the same order as by
+    /// [`prefixes()`](Self::prefixes), and `self` is left empty and ready for
+    /// re-use.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let drained: Vec<_> = set.drain().collect();
+    /// assert_eq!(drained, vec!["192.0.2.0/24".parse::<Prefix<Ipv4>>()?]);
+    /// assert!(set.is_empty());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn drain(&mut self) -> IntoPrefixes<A> {
+        mem::take(self).into_iter()
+    }
+
+    /// Release any excess capacity held by `self`.
+    ///
+    /// The nodes of the tree are allocated individually and never hold
+    /// spare capacity, so this only affects the ranges cached by
+    /// [`materialize_ranges()`](Self::materialize_ranges).
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// set.clear();
+    /// set.shrink_to_fit();
+    /// assert_eq!(set.heap_size(), 0);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        if let Some(ranges) = &mut self.ranges_cache {
+            ranges.shrink_to_fit();
+        }
+    }
+
+    /// Get an iterator over the [`PrefixRange<A>`](ip::concrete::PrefixRange)s
+    /// contained in `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut ranges = set.ranges();
+    /// assert_eq!(ranges.next(), Some("192.0.2.0/24,25,25".parse()?));
+    /// assert_eq!(ranges.next(), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn ranges(&self) -> Ranges<'_, A> {
+        self.into()
+    }
+
+    /// Get a consuming iterator over the
+    /// [`PrefixRange<A>`](ip::concrete::PrefixRange)s contained in `self`,
+    /// in the same order as yielded by [`PrefixSet::ranges()`].
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut ranges = set.into_ranges();
+    /// assert_eq!(ranges.next(), Some("192.0.2.0/24,25,25".parse()?));
+    /// assert_eq!(ranges.next(), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn into_ranges(self) -> IntoRanges<A> {
+        self.into()
+    }
+
+    /// Get an iterator over the nodes of the tree underlying `self` that
+    /// represent at least one prefix, yielding the prefix of each node
+    /// together with the prefix lengths it represents.
+    ///
+    /// This is a lower level view than [`ranges()`](Self::ranges): the
+    /// ranges yielded for a node are its lengths grouped into contiguous
+    /// runs. Nodes are yielded in the same order as their ranges, and glue
+    /// nodes, which only join two subtrees, are skipped.
+    ///
+    /// ``` rust
+    /// # use ip::{concrete::PrefixLength, Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8,8,9 10.0.0.0/8,16,16".parse()?;
+    /// let mut nodes = set.nodes();
+    /// let (prefix, lengths) = nodes.next().unwrap();
+    /// assert_eq!(prefix, "10.0.0.0/8".parse::<Prefix<Ipv4>>()?);
+    /// assert_eq!(
+    ///     lengths.map(PrefixLength::into_primitive).collect::<Vec<_>>(),
+    ///     vec![8, 9, 16]
+    /// );
+    /// assert!(nodes.next().is_none());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn nodes(&self) -> impl Iterator<Item = (Prefix<A>, Lengths<A>)> + '_ {
+        self.root
+            .iter()
+            .flat_map(|root| root.children())
+            .filter(|node| !node.is_glue())
+            .map(|node| (*node.prefix(), node.lengths()))
+    }
+
+    /// Get an iterator over the [`PrefixRange<A>`](ip::concrete::PrefixRange)s
+    /// contained in `self`, ordered by the length of their base prefix, then
+    /// by lower bound, and then by network address.
+    ///
+    /// Unlike the lazy [`PrefixSet::ranges()`], this collects and sorts all
+    /// of the ranges in `self` before returning.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "10.0.0.0/8,16,16 10.0.0.0/8 192.0.2.0/24".parse()?;
+    /// let ranges: Vec<_> = set.ranges_by_length().collect();
+    /// assert_eq!(
+    ///     ranges,
+    ///     vec![
+    ///         "10.0.0.0/8,8,8".parse::<PrefixRange<Ipv4>>()?,
+    ///         "10.0.0.0/8,16,16".parse()?,
+    ///         "192.0.2.0/24,24,24".parse()?,
+    ///     ]
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn ranges_by_length(&self) -> impl Iterator<Item = PrefixRange<A>> {
+        let mut ranges: Vec<_> = self.ranges().collect();
+        ranges.sort_by_key(|range| {
+            let prefix = range.prefix();
+            (prefix.length(), range.lower(), prefix.network())
+        });
+        ranges.into_iter()
+    }
+
+    /// Get an iterator over the [`Prefix<A>`](ip::concrete::Prefix)s
+    /// contained in `self`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut prefixes = set.prefixes();
+    /// assert_eq!(prefixes.next(), Some("192.0.2.0/25".parse()?));
+    /// assert_eq!(prefixes.next(), Some("192.0.2.128/25".parse()?));
+    /// assert_eq!(prefixes.next(), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn prefixes(&self) -> Prefixes<'_, A> {
+        self.into()
+    }
+
+    /// Get the `n`th prefix yielded by [`prefixes()`](Self::prefixes),
+    /// without enumerating the preceding prefixes.
+    ///
+    /// Nodes of the tree are skipped using the number of prefixes they
+    /// contain, which is computed from their prefix lengths, so this takes
+    /// time `O(nodes × length bits)`, rather than `O(n)`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("10.0.0.0/8,24,24".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(set.nth_prefix(65_535), Some("10.255.255.0/24".parse()?));
+    /// assert_eq!(set.nth_prefix(65_536), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn nth_prefix(&self, n: usize) -> Option<Prefix<A>> {
+        self.nth_prefix_wide(n as u128)
+    }
+
+    // the `n`th prefix yielded by `prefixes()`, indexed by `u128` so that
+    // every prefix in an IPv6 set is reachable.
+    fn nth_prefix_wide(&self, mut n: u128) -> Option<Prefix<A>> {
+        for node in self.root.as_ref()?.children() {
+            let count = node.prefix_count();
+            if n >= count {
+                n -= count;
+                continue;
+            }
+            let base: usize = node.prefix().length().into_primitive().into();
+            for length in node.lengths() {
+                let l: usize = length.into_primitive().into();
+                let count = 1u128.checked_shl((l - base) as u32).unwrap_or(u128::MAX);
+                if n < count {
+                    return Some(RangePrefixes::nth_subprefix(*node.prefix(), length, n));
+                }
+                n -= count;
+            }
+        }
+        None
+    }
+
+    /// Get an iterator over the [`Prefix<A>`](ip::concrete::Prefix)s in
+    /// `self` that would be permitted by a prefix-list entry of the form
+    /// `permit <base> ge <ge> le <le>`.
+    ///
+    /// That is, the prefixes in `self` that are covered by `base`, and whose
+    /// length falls within `ge..=le`. If `ge` is shorter than the length of
+    /// `base`, or longer than `le`, then no prefixes are matched.
+    ///
+    /// The matching subtree of `self` is extracted up front, but its
+    /// prefixes are only enumerated as the iterator is advanced, so wide
+    /// length bands are cheap to match as long as only a few prefixes are
+    /// consumed.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,24,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut matching = set.matching(
+    ///     "192.0.2.0/25".parse()?,
+    ///     26.try_into()?,
+    ///     28.try_into()?,
+    /// );
+    /// assert_eq!(matching.next(), Some("192.0.2.0/26".parse()?));
+    /// assert_eq!(matching.next(), Some("192.0.2.64/26".parse()?));
+    /// assert_eq!(matching.next(), None);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn matching(
+        &self,
+        base: Prefix<A>,
+        ge: PrefixLength<A>,
+        le: PrefixLength<A>,
+    ) -> impl Iterator<Item = Prefix<A>> {
+        let root = match (&self.root, PrefixRange::new(base, ge..=le)) {
+            (Some(root), Ok(range)) => root.intersect_nodes(&range.into()),
+            _ => None,
+        };
+        let mut matched = Self::new_with_root(root);
+        matched.aggregate();
+        matched.into_iter()
+    }
+
+    /// Get a slice of the [`PrefixRange<A>`](ip::concrete::PrefixRange)s
+    /// contained in `self`, in the same order as yielded by
+    /// [`PrefixSet::ranges()`].
+    ///
+    /// The ranges are collected the first time this method is called, and
+    /// cached so that subsequent calls return the slice directly without
+    /// walking the tree again. The cache is discarded whenever `self` is
+    /// mutated (e.g. by [`PrefixSet::insert()`], [`PrefixSet::remove()`] or
+    /// [`PrefixSet::clear()`]), and re-computed on the next call.
+    ///
+    /// This trades the memory required to hold the ranges for faster
+    /// repeated reads.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(set.materialize_ranges(), ["192.0.2.0/25,25,25".parse()?]);
+    /// set.insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?);
+    /// assert_eq!(set.materialize_ranges(), ["192.0.2.0/24,25,25".parse()?]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn materialize_ranges(&mut self) -> &[PrefixRange<A>] {
+        if self.ranges_cache.is_none() {
+            self.ranges_cache = Some(self.ranges().collect());
+        }
+        // ok to unwrap, because the cache was populated above.
+        self.ranges_cache.as_deref().unwrap()
+    }
+
+    /// Collect the [`PrefixRange<A>`](ip::concrete::PrefixRange)s contained
+    /// in `self` into a [`Vec`], in the same order as yielded by
+    /// [`PrefixSet::ranges()`].
+    ///
+    /// If the ranges have been cached by
+    /// [`materialize_ranges()`](Self::materialize_ranges), the cache is
+    /// cloned rather than walking the tree.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24,25,26".parse()?;
+    /// assert_eq!(set.to_ranges_vec(), vec!["192.0.2.0/24,25,26".parse::<PrefixRange<Ipv4>>()?]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn to_ranges_vec(&self) -> Vec<PrefixRange<A>> {
+        match &self.ranges_cache {
+            Some(ranges) => ranges.clone(),
+            None => self.ranges().collect(),
+        }
+    }
+
+    /// Collect the prefixes contained in `self` into a [`Vec`], in the same
+    /// order as yielded by [`PrefixSet::prefixes()`].
+    ///
+    /// The vector is allocated up front with room for exactly
+    /// [`len()`](Self::len) prefixes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of prefixes in `self` exceeds the capacity of a
+    /// [`Vec`], as may be the case for large IPv6 sets.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24,25,26".parse()?;
+    /// let prefixes = set.to_prefixes_vec();
+    /// assert_eq!(prefixes.len(), 6);
+    /// assert_eq!(prefixes.capacity(), 6);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn to_prefixes_vec(&self) -> Vec<Prefix<A>> {
+        let mut prefixes = Vec::with_capacity(self.len());
+        prefixes.extend(self.prefixes());
+        prefixes
+    }
+
+    // get the number of prefixes in `self`, saturating at `u128::MAX`.
+    fn count_prefixes(&self) -> u128 {
+        #[cfg(test)]
+        assert_eq!(
+            self.prefix_count,
+            self.walk_prefix_count(),
+            "stale prefix count: {:#?}",
+            self
+        );
+        self.prefix_count
+    }
+
+    // count the prefixes in the tree underlying `self` per node, without
+    // enumerating them.
+    fn walk_prefix_count(&self) -> u128 {
+        self.root.as_ref().map_or(0, |root| {
+            root.children()
+                .map(Node::prefix_count)
+                .fold(0, u128::saturating_add)
+        })
+    }
+
+    /// Get the depth of the tree underlying `self`, i.e. the number of edges
+    /// on the longest path from the root node to a leaf.
+    ///
+    /// An empty set, or a set represented by a single node, has depth `0`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new();
+    /// set.insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?);
+    /// assert_eq!(set.depth(), 0);
+    /// set.insert("192.0.2.128/26".parse::<Prefix<Ipv4>>()?);
+    /// assert_eq!(set.depth(), 1);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.depth())
+    }
+
+    /// Get the total number of nodes in the tree underlying `self`,
+    /// including any glue nodes needed to join subtrees.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new();
+    /// set.insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?);
+    /// assert_eq!(set.node_count(), 1);
+    /// set.insert("198.51.100.0/24".parse::<Prefix<Ipv4>>()?);
+    /// assert_eq!(set.node_count(), 3);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.children().count())
+    }
+
+    /// Get an estimate of the number of bytes of heap memory held by `self`.
+    ///
+    /// This is the sum of the sizes of the tree's nodes, each of which is
+    /// individually boxed, plus the capacity of any ranges cached by
+    /// [`materialize_ranges()`](Self::materialize_ranges). It excludes any
+    /// overhead or padding added by the allocator, so the true figure will be
+    /// somewhat higher.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set = PrefixSet::new();
+    /// assert_eq!(set.heap_size(), 0);
+    /// set.insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?);
+    /// assert!(set.heap_size() > 0);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn heap_size(&self) -> usize {
+        let nodes: usize = self
+            .root
+            .as_ref()
+            .map_or(0, |root| root.children().map(mem::size_of_val).sum());
+        let cache = self.ranges_cache.as_ref().map_or(0, |ranges| {
+            ranges.capacity() * mem::size_of::<PrefixRange<A>>()
+        });
+        nodes + cache
+    }
+
+    /// Serialize `self` into a plain byte representation.
+    ///
+    /// The encoding consists of a single byte identifying the address
+    /// family, followed by a pre-order walk of the nodes of the underlying
+    /// tree, each containing its full prefix and prefix length ranges.
+    ///
+    /// Use [`PrefixSet::from_bytes()`] to reconstruct the set. See also
+    /// [`PrefixSet::to_bytes_delta()`] for a more compact encoding.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let bytes = set.to_bytes();
+    /// assert_eq!(bytes, [32, 0x10, 24, 192, 0, 2, 0]);
+    /// assert_eq!(PrefixSet::from_bytes(&bytes)?, set);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![Self::family_tag()];
+        if let Some(root) = &self.root {
+            root.encode_plain(&mut buf);
+        }
+        buf
+    }
+
+    /// Construct a new [`PrefixSet<A>`] from bytes produced by
+    /// [`PrefixSet::to_bytes()`].
+    ///
+    /// An error is returned if `bytes` is truncated or malformed, contains
+    /// trailing data, or was encoded for a different address family.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Ipv6, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let bytes = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_bytes();
+    /// assert!(PrefixSet::<Ipv4>::from_bytes(&bytes).is_ok());
+    /// assert!(PrefixSet::<Ipv6>::from_bytes(&bytes).is_err());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::decode_with(bytes, Node::decode_plain)
+    }
+
+    /// Serialize `self` into a compact, delta-encoded byte representation.
+    ///
+    /// The layout follows that of [`PrefixSet::to_bytes()`], except that
+    /// each node's prefix is stored as the difference from its parent, so
+    /// that only the bits below the parent's prefix length are written.
+    /// This makes the encoding considerably smaller for deep trees.
+    ///
+    /// Use [`PrefixSet::from_bytes_delta()`] to reconstruct the set.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv6, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("2001:db8::/32".parse::<Prefix<Ipv6>>()?)
+    ///     .insert("2001:db8:f00::/48".parse::<Prefix<Ipv6>>()?)
+    ///     .to_owned();
+    /// let bytes = set.to_bytes_delta();
+    /// assert!(bytes.len() < set.to_bytes().len());
+    /// assert_eq!(PrefixSet::from_bytes_delta(&bytes)?, set);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes_delta(&self) -> Vec<u8> {
+        let mut buf = vec![Self::family_tag()];
+        if let Some(root) = &self.root {
+            root.encode_delta(&mut buf);
+        }
+        buf
+    }
+
+    /// Construct a new [`PrefixSet<A>`] from bytes produced by
+    /// [`PrefixSet::to_bytes_delta()`].
+    ///
+    /// An error is returned if `bytes` is truncated or malformed, contains
+    /// trailing data, or was encoded for a different address family.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Ipv6, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let bytes = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_bytes_delta();
+    /// assert!(PrefixSet::<Ipv4>::from_bytes_delta(&bytes).is_ok());
+    /// assert!(PrefixSet::<Ipv6>::from_bytes_delta(&bytes).is_err());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn from_bytes_delta(bytes: &[u8]) -> Result<Self, Error> {
+        Self::decode_with(bytes, Node::decode_delta)
+    }
+
+    /// Write `self` to `w` in a versioned binary format, suitable for
+    /// storing on disk or sending over the network.
+    ///
+    /// The layout is independent of the encodings produced by
+    /// [`PrefixSet::to_bytes()`] and [`PrefixSet::to_bytes_delta()`], and is:
+    ///
+    /// - the four magic bytes `PFXS`;
+    /// - a single format version byte, currently `2`;
+    /// - a single address family byte, holding the maximum prefix length of
+    ///   the address family: `32` for IPv4 or `128` for IPv6;
+    /// - the number of records that follow, as an unsigned 64-bit integer
+    ///   in big-endian byte order;
+    /// - one record for each range yielded by [`PrefixSet::ranges()`], in
+    ///   the same order.
+    ///
+    /// Each record is a fixed size, and holds, in order:
+    ///
+    /// - the network address of the range's covering prefix, as 4 (IPv4) or
+    ///   16 (IPv6) bytes in network byte order;
+    /// - the length of the covering prefix, as a single byte;
+    /// - the lower and upper bounds of the range's prefix lengths, as a
+    ///   single byte each.
+    ///
+    /// Use [`PrefixSet::read_binary()`] to reconstruct the set.
+    ///
+    /// # Errors
+    ///
+    /// Any error returned by `w` is propagated.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let mut buf = Vec::new();
+    /// set.write_binary(&mut buf)?;
+    /// assert_eq!(&buf[..6], b"PFXS\x02\x20");
+    /// assert_eq!(&buf[6..14], 1u64.to_be_bytes());
+    /// assert_eq!(&buf[14..], [192, 0, 2, 0, 24, 24, 24]);
+    /// assert_eq!(PrefixSet::read_binary(buf.as_slice())?, set);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_binary<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(BINARY_MAGIC)?;
+        w.write_all(&[BINARY_VERSION, Self::family_tag()])?;
+        w.write_all(&(self.count_ranges() as u64).to_be_bytes())?;
+        self.ranges().try_for_each(|range| {
+            let prefix = range.prefix();
+            w.write_all(core::borrow::Borrow::borrow(&prefix.network().octets()))?;
+            w.write_all(&[prefix.length(), range.lower(), range.upper()].map(length_to_byte))
+        })
+    }
+
+    /// Construct a new [`PrefixSet<A>`] from the binary format written by
+    /// [`PrefixSet::write_binary()`], reading `r` to the end.
+    ///
+    /// # Errors
+    ///
+    /// Any error returned by `r` is propagated. An error of kind
+    /// [`io::ErrorKind::InvalidData`] is returned if the magic bytes or
+    /// version are not recognised, if the data was written for a different
+    /// address family, if the number of records does not match the record
+    /// count, or if any record does not hold a valid range.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Ipv6, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut buf = Vec::new();
+    /// PrefixSet::new()
+    ///     .insert("2001:db8::/32".parse::<Prefix<Ipv6>>()?)
+    ///     .write_binary(&mut buf)?;
+    /// assert!(PrefixSet::<Ipv6>::read_binary(buf.as_slice()).is_ok());
+    /// assert!(PrefixSet::<Ipv4>::read_binary(buf.as_slice()).is_err());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn read_binary<R: Read>(mut r: R) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf)?;
+        let bytes = match buf.strip_prefix(BINARY_MAGIC) {
+            Some([BINARY_VERSION, bytes @ ..]) => bytes,
+            Some(_) => return Err(invalid_data("unsupported binary format version")),
+            None => return Err(invalid_data("missing binary format magic bytes")),
+        };
+        let bytes = match bytes.split_first() {
+            Some((&tag, rest)) if tag == Self::family_tag() => rest,
+            _ => return Err(invalid_data("missing or mismatched address family")),
+        };
+        let (count, records) = bytes
+            .split_first_chunk()
+            .ok_or_else(|| invalid_data("missing record count"))?;
+        // each record is the network address octets, followed by three
+        // prefix length bytes.
+        let octets = mem::size_of::<A::Octets>();
+        let records = records.chunks_exact(octets + 3);
+        if !records.remainder().is_empty() || records.len() as u64 != u64::from_be_bytes(*count) {
+            return Err(invalid_data("record count does not match records"));
+        }
+        records
+            .map(|record| {
+                let (network, lengths) = record.split_at(octets);
+                // ok to unwrap, because `network` is exactly
+                // `A::Octets::LENGTH` bytes long.
+                let network = Address::<A>::from_slice(network).unwrap();
+                let [length, lower, upper] = [lengths[0], lengths[1], lengths[2]].map(|byte| {
+                    PrefixLength::try_from(usize::from(byte))
+                        .map_err(|_| invalid_data("prefix length out of bounds"))
+                });
+                let prefix = concrete::Prefix::new(network, length?);
+                if prefix.network() != network {
+                    return Err(invalid_data("host bits set in network address"));
+                }
+                PrefixRange::new(prefix, lower?..=upper?)
+                    .map_err(|_| invalid_data("invalid prefix length range"))
+            })
+            .collect()
+    }
+
+    fn decode_with<F>(mut bytes: &[u8], decode: F) -> Result<Self, Error>
+    where
+        F: FnOnce(&mut &[u8]) -> Result<Box<Node<A>>, Error>,
+    {
+        match bytes.split_first() {
+            Some((&tag, rest)) if tag == Self::family_tag() => bytes = rest,
+            _ => {
+                return Err(Error::Decode {
+                    reason: "missing or mismatched address family",
+                })
+            }
+        }
+        let root = if bytes.is_empty() {
+            None
+        } else {
+            Some(decode(&mut bytes)?)
+        };
+        if !bytes.is_empty() {
+            return Err(Error::Decode {
+                reason: "trailing bytes after encoded tree",
+            });
+        }
+        Ok(Self::new_with_root(root).aggregate().to_owned())
+    }
+
+    fn family_tag() -> u8 {
+        // the maximum prefix length uniquely identifies the address family.
+        PrefixLength::<A>::MAX.into_primitive().into() as u8
+    }
+}
+
+// leading bytes of the format written by `PrefixSet::write_binary()`. the
+// version is bumped whenever the documented layout changes.
+#[cfg(feature = "std")]
+const BINARY_MAGIC: &[u8] = b"PFXS";
+#[cfg(feature = "std")]
+const BINARY_VERSION: u8 = 2;
+
+#[cfg(feature = "std")]
+fn invalid_data(reason: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+#[cfg(feature = "std")]
+fn length_to_byte<A: Afi>(length: PrefixLength<A>) -> u8 {
+    // lengths are at most 128, so this never truncates.
+    Into::<usize>::into(length.into_primitive()) as u8
+}
+
+impl<A: Afi> Default for PrefixSet<A> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Prints a summary of the prefix and range counts and the address family,
+/// or, in the alternate `{:#?}` form, the entire underlying tree.
+///
+/// ``` rust
+/// # use ip::Ipv4;
+/// # use prefixset::{Error, PrefixSet};
+/// # fn main() -> Result<(), Error> {
+/// let set: PrefixSet<Ipv4> = "192.0.2.0/24,24,25".parse()?;
+/// assert_eq!(
+///     format!("{set:?}"),
+///     "PrefixSet { prefixes: 3, ranges: 1, family: Ipv4 }"
+/// );
+/// #     Ok(())
+/// # }
+/// ```
+impl<A: Afi> fmt::Debug for PrefixSet<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.debug_struct("PrefixSet")
+                .field("root", &self.root)
+                .field("ranges_cache", &self.ranges_cache)
+                .finish()
+        } else {
+            f.debug_struct("PrefixSet")
+                .field("prefixes", &self.len())
+                .field("ranges", &self.count_ranges())
+                .field("family", &A::as_afi())
+                .finish()
+        }
+    }
+}
+
 impl<A: Afi, U> Extend<U> for PrefixSet<A>
 where
     U: Into<Node<A>>,
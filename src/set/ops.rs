@@ -1,27 +1,401 @@
-use std::cmp::{Ordering, PartialEq, PartialOrd};
-use std::ops::{Add, BitAnd, BitOr, BitXor, Mul, Not, Sub};
+use alloc::borrow::ToOwned;
+use core::cmp::{Ordering, PartialEq, PartialOrd};
+use core::hash::{Hash, Hasher};
+use core::iter::{Product, Sum};
+use core::ops::{
+    Add, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Mul, Not, Sub, SubAssign,
+};
 
-use ip::Afi;
+use ip::{concrete::PrefixRange, Afi};
 
 use num::{One, Zero};
 
 use super::PrefixSet;
 
+impl<A: Afi> PrefixSet<A> {
+    /// Construct the union of `self` and `other`, without consuming either.
+    ///
+    /// This is equivalent to `self.clone() | other.clone()`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let s = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let t = PrefixSet::new()
+    ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(s.union(&t).len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn union(&self, other: &Self) -> Self {
+        match (&self.root, &other.root) {
+            (Some(r), Some(s)) => Self::new_with_root(r.to_owned() | s.to_owned())
+                .aggregate()
+                .to_owned(),
+            (Some(_), None) => self.clone(),
+            (None, _) => other.clone(),
+        }
+    }
+
+    /// Construct the intersection of `self` and `other`, without consuming
+    /// either.
+    ///
+    /// This is equivalent to `self.clone() & other.clone()`, but neither
+    /// tree is cloned.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let s = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,24,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let t = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,26,28".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(s.intersection(&t).len(), 4);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn intersection(&self, other: &Self) -> Self {
+        match (&self.root, &other.root) {
+            (Some(r), Some(s)) => Self::new_with_root(r.intersect(s)).aggregate().to_owned(),
+            _ => Self::new(),
+        }
+    }
+
+    /// Construct the set of prefixes in `self` but not in `other`, without
+    /// consuming either.
+    ///
+    /// This is equivalent to `self.clone() - other.clone()`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let s = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,25".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let t = PrefixSet::new()
+    ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(s.difference(&t).len(), 1);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn difference(&self, other: &Self) -> Self {
+        match (&self.root, &other.root) {
+            (Some(r), Some(s)) => Self::new_with_root(Some(r.to_owned().remove(s)))
+                .aggregate()
+                .to_owned(),
+            _ => self.clone(),
+        }
+    }
+
+    /// Remove every prefix in `other` from `self`, in place.
+    ///
+    /// This is equivalent to `*self = self.clone() - other.clone()`, but
+    /// neither tree is cloned.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set: PrefixSet<Ipv4> = "192.0.2.0/24,24,25".parse()?;
+    /// set.difference_update(&"192.0.2.128/25".parse()?);
+    /// assert_eq!(set.len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn difference_update(&mut self, other: &Self) -> &mut Self {
+        self.root = match (self.root.take(), &other.root) {
+            (Some(r), Some(s)) => Some(r.remove(s)),
+            (r, _) => r,
+        };
+        self.aggregate()
+    }
+
+    /// Remove every prefix not in `other` from `self`, in place.
+    ///
+    /// This is equivalent to `*self = self.clone() & other.clone()`, but
+    /// neither tree is cloned.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut set: PrefixSet<Ipv4> = "192.0.2.0/24,24,26".parse()?;
+    /// set.intersect_update(&"192.0.2.0/24,26,28".parse()?);
+    /// assert_eq!(set.len(), 4);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn intersect_update(&mut self, other: &Self) -> &mut Self {
+        self.root = match (&self.root, &other.root) {
+            (Some(r), Some(s)) => r.intersect(s),
+            _ => None,
+        };
+        self.aggregate()
+    }
+
+    /// Construct the set of prefixes in exactly one of `self` and `other`,
+    /// without consuming either.
+    ///
+    /// This is equivalent to `self.clone() ^ other.clone()`.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let s = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,24,25".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let t = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(s.symmetric_difference(&t).len(), 5);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.union(other).difference(&self.intersection(other))
+    }
+
+    /// Construct the set containing every prefix of address family `A`.
+    ///
+    /// This is the multiplicative identity returned by
+    /// [`One::one()`](num::One::one), and the set against which the
+    /// complement `!self` is taken.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv6, Prefix};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let universe = PrefixSet::<Ipv6>::universe();
+    /// assert!(universe.contains("::/0".parse()?));
+    /// assert!(universe.contains("2001:db8::1/128".parse()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn universe() -> Self {
+        Self::new()
+            .insert(<A as ip::AfiClass>::PrefixRange::ALL)
+            .to_owned()
+    }
+
+    /// Construct the union of every set yielded by `sets`.
+    ///
+    /// The trees of the sets are merged in place, without cloning, and the
+    /// result is aggregated once at the end, rather than after each merge
+    /// as when folding with `|`. The union of no sets is the empty set.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let sets = ["192.0.2.0/25", "192.0.2.128/25", "198.51.100.0/24"]
+    ///     .into_iter()
+    ///     .map(str::parse::<PrefixSet<Ipv4>>)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// let union = PrefixSet::union_all(sets);
+    /// assert_eq!(union.len(), 3);
+    /// assert_eq!(union.ranges().count(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn union_all<I>(sets: I) -> Self
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let root = sets
+            .into_iter()
+            .filter_map(|set| set.root)
+            .reduce(|root, other| root.add(other));
+        Self::new_with_root(root).aggregate().to_owned()
+    }
+
+    /// Construct the intersection of every set yielded by `sets`.
+    ///
+    /// As with [`union_all()`](Self::union_all), the result is aggregated
+    /// once at the end. Iteration stops early if the intersection becomes
+    /// empty. The intersection of no sets is [`universe()`](Self::universe).
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let sets = ["10.0.0.0/8,8,16", "10.0.0.0/8,16,24", "10.1.0.0/16"]
+    ///     .into_iter()
+    ///     .map(str::parse::<PrefixSet<Ipv4>>)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// let intersection = PrefixSet::intersection_all(sets);
+    /// assert_eq!(intersection.len(), 1);
+    /// assert!(intersection.contains("10.1.0.0/16".parse()?));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn intersection_all<I>(sets: I) -> Self
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let mut sets = sets.into_iter();
+        let mut root = match sets.next() {
+            Some(set) => set.root,
+            None => return Self::universe(),
+        };
+        for set in sets {
+            // the accumulated tree is left unaggregated, so it is the side
+            // whose nodes are visited, and each new set is searched.
+            root = match (&root, &set.root) {
+                (Some(r), Some(s)) => r.intersect(s),
+                _ => None,
+            };
+            if root.is_none() {
+                break;
+            }
+        }
+        Self::new_with_root(root).aggregate().to_owned()
+    }
+
+    /// Construct the complement of `self` within `space`, i.e. the set of
+    /// prefixes in `space` that are not in `self`.
+    ///
+    /// This is the practical alternative to `!self`, which takes the
+    /// complement with respect to every prefix of the address family, and
+    /// is prohibitively large to compute for most sets.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, Prefix, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let s = PrefixSet::new()
+    ///     .insert("10.0.0.0/9".parse::<Prefix<Ipv4>>()?)
+    ///     .to_owned();
+    /// let complement = s.complement_within("10.0.0.0/8,9,9".parse::<PrefixRange<Ipv4>>()?);
+    /// assert!(complement.contains("10.128.0.0/9".parse()?));
+    /// assert_eq!(complement.len(), 1);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn complement_within(&self, space: PrefixRange<A>) -> Self {
+        Self::new().insert(space).difference(self)
+    }
+
+    /// Describe how `self` relates to `other` as sets of prefixes.
+    ///
+    /// This is a more informative alternative to
+    /// [`partial_cmp()`](PartialOrd::partial_cmp), which returns `None` for
+    /// both [`SetRelation::Overlapping`] and [`SetRelation::Disjoint`] sets.
+    ///
+    /// The nodes of `self` are walked once, testing each for both coverage
+    /// by and intersection with `other`. The nodes of `other` are then only
+    /// walked if that is needed to tell a subset from an equal set, or a
+    /// superset from an overlapping one.
+    ///
+    /// The empty set is a [`SetRelation::Subset`] of every non-empty set.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{set::SetRelation, Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let s = PrefixSet::new()
+    ///     .insert("10.0.0.0/8,8,16".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let t = PrefixSet::new()
+    ///     .insert("10.0.0.0/8,16,24".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// assert_eq!(s.relation(&t), SetRelation::Overlapping);
+    /// assert_eq!(s.relation(&(&s | &t)), SetRelation::Subset);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn relation(&self, other: &Self) -> SetRelation {
+        let (r, s) = match (&self.root, &other.root) {
+            (Some(r), Some(s)) => (r, s),
+            (None, None) => return SetRelation::Equal,
+            (None, Some(_)) => return SetRelation::Subset,
+            (Some(_), None) => return SetRelation::Superset,
+        };
+        let (covered, shared) = r.children().fold((true, false), |(covered, shared), node| {
+            (
+                covered && s.covers(node),
+                shared
+                    || s.intersect_nodes(node)
+                        .is_some_and(|new| new.children().any(|n| !n.is_glue())),
+            )
+        });
+        match (covered, shared) {
+            (_, false) => SetRelation::Disjoint,
+            (true, true) if s.children().all(|node| r.covers(node)) => SetRelation::Equal,
+            (true, true) => SetRelation::Subset,
+            (false, true) if s.children().all(|node| r.covers(node)) => SetRelation::Superset,
+            (false, true) => SetRelation::Overlapping,
+        }
+    }
+}
+
+/// The relationship between two [`PrefixSet<A>`]s, returned by
+/// [`PrefixSet::relation()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SetRelation {
+    /// Both sets contain exactly the same prefixes.
+    Equal,
+    /// Every prefix in `self` is in `other`, but not vice versa.
+    Subset,
+    /// Every prefix in `other` is in `self`, but not vice versa.
+    Superset,
+    /// The sets have some, but not all, prefixes in common.
+    Overlapping,
+    /// The sets have no prefixes in common.
+    Disjoint,
+}
+
+/// The empty set is the additive identity, with union as addition.
+///
+/// Note that this is not the same as `PrefixSet::one()`, which contains
+/// every prefix and is the identity for intersection.
 impl<A: Afi> Zero for PrefixSet<A> {
     fn zero() -> Self {
         Self::new()
     }
 
     fn is_zero(&self) -> bool {
-        self.root.is_some()
+        self.root.is_none()
     }
 }
 
+/// The set containing every prefix is the multiplicative identity, with
+/// intersection as multiplication.
+///
+/// See [`PrefixSet::universe()`].
 impl<A: Afi> One for PrefixSet<A> {
     fn one() -> Self {
-        Self::new()
-            .insert(<A as ip::AfiClass>::PrefixRange::ALL)
-            .to_owned()
+        Self::universe()
+    }
+}
+
+/// Sums an iterator of sets to their union, which is the empty set for an
+/// empty iterator.
+///
+/// This is [`PrefixSet::union_all()`], and avoids the intermediate
+/// aggregation of folding with `+`.
+impl<A: Afi> Sum for PrefixSet<A> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self::union_all(iter)
+    }
+}
+
+/// Multiplies an iterator of sets to their intersection, which is
+/// [`PrefixSet::universe()`] for an empty iterator.
+///
+/// This is [`PrefixSet::intersection_all()`].
+impl<A: Afi> Product for PrefixSet<A> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self::intersection_all(iter)
     }
 }
 
@@ -29,10 +403,7 @@ impl<A: Afi> BitAnd for PrefixSet<A> {
     type Output = Self;
 
     fn bitand(self, rhs: Self) -> Self::Output {
-        match (self.root, rhs.root) {
-            (Some(r), Some(s)) => Self::Output::new_with_root(r & s).aggregate().to_owned(),
-            _ => PrefixSet::zero(),
-        }
+        self.intersection(&rhs)
     }
 }
 
@@ -55,7 +426,7 @@ impl<A: Afi> BitXor for PrefixSet<A> {
     type Output = Self;
 
     fn bitxor(self, rhs: Self) -> Self::Output {
-        (self.to_owned() | rhs.to_owned()) - (self & rhs)
+        self.symmetric_difference(&rhs)
     }
 }
 
@@ -98,12 +469,96 @@ impl<A: Afi> Mul for PrefixSet<A> {
     }
 }
 
+impl<A: Afi> BitAnd for &PrefixSet<A> {
+    type Output = PrefixSet<A>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(rhs)
+    }
+}
+
+impl<A: Afi> BitOr for &PrefixSet<A> {
+    type Output = PrefixSet<A>;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl<A: Afi> BitXor for &PrefixSet<A> {
+    type Output = PrefixSet<A>;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.symmetric_difference(rhs)
+    }
+}
+
+impl<A: Afi> Sub for &PrefixSet<A> {
+    type Output = PrefixSet<A>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.difference(rhs)
+    }
+}
+
+// The assignment operators move `self`'s tree out and combine it with
+// `rhs` in place, so `s op= t` never clones `s`.
+impl<A: Afi> BitAndAssign for PrefixSet<A> {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.root = match (self.root.take(), rhs.root) {
+            (Some(r), Some(s)) => r & s,
+            _ => None,
+        };
+        self.aggregate();
+    }
+}
+
+impl<A: Afi> BitOrAssign for PrefixSet<A> {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.root = match (self.root.take(), rhs.root) {
+            (Some(r), Some(s)) => r | s,
+            (r, s) => r.or(s),
+        };
+        self.aggregate();
+    }
+}
+
+impl<A: Afi> BitXorAssign for PrefixSet<A> {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        let intersection = self.intersection(&rhs);
+        *self |= rhs;
+        *self -= intersection;
+    }
+}
+
+impl<A: Afi> SubAssign for PrefixSet<A> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.root = match (self.root.take(), rhs.root) {
+            (Some(r), Some(s)) => r - s,
+            (r, _) => r,
+        };
+        self.aggregate();
+    }
+}
+
+/// Compares the sequences of ranges yielded by [`PrefixSet::ranges()`].
+///
+/// Glue nodes never yield ranges, so two sets with the same members compare
+/// equal even if one of them carries redundant glue in its tree.
+///
+/// Sets containing different numbers of prefixes are rejected without
+/// walking either tree, by comparing the counts maintained as each set is
+/// modified. If both sets have cached their ranges with
+/// [`PrefixSet::materialize_ranges()`], the caches are compared instead of
+/// walking the trees.
 impl<A: Afi> PartialEq for PrefixSet<A> {
     fn eq(&self, other: &Self) -> bool {
-        match (&self.root, &other.root) {
-            (Some(r), Some(s)) => r.children().zip(s.children()).all(|(m, n)| m == n),
-            (None, None) => true,
-            _ => false,
+        if self.count_prefixes() != other.count_prefixes() {
+            return false;
+        }
+        match (&self.ranges_cache, &other.ranges_cache) {
+            (Some(r), Some(s)) => r == s,
+            _ => self.ranges().eq(other.ranges()),
         }
     }
 }
@@ -112,9 +567,9 @@ impl<A: Afi> PartialOrd for PrefixSet<A> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         if self == other {
             Some(Ordering::Equal)
-        } else if self.prefixes().all(|p| other.contains(p)) {
+        } else if self.is_subset(other) {
             Some(Ordering::Less)
-        } else if other.prefixes().all(|p| self.contains(p)) {
+        } else if self.is_superset(other) {
             Some(Ordering::Greater)
         } else {
             None
@@ -124,38 +579,41 @@ impl<A: Afi> PartialOrd for PrefixSet<A> {
 
 impl<A: Afi> Eq for PrefixSet<A> {}
 
+/// Hashes the sequence of ranges yielded by [`PrefixSet::ranges()`].
+///
+/// This relies on `self` being in aggregated form, in which case the
+/// sequence of ranges is a unique representation of the set's contents, and
+/// hashing is consistent with [`PartialEq`]. All public mutating methods
+/// leave the set aggregated.
+impl<A: Afi> Hash for PrefixSet<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut count = 0usize;
+        self.ranges().for_each(|range| {
+            range.hash(state);
+            count += 1;
+        });
+        state.write_usize(count);
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use ip::{concrete::PrefixRange, Ipv4, Ipv6};
+    use ip::{Ipv4, Ipv6};
 
     use paste::paste;
 
-    use crate::tests::{TestError, TestResult};
+    use crate::tests::TestResult;
 
     use super::*;
 
     impl<A: Afi> FromIterator<&'static str> for PrefixSet<A> {
         fn from_iter<T: IntoIterator<Item = &'static str>>(iter: T) -> Self {
             iter.into_iter()
-                .map(|s| {
-                    let mut elems = s.split(',');
-                    let prefix = elems
-                        .next()
-                        .ok_or(TestError::from("no prefix"))
-                        .and_then(|s| s.parse().map_err(TestError::from))?;
-                    match (elems.next(), elems.next()) {
-                        (Some(l), Some(u)) => {
-                            let lower = l.parse::<usize>()?.try_into()?;
-                            let upper = u.parse::<usize>()?.try_into()?;
-                            PrefixRange::new(prefix, lower..=upper).map_err(TestError::from)
-                        }
-                        (None, None) => Ok(prefix.into()),
-                        _ => Err(TestError::from("failed to parse range")),
-                    }
-                })
-                .map(Result::unwrap)
-                .collect()
+                .collect::<Vec<_>>()
+                .join("\n")
+                .parse()
+                .unwrap()
         }
     }
 
@@ -247,6 +705,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn zero_set_is_zero() -> TestResult {
+        assert!(PrefixSet::<Ipv4>::zero().is_zero());
+        assert!(PrefixSet::<Ipv6>::zero().is_zero());
+        Ok(())
+    }
+
+    #[test]
+    fn non_empty_set_is_not_zero() -> TestResult {
+        let s: PrefixSet<Ipv4> = vec!["192.0.2.0/24"].into_iter().collect();
+        assert!(!s.is_zero());
+        assert!(!PrefixSet::<Ipv4>::one().is_zero());
+        Ok(())
+    }
+
     test_unary_op!(!zero == one, !one == zero);
 
     test_binary_op!(
@@ -463,7 +936,17 @@ mod tests {
         xor_overlapping_set_with_covered_child {
             vec!["1.0.0.0/8", "1.0.0.0/16"].into_iter().collect::<PrefixSet<_>>()
                 ^ vec!["1.0.0.0/8,16,16"].into_iter().collect(),
-            vec!["1.0.0.0/8"].into_iter().collect()
+            vec![
+                "1.0.0.0/8",
+                "1.1.0.0/16",
+                "1.2.0.0/15,16,16",
+                "1.4.0.0/14,16,16",
+                "1.8.0.0/13,16,16",
+                "1.16.0.0/12,16,16",
+                "1.32.0.0/11,16,16",
+                "1.64.0.0/10,16,16",
+                "1.128.0.0/9,16,16",
+            ].into_iter().collect()
         };
         sub_disjoint_nodes {
             vec!["2.0.0.0/8,8,16"].into_iter().collect::<PrefixSet<_>>()
@@ -527,17 +1010,7 @@ mod tests {
         sub_overlapping_set_with_covered_child {
             vec!["1.0.0.0/8", "1.0.0.0/16"].into_iter().collect::<PrefixSet<_>>()
                 - vec!["1.0.0.0/8,16,16"].into_iter().collect(),
-            vec![
-                "1.0.0.0/8",
-                "1.1.0.0/16",
-                "1.2.0.0/15,16,16",
-                "1.4.0.0/14,16,16",
-                "1.8.0.0/13,16,16",
-                "1.16.0.0/12,16,16",
-                "1.32.0.0/11,16,16",
-                "1.64.0.0/10,16,16",
-                "1.128.0.0/9,16,16",
-            ].into_iter().collect()
+            vec!["1.0.0.0/8"].into_iter().collect()
         };
         sub_complex_deaggregation {
             vec!["2.0.0.0/8,8,10", "3.0.0.0/8,8,9"].into_iter().collect::<PrefixSet<_>>()
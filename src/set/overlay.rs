@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use ip::{
+    concrete::{Prefix, PrefixRange},
+    Afi,
+};
+
+use super::PrefixSet;
+
+// group the ranges of `set` by their covering prefix, in iteration order.
+fn ranges_by_base<A: Afi>(set: &PrefixSet<A>) -> Vec<(Prefix<A>, Vec<PrefixRange<A>>)> {
+    let mut groups: Vec<(Prefix<A>, Vec<PrefixRange<A>>)> = Vec::new();
+    // each node yields all of its ranges consecutively, so ranges sharing a
+    // covering prefix are always adjacent.
+    for range in set.ranges() {
+        match groups.last_mut() {
+            Some((base, ranges)) if *base == range.prefix() => ranges.push(range),
+            _ => groups.push((range.prefix(), vec![range])),
+        }
+    }
+    groups
+}
+
+impl<A: Afi> PrefixSet<A> {
+    /// Construct the union of `self` and `other`, calling `resolve` to
+    /// decide the outcome wherever the two sets disagree about the length
+    /// bands covered by a common prefix.
+    ///
+    /// Ranges whose covering prefix appears in only one of the sets, and
+    /// ranges present identically in both, are included unchanged. For each
+    /// remaining pair of ranges `(ours, theirs)` with the same covering
+    /// prefix, `ours` from `self` and `theirs` from `other`, the range
+    /// returned by `resolve(ours, theirs)` is included in place of both.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let ours = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,24,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let theirs = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,24,24".parse::<PrefixRange<Ipv4>>()?)
+    ///     .insert("198.51.100.0/24,24,24".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// // prefer the length bands from `theirs`
+    /// let set = ours.overlay(&theirs, |_, theirs| theirs);
+    /// assert_eq!(set.len(), 2);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn overlay<F>(&self, other: &Self, mut resolve: F) -> Self
+    where
+        F: FnMut(PrefixRange<A>, PrefixRange<A>) -> PrefixRange<A>,
+    {
+        let mut theirs: HashMap<_, _> = ranges_by_base(other).into_iter().collect();
+        let mut result = Vec::new();
+        for (base, ours) in ranges_by_base(self) {
+            match theirs.remove(&base) {
+                Some(mut their_ranges) => {
+                    let (common, our_ranges): (Vec<_>, Vec<_>) = ours
+                        .into_iter()
+                        .partition(|range| their_ranges.contains(range));
+                    their_ranges.retain(|range| !common.contains(range));
+                    result.extend(common);
+                    if our_ranges.is_empty() || their_ranges.is_empty() {
+                        result.extend(our_ranges);
+                        result.extend(their_ranges);
+                    } else {
+                        for ours in &our_ranges {
+                            for theirs in &their_ranges {
+                                result.push(resolve(ours.clone(), theirs.clone()));
+                            }
+                        }
+                    }
+                }
+                None => result.extend(ours),
+            }
+        }
+        result.extend(theirs.into_values().flatten());
+        result.into_iter().collect()
+    }
+}
@@ -0,0 +1,178 @@
+use ip::{Afi, Ipv4, Ipv6};
+
+use rayon::prelude::*;
+
+use crate::node::Node;
+
+use super::PrefixSet;
+
+// the number of pieces to split each tree into, enough to keep every thread
+// in the pool busy when the pieces are of uneven size.
+fn pieces() -> usize {
+    rayon::current_num_threads() * 4
+}
+
+// merge partial result trees using `Node::add()`.
+//
+// union is associative and commutative, and `add()` never changes the set of
+// prefixes represented by either of its arguments, so the trees may be merged
+// in any grouping or order. aggregation of the final tree then produces the
+// same canonical tree as the equivalent serial operation.
+fn merge<A: Afi, I>(partials: I) -> PrefixSet<A>
+where
+    Node<A>: Send + Sync,
+    I: ParallelIterator<Item = Box<Node<A>>>,
+{
+    PrefixSet::new_with_root(partials.reduce_with(|root, partial| root.add(partial)))
+        .aggregate()
+        .to_owned()
+}
+
+// split the tree rooted at `root` into subtrees that together contain each
+// of its nodes exactly once.
+fn split<A: Afi>(root: &Node<A>) -> Vec<Box<Node<A>>> {
+    let (frontier, expanded) = root.split(pieces());
+    frontier
+        .into_iter()
+        .map(|node| node.to_owned().boxed())
+        .chain(
+            expanded
+                .into_iter()
+                .filter(|node| !node.is_glue())
+                .map(Node::detached),
+        )
+        .collect()
+}
+
+fn union<A: Afi>(s: &PrefixSet<A>, t: &PrefixSet<A>) -> PrefixSet<A>
+where
+    Node<A>: Send + Sync,
+{
+    let pieces: Vec<_> = [&s.root, &t.root]
+        .into_iter()
+        .flatten()
+        .flat_map(|root| split(root))
+        .collect();
+    merge(pieces.into_par_iter())
+}
+
+fn intersection<A: Afi>(s: &PrefixSet<A>, t: &PrefixSet<A>) -> PrefixSet<A>
+where
+    Node<A>: Send + Sync,
+{
+    match (&s.root, &t.root) {
+        (Some(r), Some(s)) => {
+            let nodes: Vec<_> = r.children().collect();
+            merge(
+                nodes
+                    .into_par_iter()
+                    .filter_map(|node| s.intersect_nodes(node)),
+            )
+        }
+        _ => PrefixSet::new(),
+    }
+}
+
+fn difference<A: Afi>(s: &PrefixSet<A>, t: &PrefixSet<A>) -> PrefixSet<A>
+where
+    Node<A>: Send + Sync,
+{
+    match (&s.root, &t.root) {
+        (Some(r), Some(t)) => merge(split(r).into_par_iter().map(
+            |piece| match t.restrict_to(&piece) {
+                Some(restricted) => piece.remove(&restricted),
+                None => piece,
+            },
+        )),
+        _ => s.clone(),
+    }
+}
+
+macro_rules! impl_par_ops {
+    ( $( $afi:ty ),* $(,)? ) => { $(
+        /// Parallel set operations, available with the `rayon` feature.
+        ///
+        /// Each of these methods produces the same result as its serial
+        /// counterpart, dividing the work among the threads of the current
+        /// [`rayon`] thread pool.
+        impl PrefixSet<$afi> {
+            /// Construct the union of `self` and `other` in parallel.
+            ///
+            /// The trees of both sets are split into subtrees, which are
+            /// merged in a parallel reduction.
+            ///
+            /// ``` rust
+            /// # use ip::{Ipv4, Prefix};
+            /// # use prefixset::{Error, PrefixSet};
+            /// # fn main() -> Result<(), Error> {
+            /// let s = PrefixSet::new()
+            ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+            ///     .to_owned();
+            /// let t = PrefixSet::new()
+            ///     .insert("192.0.2.128/25".parse::<Prefix<Ipv4>>()?)
+            ///     .to_owned();
+            /// assert_eq!(s.par_union(&t), s.union(&t));
+            /// #     Ok(())
+            /// # }
+            /// ```
+            pub fn par_union(&self, other: &Self) -> Self {
+                union(self, other)
+            }
+
+            /// Construct the intersection of `self` and `other` in parallel.
+            ///
+            /// The nodes of `self` are each intersected with `other` in
+            /// parallel. Each node of `self` contains a distinct subset of
+            /// its prefixes, and intersection distributes over union, so
+            /// merging the partial results yields the intersection of the
+            /// whole.
+            ///
+            /// ``` rust
+            /// # use ip::{Ipv4, PrefixRange};
+            /// # use prefixset::{Error, PrefixSet};
+            /// # fn main() -> Result<(), Error> {
+            /// let s = PrefixSet::new()
+            ///     .insert("192.0.2.0/24,24,26".parse::<PrefixRange<Ipv4>>()?)
+            ///     .to_owned();
+            /// let t = PrefixSet::new()
+            ///     .insert("192.0.2.0/24,26,28".parse::<PrefixRange<Ipv4>>()?)
+            ///     .to_owned();
+            /// assert_eq!(s.par_intersection(&t), s.intersection(&t));
+            /// #     Ok(())
+            /// # }
+            /// ```
+            pub fn par_intersection(&self, other: &Self) -> Self {
+                intersection(self, other)
+            }
+
+            /// Construct the set of prefixes in `self` but not in `other` in
+            /// parallel.
+            ///
+            /// The tree of `self` is split into subtrees, and from each is
+            /// removed the part of `other` that lies within the prefix at
+            /// its root. As for intersection, difference distributes over
+            /// the union of the subtrees, so merging the partial results
+            /// yields the difference of the whole.
+            ///
+            /// ``` rust
+            /// # use ip::{Ipv4, Prefix, PrefixRange};
+            /// # use prefixset::{Error, PrefixSet};
+            /// # fn main() -> Result<(), Error> {
+            /// let s = PrefixSet::new()
+            ///     .insert("192.0.2.0/24,25,25".parse::<PrefixRange<Ipv4>>()?)
+            ///     .to_owned();
+            /// let t = PrefixSet::new()
+            ///     .insert("192.0.2.0/25".parse::<Prefix<Ipv4>>()?)
+            ///     .to_owned();
+            /// assert_eq!(s.par_difference(&t), s.difference(&t));
+            /// #     Ok(())
+            /// # }
+            /// ```
+            pub fn par_difference(&self, other: &Self) -> Self {
+                difference(self, other)
+            }
+        }
+    )* };
+}
+
+impl_par_ops!(Ipv4, Ipv6);
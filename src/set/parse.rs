@@ -0,0 +1,209 @@
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use ip::{
+    concrete::{Prefix, PrefixLength, PrefixRange},
+    Afi,
+};
+
+use crate::error::{Error, RangeField};
+#[cfg(feature = "std")]
+use crate::node::Node;
+
+use super::PrefixSet;
+
+// parse a single entry, of the form `prefix` or `prefix,lower,upper`.
+fn parse_entry<A: Afi>(token: &str) -> Result<PrefixRange<A>, Error> {
+    token
+        .parse::<PrefixRange<A>>()
+        .or_else(|_| token.parse::<Prefix<A>>().map(PrefixRange::from))
+        .map_err(|source| {
+            range_error::<A>(token).unwrap_or_else(|| Error::EntryParse {
+                token: token.to_owned(),
+                source,
+            })
+        })
+}
+
+// if `input` is a `prefix,lower,upper` range, find the first of its fields
+// that is invalid.
+fn range_error<A: Afi>(input: &str) -> Option<Error> {
+    let fields: Vec<_> = input.split(',').collect();
+    let &[prefix, lower, upper] = fields.as_slice() else {
+        return None;
+    };
+    let error = |field, token: &str, source| Error::RangeParse {
+        input: input.to_owned(),
+        field,
+        token: token.to_owned(),
+        source,
+    };
+    if prefix.parse::<Prefix<A>>().is_err() {
+        return Some(error(RangeField::Prefix, prefix, None));
+    }
+    [(RangeField::Lower, lower), (RangeField::Upper, upper)]
+        .into_iter()
+        .find_map(|(field, token)| match token.parse::<u8>() {
+            Ok(length) => PrefixLength::<A>::try_from(usize::from(length))
+                .err()
+                .map(|_| error(field, token, None)),
+            Err(source) => Some(error(field, token, Some(source))),
+        })
+}
+
+impl<A: Afi> PrefixSet<A> {
+    /// Construct a new [`PrefixSet<A>`] from an iterator of strings, each
+    /// holding a single prefix or prefix range.
+    ///
+    /// Each string is parsed as a `prefix,lower,upper` range, falling back
+    /// to a bare prefix.
+    ///
+    /// # Errors
+    ///
+    /// The error for the first string that can't be parsed is returned,
+    /// as for [`from_str()`](Self::from_str).
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::<Ipv4>::try_from_strs(["192.0.2.0/24", "198.51.100.0/24,25,25"])?;
+    /// assert_eq!(set.len(), 3);
+    /// assert!(PrefixSet::<Ipv4>::try_from_strs(["192.0.2.0/24", "192.0.2.0/33"]).is_err());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn try_from_strs<'a, I>(iter: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let ranges = iter
+            .into_iter()
+            .map(parse_entry)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new().insert_from(ranges).to_owned())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: Afi> PrefixSet<A> {
+    /// Construct a new [`PrefixSet<A>`] from a reader yielding one prefix or
+    /// prefix range per line.
+    ///
+    /// Ranges are written as `prefix,lower,upper`. Blank lines are skipped,
+    /// as is any text following a `#`.
+    ///
+    /// Entries are parsed and inserted one line at a time, and the set is
+    /// aggregated once at the end, so the input is never buffered in full.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Io`] is returned if reading from `reader` fails, and an
+    /// [`Error::Line`] carrying the 1-based line number if a line can't be
+    /// parsed.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let input = "# route-set AS-EXAMPLE\n192.0.2.0/24\n\n198.51.100.0/24,25,25\n";
+    /// let set = PrefixSet::<Ipv4>::from_reader(input.as_bytes())?;
+    /// assert_eq!(set.len(), 3);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, Error> {
+        let mut set = Self::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let entry = line.split('#').next().unwrap_or_default().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let range = parse_entry(entry).map_err(|err| Error::Line {
+                number: i + 1,
+                source: Box::new(err),
+            })?;
+            set.insert_node(Node::from(range).boxed());
+        }
+        Ok(set.aggregate().to_owned())
+    }
+
+    /// Construct a new [`PrefixSet<A>`] from the file at `path`, containing
+    /// one prefix or prefix range per line.
+    ///
+    /// The file is read using [`from_reader()`](Self::from_reader), and the
+    /// same format applies.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::Io`] is returned if the file can't be opened or read,
+    /// and an [`Error::Line`] if a line can't be parsed.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::<Ipv4>::from_file("./test_data/RS-EXAMPLE-prefixes.txt")?;
+    /// assert_eq!(set.len(), 9);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<A: Afi> FromStr for PrefixSet<A> {
+    type Err = Error;
+
+    /// Parse a list of prefixes and prefix ranges, separated by whitespace
+    /// or commas.
+    ///
+    /// Ranges are written as `prefix,lower,upper`. Any numeric field
+    /// following a prefix is taken to be one of the bounds of a range.
+    ///
+    /// # Errors
+    ///
+    /// An [`Error::RangeParse`] identifying the offending field is returned
+    /// if the prefix or either length bound of a range is invalid, and an
+    /// [`Error::EntryParse`] for any other invalid entry.
+    ///
+    /// ``` rust
+    /// # use ip::Ipv4;
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24, 198.51.100.0/24,25,25
+    ///     203.0.113.0/24"
+    ///     .parse()?;
+    /// assert_eq!(set.len(), 4);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries: Vec<String> = Vec::new();
+        for field in s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|field| !field.is_empty())
+        {
+            match entries.last_mut() {
+                Some(entry) if !field.contains('/') => {
+                    entry.push(',');
+                    entry.push_str(field);
+                }
+                _ => entries.push(field.to_owned()),
+            }
+        }
+        let ranges = entries
+            .iter()
+            .map(|entry| parse_entry(entry))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new().insert_from(ranges).to_owned())
+    }
+}
@@ -0,0 +1,131 @@
+use core::fmt;
+
+use ip::{concrete::PrefixLength, Afi};
+
+use super::PrefixSet;
+
+/// Limits on the contents of a [`PrefixSet<A>`], checked by
+/// [`PrefixSet::validate()`].
+///
+/// Each limit is only enforced if it is set. The default policy sets no
+/// limits, and so accepts every set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SetPolicy<A: Afi> {
+    /// The maximum number of prefixes that the set may contain.
+    pub max_prefixes: Option<usize>,
+    /// The maximum length of any prefix in the set.
+    pub max_length: Option<PrefixLength<A>>,
+    /// The minimum length of any prefix in the set.
+    pub min_length: Option<PrefixLength<A>>,
+}
+
+impl<A: Afi> Default for SetPolicy<A> {
+    fn default() -> Self {
+        Self {
+            max_prefixes: None,
+            max_length: None,
+            min_length: None,
+        }
+    }
+}
+
+/// The first limit of a [`SetPolicy<A>`] found to be exceeded by
+/// [`PrefixSet::validate()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PolicyViolation<A: Afi> {
+    /// The set contains more prefixes than allowed.
+    TooManyPrefixes {
+        /// The maximum number of prefixes allowed by the policy.
+        limit: usize,
+        /// The number of prefixes in the set, saturating at `usize::MAX`.
+        found: usize,
+    },
+    /// The set contains a prefix longer than allowed.
+    TooLong {
+        /// The maximum prefix length allowed by the policy.
+        limit: PrefixLength<A>,
+        /// The length of the longest prefix in the set.
+        found: PrefixLength<A>,
+    },
+    /// The set contains a prefix shorter than allowed.
+    TooShort {
+        /// The minimum prefix length allowed by the policy.
+        limit: PrefixLength<A>,
+        /// The length of the shortest prefix in the set.
+        found: PrefixLength<A>,
+    },
+}
+
+impl<A: Afi> fmt::Display for PolicyViolation<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyPrefixes { limit, found } => {
+                write!(f, "{found} prefixes exceeds the limit of {limit}")
+            }
+            Self::TooLong { limit, found } => {
+                write!(f, "prefix length /{found} is longer than /{limit}")
+            }
+            Self::TooShort { limit, found } => {
+                write!(f, "prefix length /{found} is shorter than /{limit}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: Afi> std::error::Error for PolicyViolation<A> {}
+
+impl<A: Afi> PrefixSet<A> {
+    /// Check `self` against the limits set in `policy`.
+    ///
+    /// The prefix count is computed per range, and the minimum and maximum
+    /// lengths are read from the tree, so no prefixes are enumerated.
+    ///
+    /// # Errors
+    ///
+    /// The first violated limit is returned, together with the offending
+    /// value, checking the prefix count, then the maximum length, and then
+    /// the minimum length.
+    ///
+    /// ``` rust
+    /// # use ip::{concrete::PrefixLength, Ipv4};
+    /// # use prefixset::{
+    /// #     set::{PolicyViolation, SetPolicy},
+    /// #     Error, PrefixSet,
+    /// # };
+    /// # fn main() -> Result<(), Error> {
+    /// let policy = SetPolicy {
+    ///     max_length: Some(PrefixLength::<Ipv4>::from_primitive(24)?),
+    ///     ..Default::default()
+    /// };
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24,24,25".parse()?;
+    /// assert_eq!(
+    ///     set.validate(&policy),
+    ///     Err(PolicyViolation::TooLong {
+    ///         limit: PrefixLength::from_primitive(24)?,
+    ///         found: PrefixLength::from_primitive(25)?,
+    ///     })
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn validate(&self, policy: &SetPolicy<A>) -> Result<(), PolicyViolation<A>> {
+        if let Some(limit) = policy.max_prefixes {
+            let found = self.len();
+            if found > limit {
+                return Err(PolicyViolation::TooManyPrefixes { limit, found });
+            }
+        }
+        if let (Some(limit), Some(found)) = (policy.max_length, self.max_length()) {
+            if found > limit {
+                return Err(PolicyViolation::TooLong { limit, found });
+            }
+        }
+        if let (Some(limit), Some(found)) = (policy.min_length, self.min_length()) {
+            if found < limit {
+                return Err(PolicyViolation::TooShort { limit, found });
+            }
+        }
+        Ok(())
+    }
+}
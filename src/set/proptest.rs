@@ -0,0 +1,38 @@
+use alloc::vec::Vec;
+
+use ip::{Afi, Prefix};
+use proptest::{
+    arbitrary::{any_with, Arbitrary, ParamsFor},
+    strategy::{BoxedStrategy, Strategy},
+};
+
+use super::PrefixSet;
+
+/// Generate arbitrary [`PrefixSet<A>`]s for property testing, by collecting
+/// an arbitrary [`Vec<Prefix<A>>`].
+///
+/// The parameters are those of the underlying [`Vec<Prefix<A>>`] strategy,
+/// and so control the number of prefixes inserted, rather than the size of
+/// the resulting set.
+///
+/// ``` rust
+/// # use ip::Ipv4;
+/// # use prefixset::PrefixSet;
+/// # use proptest::prelude::*;
+/// proptest!(|(s in any::<PrefixSet<Ipv4>>())| {
+///     prop_assert_eq!(&s | &s, s);
+/// });
+/// ```
+impl<A: Afi> Arbitrary for PrefixSet<A>
+where
+    Prefix<A>: Arbitrary,
+{
+    type Parameters = ParamsFor<Vec<Prefix<A>>>;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        any_with::<Vec<Prefix<A>>>(args)
+            .prop_map(|prefixes| prefixes.into_iter().collect())
+            .boxed()
+    }
+}
@@ -0,0 +1,78 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use ip::{concrete::Prefix, Afi};
+use rand::{Rng, RngExt};
+
+use super::PrefixSet;
+
+impl<A: Afi> PrefixSet<A> {
+    /// Select a prefix from `self` uniformly at random, using `rng`.
+    ///
+    /// Every prefix contained in `self` is equally likely to be selected,
+    /// regardless of how the set's ranges are structured.
+    ///
+    /// Returns `None` if `self` is empty.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # use rand::SeedableRng;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let prefix = set.sample(&mut rng).unwrap();
+    /// assert!(set.contains(prefix));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Prefix<A>> {
+        match self.count_prefixes() {
+            0 => None,
+            total => self.nth_prefix_wide(rng.random_range(0..total)),
+        }
+    }
+
+    /// Select `n` distinct prefixes from `self` uniformly at random, using
+    /// `rng`.
+    ///
+    /// The selected prefixes are returned in the order in which they are
+    /// yielded by [`prefixes()`](Self::prefixes). If `self` contains `n` or
+    /// fewer prefixes, then all of them are returned.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # use rand::SeedableRng;
+    /// # fn main() -> Result<(), Error> {
+    /// # let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+    /// let set = PrefixSet::new()
+    ///     .insert("192.0.2.0/24,25,26".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let prefixes = set.sample_n(&mut rng, 3);
+    /// assert_eq!(prefixes.len(), 3);
+    /// assert!(prefixes.iter().all(|prefix| set.contains(*prefix)));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn sample_n<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<Prefix<A>> {
+        let total = self.count_prefixes();
+        let n = n as u128;
+        if n >= total {
+            return self.prefixes().collect();
+        }
+        // Floyd's algorithm: draws exactly `n` distinct indices.
+        let mut indices = BTreeSet::new();
+        for j in (total - n)..total {
+            let t = rng.random_range(0..=j);
+            if !indices.insert(t) {
+                indices.insert(j);
+            }
+        }
+        indices
+            .into_iter()
+            .filter_map(|i| self.nth_prefix_wide(i))
+            .collect()
+    }
+}
@@ -0,0 +1,163 @@
+use alloc::{borrow::ToOwned, vec::Vec};
+use core::borrow::Borrow;
+use core::fmt;
+use core::marker::PhantomData;
+
+use ip::{
+    concrete::{Address, Prefix, PrefixLength, PrefixRange},
+    traits::Prefix as _,
+    Afi,
+};
+use serde::{
+    de::{self, Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, SerializeTuple, Serializer},
+};
+
+use super::PrefixSet;
+
+fn octets_len<A: Afi>() -> usize {
+    Address::<A>::ZEROS.octets().borrow().len()
+}
+
+fn length_to_u8<A: Afi>(length: PrefixLength<A>) -> u8 {
+    // lengths are at most 128, so this never truncates.
+    Into::<usize>::into(length.into_primitive()) as u8
+}
+
+fn length_from_u8<A: Afi, E: de::Error>(byte: u8) -> Result<PrefixLength<A>, E> {
+    PrefixLength::try_from(usize::from(byte)).map_err(E::custom)
+}
+
+// a range is serialized as a `"prefix,lower,upper"` string in human readable
+// formats, and otherwise as a tuple of its prefix length, lower and upper
+// bounds, followed by the network address octets.
+struct SerRange<A: Afi>(PrefixRange<A>);
+
+impl<A: Afi> Serialize for SerRange<A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let range = &self.0;
+        if serializer.is_human_readable() {
+            serializer.collect_str(&format_args!(
+                "{},{},{}",
+                range.prefix(),
+                range.lower(),
+                range.upper()
+            ))
+        } else {
+            let octets = range.prefix().network().octets();
+            let octets = octets.borrow();
+            let mut tuple = serializer.serialize_tuple(3 + octets.len())?;
+            tuple.serialize_element(&length_to_u8(range.prefix().length()))?;
+            tuple.serialize_element(&length_to_u8(range.lower()))?;
+            tuple.serialize_element(&length_to_u8(range.upper()))?;
+            for octet in octets {
+                tuple.serialize_element(octet)?;
+            }
+            tuple.end()
+        }
+    }
+}
+
+struct DeRange<A: Afi>(PrefixRange<A>);
+
+impl<'de, A: Afi> Deserialize<'de> for DeRange<A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(RangeStrVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_tuple(3 + octets_len::<A>(), RangeTupleVisitor(PhantomData))
+        }
+    }
+}
+
+struct RangeStrVisitor<A: Afi>(PhantomData<A>);
+
+impl<'de, A: Afi> Visitor<'de> for RangeStrVisitor<A> {
+    type Value = DeRange<A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a prefix range string of the form \"prefix,lower,upper\"")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        // accept bare prefixes too, as a range covering only that prefix.
+        s.parse()
+            .or_else(|_| s.parse::<Prefix<A>>().map(PrefixRange::from))
+            .map(DeRange)
+            .map_err(E::custom)
+    }
+}
+
+struct RangeTupleVisitor<A: Afi>(PhantomData<A>);
+
+impl<'de, A: Afi> Visitor<'de> for RangeTupleVisitor<A> {
+    type Value = DeRange<A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a tuple of a prefix length, range bounds and {} address octets",
+            octets_len::<A>()
+        )
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+        let mut next = |i| {
+            seq.next_element::<u8>()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))
+        };
+        let length = length_from_u8(next(0)?)?;
+        let lower = length_from_u8(next(1)?)?;
+        let upper = length_from_u8(next(2)?)?;
+        let octets = (0..octets_len::<A>())
+            .map(|i| next(3 + i))
+            .collect::<Result<Vec<_>, _>>()?;
+        // ok to unwrap, because exactly `A::Octets::LENGTH` octets were read.
+        let network = Address::<A>::from_slice(&octets).unwrap();
+        PrefixRange::new(Prefix::new(network, length), lower..=upper)
+            .map(DeRange)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<A: Afi> Serialize for PrefixSet<A> {
+    /// Serialize `self` as a sequence of the
+    /// [`PrefixRange<A>`](ip::concrete::PrefixRange)s that it contains.
+    ///
+    /// In human readable formats, each range is represented as a string of
+    /// the form `"2001:db8::/36,37,37"`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.ranges().map(SerRange))
+    }
+}
+
+impl<'de, A: Afi> Deserialize<'de> for PrefixSet<A> {
+    /// Deserialize a sequence of
+    /// [`PrefixRange<A>`](ip::concrete::PrefixRange)s, as produced by the
+    /// [`Serialize`] implementation, into a new [`PrefixSet<A>`].
+    ///
+    /// The ranges need not be aggregated, or disjoint. In human readable
+    /// formats, bare prefixes are also accepted.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(SetVisitor(PhantomData))
+    }
+}
+
+struct SetVisitor<A: Afi>(PhantomData<A>);
+
+impl<'de, A: Afi> Visitor<'de> for SetVisitor<A> {
+    type Value = PrefixSet<A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of prefix ranges")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+        let mut ranges = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(DeRange(range)) = seq.next_element()? {
+            ranges.push(range);
+        }
+        // aggregation runs once, after all of the ranges are inserted.
+        Ok(PrefixSet::new().insert_from(ranges).to_owned())
+    }
+}
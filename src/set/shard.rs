@@ -0,0 +1,90 @@
+use alloc::vec::Vec;
+use core::mem;
+
+use ip::{concrete::PrefixRange, traits::PrefixLength as _, Afi};
+
+use crate::node::Node;
+
+use super::PrefixSet;
+
+// split `range` into pieces that together contain the same prefixes, or
+// return `None` if it contains only a single prefix.
+fn split_range<A: Afi>(range: PrefixRange<A>) -> Option<Vec<PrefixRange<A>>> {
+    let base = range.prefix();
+    let next = base.length().increment().ok()?;
+    if range.upper() < next {
+        return None;
+    }
+    let mut parts = Vec::with_capacity(3);
+    if range.lower() < next {
+        // ok to unwrap, because the base prefix length is always valid.
+        parts.push(PrefixRange::new(base, base.length()..=base.length()).unwrap());
+    }
+    // ok to unwrap, because `next <= range.upper() <= MAX_LENGTH`.
+    let halves = PrefixRange::new(base, next..=next).unwrap();
+    let (lower, upper) = (range.lower().max(next), range.upper());
+    parts.extend(halves.into_iter().map(|half| {
+        // ok to unwrap, because `half.length() <= lower <= upper`.
+        PrefixRange::new(half, lower..=upper).unwrap()
+    }));
+    Some(parts)
+}
+
+impl<A: Afi> PrefixSet<A> {
+    /// Split `self` into `n` pairwise disjoint [`PrefixSet<A>`]s whose union
+    /// is equal to `self`.
+    ///
+    /// The shards are balanced by the number of prefixes they contain.
+    /// Split points are chosen using prefix counts computed from the tree,
+    /// and ranges are only broken up where they would otherwise overfill a
+    /// shard, so the prefixes themselves are never enumerated.
+    ///
+    /// Shards may be empty if `self` contains fewer than `n` prefixes.
+    ///
+    /// ``` rust
+    /// # use ip::{Ipv4, PrefixRange};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set = PrefixSet::new()
+    ///     .insert("10.0.0.0/8,16,16".parse::<PrefixRange<Ipv4>>()?)
+    ///     .to_owned();
+    /// let shards = set.shards(4);
+    /// assert_eq!(shards.len(), 4);
+    /// assert!(shards.iter().all(|shard| shard.len() == 64));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn shards(&self, n: usize) -> Vec<Self> {
+        let mut shards = Vec::with_capacity(n);
+        if n == 0 {
+            return shards;
+        }
+        let target = self.count_prefixes().div_ceil(n as u128).max(1);
+        // a stack of ranges still to be assigned, in iteration order.
+        let mut pending: Vec<_> = self.ranges().collect();
+        pending.reverse();
+        let mut current = Self::new();
+        let mut filled = 0u128;
+        while let Some(range) = pending.pop() {
+            let node = Node::from(range.clone());
+            let count = node.prefix_count();
+            if filled.saturating_add(count) > target && shards.len() + 1 < n {
+                if let Some(parts) = split_range(range) {
+                    pending.extend(parts.into_iter().rev());
+                    continue;
+                }
+            }
+            current.insert_node(node.boxed());
+            filled = filled.saturating_add(count);
+            if filled >= target && shards.len() + 1 < n {
+                current.aggregate();
+                shards.push(mem::take(&mut current));
+                filled = 0;
+            }
+        }
+        current.aggregate();
+        shards.push(current);
+        shards.resize_with(n, Self::new);
+        shards
+    }
+}
@@ -0,0 +1,103 @@
+use alloc::collections::BTreeMap;
+
+use ip::{concrete::PrefixLength, Afi};
+
+use super::PrefixSet;
+
+/// Summary statistics of a [`PrefixSet<A>`], returned by
+/// [`PrefixSet::stats()`].
+///
+/// Prefix counts saturate at [`u128::MAX`], which can only be reached by
+/// IPv6 sets covering (almost) every possible prefix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SetStats<A: Afi> {
+    /// The number of prefixes in the set.
+    pub prefix_count: u128,
+    /// The number of ranges in the aggregated representation of the set.
+    pub range_count: usize,
+    /// The mean length of the prefixes in the set, or `0.0` if it is empty.
+    pub mean_length: f64,
+    /// The most common length of the prefixes in the set, preferring the
+    /// shortest in the case of a tie, or `None` if it is empty.
+    pub modal_length: Option<PrefixLength<A>>,
+}
+
+impl<A: Afi> SetStats<A> {
+    /// Get the ratio of ranges to prefixes, or `0.0` for an empty set.
+    ///
+    /// The smaller the ratio, the more effectively the prefixes in the set
+    /// are aggregated into ranges.
+    pub fn aggregation_ratio(&self) -> f64 {
+        if self.prefix_count == 0 {
+            0.0
+        } else {
+            self.range_count as f64 / self.prefix_count as f64
+        }
+    }
+}
+
+impl<A: Afi> PrefixSet<A> {
+    /// Compute [`SetStats<A>`] summarizing the prefixes in `self`.
+    ///
+    /// The statistics are accumulated in a single walk of the tree, counting
+    /// the prefixes of each length per node rather than enumerating them.
+    ///
+    /// ``` rust
+    /// # use ip::{concrete::PrefixLength, Ipv4};
+    /// # use prefixset::{Error, PrefixSet};
+    /// # fn main() -> Result<(), Error> {
+    /// let set: PrefixSet<Ipv4> = "192.0.2.0/24,25,25 198.51.100.0/24".parse()?;
+    /// let stats = set.stats();
+    /// assert_eq!(stats.prefix_count, 3);
+    /// assert_eq!(stats.range_count, 2);
+    /// assert_eq!(stats.modal_length, Some(PrefixLength::from_primitive(25)?));
+    /// assert!((stats.mean_length - 74.0 / 3.0).abs() < 1e-9);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> SetStats<A> {
+        let mut histogram: BTreeMap<PrefixLength<A>, u128> = BTreeMap::new();
+        let mut range_count = 0;
+        self.root
+            .iter()
+            .flat_map(|root| root.children())
+            .for_each(|node| {
+                range_count += node.ranges().count();
+                let base: usize = node.prefix().length().into_primitive().into();
+                node.lengths().for_each(|length| {
+                    let offset: usize = Into::<usize>::into(length.into_primitive()) - base;
+                    let count = 1u128.checked_shl(offset as u32).unwrap_or(u128::MAX);
+                    histogram
+                        .entry(length)
+                        .and_modify(|n| *n = n.saturating_add(count))
+                        .or_insert(count);
+                })
+            });
+        let prefix_count = histogram.values().copied().fold(0, u128::saturating_add);
+        let mean_length = if prefix_count == 0 {
+            0.0
+        } else {
+            histogram
+                .iter()
+                .map(|(length, &n)| Into::<usize>::into(length.into_primitive()) as f64 * n as f64)
+                .sum::<f64>()
+                / prefix_count as f64
+        };
+        let modal_length = histogram
+            .iter()
+            .fold(
+                None,
+                |modal: Option<(PrefixLength<A>, u128)>, (&length, &n)| match modal {
+                    Some((_, m)) if m >= n => modal,
+                    _ => Some((length, n)),
+                },
+            )
+            .map(|(length, _)| length);
+        SetStats {
+            prefix_count,
+            range_count,
+            mean_length,
+            modal_length,
+        }
+    }
+}
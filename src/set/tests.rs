@@ -3,7 +3,7 @@ use ip::{Ipv4, Prefix, PrefixRange};
 use crate::tests::TestResult;
 // use crate::{IpPrefixRange, Ipv4Prefix};
 
-use super::PrefixSet;
+use super::{Node, PrefixSet};
 
 fn assert_send<T: Send>(_: T) -> TestResult {
     Ok(())
@@ -254,3 +254,5647 @@ mod new_ipv4_prefix_set {
         }
     }
 }
+
+mod byte_encoding {
+    use ip::{Afi, Ipv6};
+
+    use crate::error::Error;
+
+    use super::*;
+
+    fn setup_ipv4() -> PrefixSet<Ipv4> {
+        [
+            "10.0.0.0/8,16,24",
+            "192.0.2.0/25,25,25",
+            "192.0.2.128/25,27,28",
+            "203.0.113.7/32,32,32",
+        ]
+        .into_iter()
+        .map(|s| s.parse::<PrefixRange<Ipv4>>().unwrap())
+        .collect()
+    }
+
+    fn setup_ipv6() -> PrefixSet<Ipv6> {
+        [
+            "2001:db8::/32,48,64",
+            "2001:db8:f00::/48,48,48",
+            "2001:db8:baa::/48,56,128",
+        ]
+        .into_iter()
+        .map(|s| s.parse::<PrefixRange<Ipv6>>().unwrap())
+        .collect()
+    }
+
+    fn decode_error<A: Afi>(result: Result<PrefixSet<A>, Error>) -> &'static str {
+        match result {
+            Err(Error::Decode { reason }) => reason,
+            other => panic!("expected a decoding error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn empty_set_round_trips() -> TestResult {
+        let s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.to_bytes(), [32]);
+        assert_eq!(s.to_bytes_delta(), [32]);
+        assert!(PrefixSet::<Ipv4>::from_bytes(&s.to_bytes())?.is_empty());
+        assert!(PrefixSet::<Ipv4>::from_bytes_delta(&s.to_bytes_delta())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn ipv4_set_round_trips() -> TestResult {
+        let s = setup_ipv4();
+        let t = PrefixSet::from_bytes(&s.to_bytes())?;
+        let u = PrefixSet::from_bytes_delta(&s.to_bytes_delta())?;
+        assert_eq!(s, t);
+        assert_eq!(s, u);
+        assert_eq!(
+            s.ranges().collect::<Vec<_>>(),
+            u.ranges().collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_set_round_trips() -> TestResult {
+        let s = setup_ipv6();
+        let t = PrefixSet::from_bytes(&s.to_bytes())?;
+        let u = PrefixSet::from_bytes_delta(&s.to_bytes_delta())?;
+        assert_eq!(s, t);
+        assert_eq!(s, u);
+        assert_eq!(
+            s.ranges().collect::<Vec<_>>(),
+            u.ranges().collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn delta_encoding_is_smaller() -> TestResult {
+        let s = setup_ipv6();
+        assert!(s.to_bytes_delta().len() < s.to_bytes().len());
+        Ok(())
+    }
+
+    #[test]
+    fn address_family_mismatch_is_rejected() -> TestResult {
+        let reason = "missing or mismatched address family";
+        let s = setup_ipv4();
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv6>::from_bytes(&s.to_bytes())),
+            reason
+        );
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv6>::from_bytes_delta(&s.to_bytes_delta())),
+            reason
+        );
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes_delta(&[])),
+            reason
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_input_is_rejected() -> TestResult {
+        let s = setup_ipv6();
+        // a lone family tag is a valid encoding of the empty set.
+        let bytes = s.to_bytes();
+        (2..bytes.len())
+            .for_each(|len| assert!(PrefixSet::<Ipv6>::from_bytes(&bytes[..len]).is_err()));
+        let bytes = s.to_bytes_delta();
+        (2..bytes.len())
+            .for_each(|len| assert!(PrefixSet::<Ipv6>::from_bytes_delta(&bytes[..len]).is_err()));
+        // header announcing a /8 with no prefix bits following
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes_delta(&[32, 0x18])),
+            "unexpected end of input"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() -> TestResult {
+        let reason = "trailing bytes after encoded tree";
+        let s = setup_ipv4();
+        let mut bytes = s.to_bytes();
+        bytes.push(0);
+        assert_eq!(decode_error(PrefixSet::<Ipv4>::from_bytes(&bytes)), reason);
+        let mut bytes = s.to_bytes_delta();
+        bytes.push(0);
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes_delta(&bytes)),
+            reason
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_bounds_prefix_length_is_rejected() -> TestResult {
+        let reason = "prefix length out of bounds";
+        // singleton node of length 33
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes(&[32, 0x10, 33, 10, 0, 0, 0])),
+            reason
+        );
+        // singleton node with an extended length delta of 33
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes_delta(&[32, 0x1f, 33])),
+            reason
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_gluemap_encoding_is_rejected() -> TestResult {
+        let reason = "invalid gluemap encoding";
+        // node of length 8 with gluemap kind `0b11`
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes(&[32, 0x30, 8, 10, 0, 0, 0])),
+            reason
+        );
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes_delta(&[32, 0x38, 10])),
+            reason
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inverted_prefix_length_range_is_rejected() -> TestResult {
+        let reason = "invalid prefix length range";
+        // node of length 8 with one range, from 24 to 16
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes(&[
+                32, 0x20, 8, 10, 0, 0, 0, 1, 24, 16
+            ])),
+            reason
+        );
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes_delta(&[
+                32, 0x28, 10, 1, 24, 16
+            ])),
+            reason
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_plain_node_header_is_rejected() -> TestResult {
+        // plain encoded headers must not carry a length delta
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes(&[32, 0x11, 8, 10, 0, 0, 0])),
+            "invalid node header"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn child_that_is_not_a_subprefix_is_rejected() -> TestResult {
+        let reason = "child prefix is not a subprefix of its parent";
+        // glue node 10.0.0.0/8 with left child 11.0.0.0/9
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes(&[
+                32, 0x80, 8, 10, 0, 0, 0, 0x10, 9, 11, 0, 0, 0
+            ])),
+            reason
+        );
+        // glue node 10.0.0.0/8 with a left child of zero length delta
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes_delta(&[32, 0x88, 10, 0x10])),
+            reason
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn child_on_the_wrong_branch_is_rejected() -> TestResult {
+        // glue node 10.0.0.0/8 with left child 10.128.0.0/9
+        assert_eq!(
+            decode_error(PrefixSet::<Ipv4>::from_bytes(&[
+                32, 0x80, 8, 10, 0, 0, 0, 0x10, 9, 10, 128, 0, 0
+            ])),
+            "child prefix is on the wrong branch"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+mod binary {
+    use std::io;
+
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn setup() -> Result<PrefixSet<Ipv4>, crate::Error> {
+        "10.0.0.0/8,8,16 192.0.2.0/24 198.51.100.0/24,25,25".parse()
+    }
+
+    fn write(s: &PrefixSet<Ipv4>) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        s.write_binary(&mut buf)?;
+        Ok(buf)
+    }
+
+    #[test]
+    fn round_trips() -> TestResult {
+        let s = setup()?;
+        assert_eq!(PrefixSet::read_binary(write(&s)?.as_slice())?, s);
+        Ok(())
+    }
+
+    #[test]
+    fn round_trips_empty_set() -> TestResult {
+        let s = PrefixSet::new();
+        let buf = write(&s)?;
+        assert_eq!(buf, b"PFXS\x02\x20\0\0\0\0\0\0\0\0");
+        assert_eq!(PrefixSet::read_binary(buf.as_slice())?, s);
+        Ok(())
+    }
+
+    #[test]
+    fn writes_documented_layout() -> TestResult {
+        let buf = write(&setup()?)?;
+        let mut expected = b"PFXS\x02\x20".to_vec();
+        expected.extend_from_slice(&3u64.to_be_bytes());
+        expected.extend_from_slice(&[198, 51, 100, 0, 24, 25, 25]);
+        expected.extend_from_slice(&[192, 0, 2, 0, 24, 24, 24]);
+        expected.extend_from_slice(&[10, 0, 0, 0, 8, 8, 16]);
+        assert_eq!(buf, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn round_trips_ipv6() -> TestResult {
+        let s: PrefixSet<Ipv6> = "2001:db8::/32,32,48 2001:db8:f::/64".parse()?;
+        let mut buf = Vec::new();
+        s.write_binary(&mut buf)?;
+        assert_eq!(&buf[5..14], b"\x80\0\0\0\0\0\0\0\x02");
+        assert_eq!(buf.len(), 14 + 2 * 19);
+        assert_eq!(PrefixSet::read_binary(buf.as_slice())?, s);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_bad_magic() -> TestResult {
+        let mut buf = write(&setup()?)?;
+        buf[0] = b'X';
+        let err = PrefixSet::<Ipv4>::read_binary(buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_unknown_version() -> TestResult {
+        let mut buf = write(&setup()?)?;
+        buf[4] = 1;
+        let err = PrefixSet::<Ipv4>::read_binary(buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_family_mismatch() -> TestResult {
+        let buf = write(&setup()?)?;
+        let err = PrefixSet::<Ipv6>::read_binary(buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_truncated_input() -> TestResult {
+        let buf = write(&setup()?)?;
+        let err = PrefixSet::<Ipv4>::read_binary(&buf[..buf.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = PrefixSet::<Ipv4>::read_binary(&buf[..buf.len() - 7]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = PrefixSet::<Ipv4>::read_binary(&buf[..10]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_host_bits_set() -> TestResult {
+        let mut buf = write(&setup()?)?;
+        // the last octet of the first record's network address.
+        buf[17] = 1;
+        let err = PrefixSet::<Ipv4>::read_binary(buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_lengths() -> TestResult {
+        let mut buf = write(&setup()?)?;
+        // the first record's lower bound, below its prefix length.
+        buf[19] = 7;
+        let err = PrefixSet::<Ipv4>::read_binary(buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let mut buf = write(&setup()?)?;
+        // the first record's upper bound, beyond the maximum length.
+        buf[20] = 33;
+        let err = PrefixSet::<Ipv4>::read_binary(buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+}
+
+mod matching_a_filter_entry {
+    use ip::traits::Prefix as _;
+
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        [
+            "10.0.0.0/8,8,8",
+            "10.1.0.0/16,16,16",
+            "10.2.0.0/16,20,22",
+            "10.3.0.0/24,24,24",
+            "10.255.255.0/25,25,25",
+            "172.16.0.0/16,16,16",
+        ]
+        .into_iter()
+        .map(|s| s.parse::<PrefixRange<Ipv4>>().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn yields_covered_prefixes_within_length_band() -> TestResult {
+        let s = setup();
+        // permit 10.0.0.0/8 ge 16 le 20
+        let mut matched: Vec<_> = s
+            .matching("10.0.0.0/8".parse()?, 16.try_into()?, 20.try_into()?)
+            .collect();
+        matched.sort_by_key(|p| (p.network(), p.length()));
+        let mut expected = vec!["10.1.0.0/16".parse::<Prefix<Ipv4>>()?];
+        expected.extend((0..16).map(|i| {
+            format!("10.2.{}.0/20", i << 4)
+                .parse::<Prefix<Ipv4>>()
+                .unwrap()
+        }));
+        assert_eq!(matched, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn excludes_prefixes_outside_base() -> TestResult {
+        let s = setup();
+        // permit 172.16.0.0/12 ge 16 le 24
+        let matched: Vec<_> = s
+            .matching("172.16.0.0/12".parse()?, 16.try_into()?, 24.try_into()?)
+            .collect();
+        assert_eq!(matched, vec!["172.16.0.0/16".parse::<Prefix<Ipv4>>()?]);
+        Ok(())
+    }
+
+    #[test]
+    fn exact_length_entry_matches_single_prefix() -> TestResult {
+        let s = setup();
+        // permit 10.0.0.0/8 ge 24 le 24
+        let matched: Vec<_> = s
+            .matching("10.0.0.0/8".parse()?, 24.try_into()?, 24.try_into()?)
+            .collect();
+        assert_eq!(matched, vec!["10.3.0.0/24".parse::<Prefix<Ipv4>>()?]);
+        Ok(())
+    }
+
+    #[test]
+    fn wide_length_band_is_lazy() -> TestResult {
+        let s: PrefixSet<ip::Ipv6> = "2001:db8::/32,32,128".parse()?;
+        // permit 2001:db8::/32 ge 48 le 128
+        let mut matched = s.matching("2001:db8::/32".parse()?, 48.try_into()?, 128.try_into()?);
+        assert_eq!(matched.next(), Some("2001:db8::/48".parse()?));
+        assert_eq!(matched.next(), Some("2001:db8:1::/48".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_length_band_matches_nothing() -> TestResult {
+        let s = setup();
+        assert_eq!(
+            s.matching("10.0.0.0/8".parse()?, 24.try_into()?, 16.try_into()?)
+                .count(),
+            0
+        );
+        assert_eq!(
+            s.matching("10.0.0.0/16".parse()?, 8.try_into()?, 16.try_into()?)
+                .count(),
+            0
+        );
+        Ok(())
+    }
+}
+
+mod materialized_ranges {
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        let mut s: PrefixSet<Ipv4> = ["10.0.0.0/8,16,16", "192.0.2.0/24,24,24"]
+            .into_iter()
+            .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+            .collect();
+        s.materialize_ranges();
+        s
+    }
+
+    #[test]
+    fn cache_is_populated() -> TestResult {
+        let s = setup();
+        assert_eq!(
+            s.ranges_cache.as_deref(),
+            Some(&s.ranges().collect::<Vec<_>>()[..])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn insert_invalidates_cache() -> TestResult {
+        let mut s = setup();
+        s.insert("203.0.113.0/24".parse::<Prefix<Ipv4>>()?);
+        assert!(s.ranges_cache.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn remove_invalidates_cache() -> TestResult {
+        let mut s = setup();
+        s.remove("192.0.2.0/24".parse::<Prefix<Ipv4>>()?);
+        assert!(s.ranges_cache.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn clear_invalidates_cache() -> TestResult {
+        let mut s = setup();
+        s.clear();
+        assert!(s.ranges_cache.is_none());
+        assert!(s.materialize_ranges().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn re_read_after_mutation_recomputes() -> TestResult {
+        let mut s = setup();
+        assert_eq!(s.materialize_ranges().len(), 2);
+        s.insert("203.0.113.0/24".parse::<Prefix<Ipv4>>()?);
+        let ranges = s.materialize_ranges().to_vec();
+        assert_eq!(ranges.len(), 3);
+        assert!(ranges.contains(&"203.0.113.0/24,24,24".parse()?));
+        assert_eq!(ranges, s.ranges().collect::<Vec<_>>());
+        Ok(())
+    }
+}
+
+mod set_diff {
+    use super::*;
+
+    fn set_of(ranges: &[&str]) -> PrefixSet<Ipv4> {
+        ranges
+            .iter()
+            .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reports_added_and_removed_ranges() -> TestResult {
+        let old = set_of(&["10.0.0.0/8,8,8", "192.0.2.0/24,24,24"]);
+        let new = set_of(&["10.0.0.0/8,8,8", "198.51.100.0/24,24,24"]);
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.added().collect::<Vec<_>>(),
+            vec!["198.51.100.0/24,24,24".parse::<PrefixRange<Ipv4>>()?]
+        );
+        assert_eq!(
+            diff.removed().collect::<Vec<_>>(),
+            vec!["192.0.2.0/24,24,24".parse::<PrefixRange<Ipv4>>()?]
+        );
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "+ 198.51.100.0/24^24-24\n- 192.0.2.0/24^24-24\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn diff_of_equal_sets_is_empty() -> TestResult {
+        let s = set_of(&["10.0.0.0/8,16,24"]);
+        let diff = s.diff(&s.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+        Ok(())
+    }
+
+    #[test]
+    fn applying_diff_recovers_other() -> TestResult {
+        let old = set_of(&["10.0.0.0/8,8,12", "192.0.2.0/24,25,26"]);
+        let new = set_of(&["10.0.0.0/8,10,16", "203.0.113.0/24,24,24"]);
+        let diff = old.diff(&new);
+        let mut patched = old.clone();
+        patched
+            .remove_from(diff.removed())
+            .insert_from(diff.added());
+        assert_eq!(patched, new);
+        Ok(())
+    }
+}
+
+mod change_summary {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn set_of<A: ip::Afi>(ranges: &[&str]) -> PrefixSet<A> {
+        ranges
+            .iter()
+            .map(|r| r.parse::<ip::concrete::PrefixRange<A>>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn counts_added_removed_and_unchanged_prefixes() -> TestResult {
+        let previous = set_of::<Ipv4>(&["10.0.0.0/8,16,16", "192.0.2.0/24,24,24"]);
+        let current =
+            set_of::<Ipv4>(&["10.0.0.0/9,16,16", "10.0.0.0/8,8,8", "203.0.113.0/24,25,26"]);
+        let summary = current.change_summary(&previous);
+        // 10.0.0.0/8 + 2 x /25 + 4 x /26
+        assert_eq!(summary.added_prefixes, 1 + 2 + 4);
+        // 10.128.0.0/9 ge 16 le 16 + 192.0.2.0/24
+        assert_eq!(summary.removed_prefixes, 128 + 1);
+        // 10.0.0.0/9 ge 16 le 16
+        assert_eq!(summary.unchanged_prefixes, 128);
+        Ok(())
+    }
+
+    #[test]
+    fn identical_sets_are_all_unchanged() -> TestResult {
+        let s = set_of::<Ipv4>(&["10.0.0.0/8,16,24"]);
+        let summary = s.change_summary(&s.clone());
+        assert_eq!(summary.added_prefixes, 0);
+        assert_eq!(summary.removed_prefixes, 0);
+        assert_eq!(
+            summary.unchanged_prefixes,
+            (1 << 16)
+                + (1 << 15)
+                + (1 << 14)
+                + (1 << 13)
+                + (1 << 12)
+                + (1 << 11)
+                + (1 << 10)
+                + (1 << 9)
+                + (1 << 8)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn counts_saturate_for_huge_ipv6_sets() -> TestResult {
+        let all = set_of::<Ipv6>(&["::/0,0,128"]);
+        let summary = all.change_summary(&PrefixSet::new());
+        assert_eq!(summary.added_prefixes, u128::MAX);
+        assert_eq!(summary.removed_prefixes, 0);
+        assert_eq!(summary.unchanged_prefixes, 0);
+        Ok(())
+    }
+}
+
+mod insert_if_not_covered {
+    use super::*;
+
+    #[test]
+    fn skips_prefix_covered_by_a_less_specific() -> TestResult {
+        let mut s = PrefixSet::<Ipv4>::new()
+            .insert("192.0.0.0/16".parse::<Prefix<Ipv4>>()?)
+            .to_owned();
+        let before = s.clone();
+        assert!(!s.insert_if_not_covered("192.0.2.0/24".parse::<Prefix<Ipv4>>()?));
+        assert_eq!(s, before);
+        assert!(!s.contains("192.0.2.0/24".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn skips_range_covered_by_an_equal_prefix() -> TestResult {
+        let mut s = PrefixSet::<Ipv4>::new()
+            .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+            .to_owned();
+        assert!(!s.insert_if_not_covered("192.0.2.0/24,25,26".parse::<PrefixRange<Ipv4>>()?));
+        assert_eq!(s.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn inserts_prefix_covered_only_by_more_specifics() -> TestResult {
+        let mut s = PrefixSet::<Ipv4>::new()
+            .insert("192.0.2.0/24,25,25".parse::<PrefixRange<Ipv4>>()?)
+            .to_owned();
+        assert!(s.insert_if_not_covered("192.0.2.0/24".parse::<Prefix<Ipv4>>()?));
+        assert!(s.contains("192.0.2.0/24".parse()?));
+        assert_eq!(s.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn inserts_into_empty_set() -> TestResult {
+        let mut s = PrefixSet::<Ipv4>::new();
+        assert!(s.insert_if_not_covered("192.0.2.0/24".parse::<Prefix<Ipv4>>()?));
+        assert_eq!(s.len(), 1);
+        Ok(())
+    }
+}
+
+mod get_or_insert {
+    use super::*;
+
+    fn setup() -> TestResult<PrefixSet<Ipv4>> {
+        Ok("10.0.0.0/8,16,16 10.1.0.0/16,24,24 192.0.2.0/25 203.0.113.0/24,26,26".parse()?)
+    }
+
+    // the two-call equivalent of `get_or_insert()`.
+    fn check_then_insert(set: &mut PrefixSet<Ipv4>, prefix: Prefix<Ipv4>) -> bool {
+        if set.contains(prefix) {
+            false
+        } else {
+            set.insert(prefix);
+            true
+        }
+    }
+
+    #[test]
+    fn reports_present_prefixes() -> TestResult {
+        let mut s = setup()?;
+        let before = s.clone();
+        for prefix in [
+            "10.1.0.0/16",
+            "10.1.2.0/24",
+            "192.0.2.0/25",
+            "203.0.113.64/26",
+        ] {
+            assert!(!s.get_or_insert(prefix.parse()?), "{}", prefix);
+        }
+        assert_eq!(s, before);
+        Ok(())
+    }
+
+    #[test]
+    fn inserts_absent_prefixes() -> TestResult {
+        let mut s = setup()?;
+        for prefix in [
+            "10.0.0.0/8",
+            "10.1.2.0/25",
+            "192.0.2.128/25",
+            "198.51.100.0/24",
+        ] {
+            assert!(s.get_or_insert(prefix.parse()?), "{}", prefix);
+            assert!(s.contains(prefix.parse()?));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn aggregates_after_insertion() -> TestResult {
+        let mut s = setup()?;
+        assert!(s.get_or_insert("192.0.2.128/25".parse()?));
+        assert!(s.contains_range("192.0.2.0/24,25,25".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn inserts_into_empty_set() -> TestResult {
+        let mut s = PrefixSet::<Ipv4>::new();
+        assert!(s.get_or_insert("192.0.2.0/24".parse()?));
+        assert!(!s.get_or_insert("192.0.2.0/24".parse()?));
+        assert_eq!(s.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_check_then_insert() -> TestResult {
+        let queries = [
+            "0.0.0.0/0",
+            "10.0.0.0/8",
+            "10.0.0.0/16",
+            "10.1.0.0/16",
+            "10.1.0.0/17",
+            "10.1.255.0/24",
+            "10.2.3.0/24",
+            "192.0.2.0/24",
+            "192.0.2.0/25",
+            "192.0.2.0/26",
+            "192.0.2.128/25",
+            "203.0.113.0/24",
+            "203.0.113.192/26",
+            "203.0.113.192/27",
+        ];
+        let mut single = setup()?;
+        let mut double = setup()?;
+        for prefix in queries {
+            let prefix = prefix.parse()?;
+            assert_eq!(
+                single.get_or_insert(prefix),
+                check_then_insert(&mut double, prefix),
+                "{}",
+                prefix
+            );
+            assert_eq!(single, double);
+        }
+        for prefix in queries {
+            assert!(!single.get_or_insert(prefix.parse()?));
+        }
+        Ok(())
+    }
+}
+
+mod insert_from_instrumented {
+    use super::super::AggregateEvent;
+    use super::*;
+
+    fn insert<T: Into<Node<Ipv4>>>(
+        set: &mut PrefixSet<Ipv4>,
+        items: Vec<T>,
+    ) -> Vec<AggregateEvent<Ipv4>> {
+        let mut events = Vec::new();
+        set.insert_from_instrumented(items, |event| events.push(event));
+        events
+    }
+
+    #[test]
+    fn reports_merge_of_siblings_and_clean_of_children() -> TestResult {
+        let mut s = PrefixSet::new();
+        let events = insert(
+            &mut s,
+            vec![
+                "192.0.2.0/25".parse::<Prefix<Ipv4>>()?,
+                "192.0.2.128/25".parse()?,
+            ],
+        );
+        assert_eq!(
+            events,
+            vec![
+                AggregateEvent::Merge {
+                    prefix: "192.0.2.0/24".parse()?
+                },
+                AggregateEvent::Clean {
+                    prefix: "192.0.2.0/25".parse()?
+                },
+                AggregateEvent::Clean {
+                    prefix: "192.0.2.128/25".parse()?
+                },
+            ]
+        );
+        assert_eq!(s.len(), 2);
+        assert!(s.is_aggregated());
+        Ok(())
+    }
+
+    #[test]
+    fn reports_deduplication_under_a_covering_range() -> TestResult {
+        let mut s = PrefixSet::new();
+        let events = insert(
+            &mut s,
+            vec![
+                "192.0.2.0/25,25,25".parse::<PrefixRange<Ipv4>>()?,
+                "192.0.2.128/25,25,25".parse()?,
+                "192.0.2.0/23,25,25".parse()?,
+            ],
+        );
+        assert_eq!(
+            events,
+            vec![
+                AggregateEvent::Deduplicate {
+                    prefix: "192.0.2.0/25".parse()?
+                },
+                AggregateEvent::Clean {
+                    prefix: "192.0.2.0/25".parse()?
+                },
+                AggregateEvent::Deduplicate {
+                    prefix: "192.0.2.128/25".parse()?
+                },
+                AggregateEvent::Clean {
+                    prefix: "192.0.2.128/25".parse()?
+                },
+                AggregateEvent::Clean {
+                    prefix: "192.0.2.0/24".parse()?
+                },
+            ]
+        );
+        assert_eq!(s.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_insert_from() -> TestResult {
+        let items: Vec<PrefixRange<Ipv4>> =
+            ["10.0.0.0/8,16,16", "10.1.0.0/16,16,24", "10.2.0.0/16,16,16"]
+                .into_iter()
+                .map(str::parse)
+                .collect::<Result<_, _>>()?;
+        let mut instrumented: PrefixSet<Ipv4> = "192.0.2.0/24".parse()?;
+        let mut plain = instrumented.clone();
+        assert!(!insert(&mut instrumented, items.clone()).is_empty());
+        plain.insert_from(items);
+        assert_eq!(instrumented, plain);
+        Ok(())
+    }
+
+    #[test]
+    fn no_new_items_reports_no_events() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "192.0.2.0/24,25,26".parse()?;
+        assert!(insert::<Prefix<Ipv4>>(&mut s, Vec::new()).is_empty());
+        Ok(())
+    }
+}
+
+mod shards {
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        // heavily skewed: one range holding almost every prefix, plus a
+        // handful of individual prefixes.
+        [
+            "10.0.0.0/8,20,20",
+            "192.0.2.0/24,24,24",
+            "198.51.100.0/24,24,24",
+            "203.0.113.0/24,25,25",
+        ]
+        .into_iter()
+        .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn returns_requested_number_of_shards() -> TestResult {
+        let s = setup();
+        for n in 1..=8 {
+            assert_eq!(s.shards(n).len(), n);
+        }
+        assert!(s.shards(0).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn shards_are_pairwise_disjoint() -> TestResult {
+        let shards = setup().shards(5);
+        for (i, a) in shards.iter().enumerate() {
+            for b in &shards[i + 1..] {
+                assert_eq!((a.clone() & b.clone()).count_prefixes(), 0);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn shards_union_to_the_whole() -> TestResult {
+        let s = setup();
+        let union = s
+            .shards(5)
+            .into_iter()
+            .fold(PrefixSet::new(), |acc, shard| acc | shard);
+        assert_eq!(union, s);
+        Ok(())
+    }
+
+    #[test]
+    fn shards_are_balanced() -> TestResult {
+        let s = setup();
+        let target = s.count_prefixes() / 5;
+        for shard in s.shards(5) {
+            let count = shard.count_prefixes();
+            assert!(count * 10 >= target * 9, "{count} is far below {target}");
+            assert!(count * 10 <= target * 11, "{count} is far above {target}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn small_set_leaves_empty_shards() -> TestResult {
+        let s = PrefixSet::<Ipv4>::new()
+            .insert("192.0.2.0/24".parse::<Prefix<Ipv4>>()?)
+            .to_owned();
+        let shards = s.shards(3);
+        assert_eq!(shards[0], s);
+        assert!(shards[1..].iter().all(|shard| shard.count_prefixes() == 0));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+mod overlay {
+    use super::*;
+
+    fn set_of(ranges: &[&str]) -> PrefixSet<Ipv4> {
+        ranges
+            .iter()
+            .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn non_conflicting_entries_pass_through() -> TestResult {
+        let ours = set_of(&["192.0.2.0/24,24,24", "10.0.0.0/8,16,16"]);
+        let theirs = set_of(&["198.51.100.0/24,25,25", "10.0.0.0/8,16,16"]);
+        let mut calls = 0;
+        let set = ours.overlay(&theirs, |ours, _| {
+            calls += 1;
+            ours
+        });
+        assert_eq!(calls, 0);
+        assert_eq!(set, ours | theirs);
+        Ok(())
+    }
+
+    #[test]
+    fn conflicting_bases_invoke_the_resolver() -> TestResult {
+        let ours = set_of(&["192.0.2.0/24,24,26", "203.0.113.0/24,24,24"]);
+        let theirs = set_of(&["192.0.2.0/24,25,25"]);
+        let mut conflicts = Vec::new();
+        let set = ours.overlay(&theirs, |ours, theirs| {
+            conflicts.push((ours.clone(), theirs.clone()));
+            theirs
+        });
+        assert_eq!(
+            conflicts,
+            vec![(
+                "192.0.2.0/24,24,26".parse::<PrefixRange<Ipv4>>()?,
+                "192.0.2.0/24,25,25".parse::<PrefixRange<Ipv4>>()?
+            )]
+        );
+        assert_eq!(set, set_of(&["192.0.2.0/24,25,25", "203.0.113.0/24,24,24"]));
+        Ok(())
+    }
+
+    #[test]
+    fn resolver_can_narrow_the_union() -> TestResult {
+        let ours = set_of(&["192.0.2.0/24,24,25"]);
+        let theirs = set_of(&["192.0.2.0/24,24,26"]);
+        let set = ours.overlay(&theirs, |ours, _| ours);
+        assert_eq!(set, ours);
+        assert_ne!(set, ours | theirs);
+        Ok(())
+    }
+}
+
+mod contains_range {
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        ["192.0.0.0/16,24,24", "192.0.2.0/24,25,26"]
+            .into_iter()
+            .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn empty_set_contains_no_ranges() -> TestResult {
+        let s = PrefixSet::<Ipv4>::new();
+        assert!(!s.contains_range("192.0.2.0/24,24,24".parse()?));
+        assert!(!s.contains_range("0.0.0.0/0,0,32".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn contains_inserted_range() -> TestResult {
+        let s = setup();
+        assert!(s.contains_range("192.0.2.0/24,25,26".parse()?));
+        assert!(s.contains_range("192.0.0.0/16,24,24".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn contains_range_spanning_nodes() -> TestResult {
+        // the /24 is held by the /16 node, and the /25s and /26s by the /24
+        // node.
+        let s = setup();
+        assert!(s.contains_range("192.0.2.0/24,24,26".parse()?));
+        assert!(s.contains_range("192.0.2.64/26,26,26".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_contain_partially_covered_range() -> TestResult {
+        let s = setup();
+        assert!(!s.contains_range("192.0.2.0/24,24,27".parse()?));
+        assert!(!s.contains_range("192.0.3.0/24,24,25".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_contain_range_based_on_super_prefix() -> TestResult {
+        let s = setup();
+        assert!(!s.contains_range("192.0.0.0/15,24,24".parse()?));
+        assert!(!s.contains_range("192.0.0.0/8,25,25".parse()?));
+        Ok(())
+    }
+}
+
+mod covers_all_of {
+    use super::*;
+
+    fn range(s: &str) -> PrefixRange<Ipv4> {
+        s.parse().unwrap()
+    }
+
+    fn halves() -> TestResult<PrefixSet<Ipv4>> {
+        Ok("192.0.2.0/25 192.0.2.128/25".parse()?)
+    }
+
+    // brute force: every prefix of `range` is contained in `set`.
+    fn expected(set: &PrefixSet<Ipv4>, range: PrefixRange<Ipv4>) -> bool {
+        PrefixSet::singleton(range)
+            .prefixes()
+            .all(|prefix| set.contains(prefix))
+    }
+
+    #[test]
+    fn halves_cover_range() -> TestResult {
+        let s = halves()?;
+        assert!(s.covers_all_of(range("192.0.2.0/24,25,25")));
+        Ok(())
+    }
+
+    #[test]
+    fn halves_do_not_cover_other_lengths() -> TestResult {
+        let s = halves()?;
+        assert!(!s.covers_all_of(range("192.0.2.0/24,24,25")));
+        assert!(!s.covers_all_of(range("192.0.2.0/24,25,26")));
+        assert!(!s.covers_all_of(range("192.0.0.0/23,25,25")));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_half_is_not_covered() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.0/25 192.0.2.128/26".parse()?;
+        assert!(!s.covers_all_of(range("192.0.2.0/24,25,25")));
+        assert!(s.covers_all_of(range("192.0.2.0/25,25,25")));
+        Ok(())
+    }
+
+    #[test]
+    fn range_covered_by_ancestor_and_subtree() -> TestResult {
+        let s: PrefixSet<Ipv4> =
+            "192.0.0.0/16,24,24 192.0.2.0/25,25,26 192.0.2.128/25,25,26".parse()?;
+        assert!(s.covers_all_of(range("192.0.2.0/24,24,26")));
+        assert!(!s.covers_all_of(range("192.0.2.0/24,24,27")));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_covers_nothing() {
+        let s = PrefixSet::<Ipv4>::new();
+        assert!(!s.covers_all_of(range("192.0.2.0/24,25,25")));
+    }
+
+    #[test]
+    fn agrees_with_brute_force() -> TestResult {
+        let s: PrefixSet<Ipv4> =
+            "10.0.0.0/8,9,9 10.0.0.0/9,10,11 10.128.0.0/10,11,11 10.192.0.0/10,12,12".parse()?;
+        for r in [
+            "10.0.0.0/8,8,8",
+            "10.0.0.0/8,9,9",
+            "10.0.0.0/8,9,10",
+            "10.0.0.0/8,11,11",
+            "10.0.0.0/8,12,12",
+            "10.0.0.0/9,10,11",
+            "10.128.0.0/9,11,11",
+            "10.128.0.0/9,12,12",
+            "10.192.0.0/10,11,12",
+        ] {
+            assert_eq!(s.covers_all_of(range(r)), expected(&s, range(r)), "{r}");
+            assert_eq!(s.covers_all_of(range(r)), s.contains_range(range(r)), "{r}");
+        }
+        Ok(())
+    }
+}
+
+mod subsets_and_disjointness {
+    use super::*;
+
+    fn set_of(ranges: &[&str]) -> PrefixSet<Ipv4> {
+        ranges
+            .iter()
+            .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn empty_set_is_subset_of_everything() -> TestResult {
+        let empty = PrefixSet::<Ipv4>::new();
+        let s = set_of(&["1.0.0.0/8,8,16"]);
+        assert!(empty.is_subset(&empty));
+        assert!(empty.is_subset(&s));
+        assert!(!s.is_subset(&empty));
+        assert!(s.is_superset(&empty));
+        assert!(empty.is_superset(&empty));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_is_disjoint_from_everything() -> TestResult {
+        let empty = PrefixSet::<Ipv4>::new();
+        let s = set_of(&["1.0.0.0/8,8,16"]);
+        assert!(empty.is_disjoint(&empty));
+        assert!(empty.is_disjoint(&s));
+        assert!(s.is_disjoint(&empty));
+        Ok(())
+    }
+
+    #[test]
+    fn nested_ranges() -> TestResult {
+        let outer = set_of(&["1.0.0.0/8,8,16"]);
+        let inner = set_of(&["1.0.0.0/12,12,16"]);
+        assert!(inner.is_subset(&outer));
+        assert!(!outer.is_subset(&inner));
+        assert!(outer.is_superset(&inner));
+        assert!(!inner.is_superset(&outer));
+        assert!(!outer.is_disjoint(&inner));
+        assert!(!inner.is_disjoint(&outer));
+        Ok(())
+    }
+
+    #[test]
+    fn subset_spanning_nodes() -> TestResult {
+        let s = set_of(&["192.0.0.0/16,24,24", "192.0.2.0/24,25,25"]);
+        let sub = set_of(&["192.0.2.0/24,24,25"]);
+        assert!(sub.is_subset(&s));
+        assert!(!s.is_subset(&sub));
+        Ok(())
+    }
+
+    #[test]
+    fn overlapping_ranges_are_neither_subsets_nor_disjoint() -> TestResult {
+        let a = set_of(&["1.0.0.0/8,8,12"]);
+        let b = set_of(&["1.0.0.0/8,12,16"]);
+        assert!(!a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(!a.is_disjoint(&b));
+        Ok(())
+    }
+
+    #[test]
+    fn disjoint_sets() -> TestResult {
+        let a = set_of(&["1.0.0.0/8,8,11", "2.0.0.0/8,8,16"]);
+        let b = set_of(&["1.0.0.0/8,12,15", "3.0.0.0/8,8,16"]);
+        assert!(a.is_disjoint(&b));
+        assert!(b.is_disjoint(&a));
+        assert!(!a.is_subset(&b));
+        Ok(())
+    }
+}
+
+mod owned_iteration {
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        [
+            "10.0.0.0/8,16,16",
+            "192.0.2.0/24,25,26",
+            "198.51.100.0/24,24,24",
+            "203.0.113.0/25,27,28",
+        ]
+        .into_iter()
+        .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn into_ranges_matches_ranges() -> TestResult {
+        let s = setup();
+        let borrowed: Vec<_> = s.ranges().collect();
+        let owned: Vec<_> = s.into_ranges().collect();
+        assert_eq!(owned, borrowed);
+        Ok(())
+    }
+
+    #[test]
+    fn into_iter_matches_prefixes() -> TestResult {
+        let s = setup();
+        let borrowed: Vec<_> = s.prefixes().collect();
+        let owned: Vec<_> = s.into_iter().collect();
+        assert_eq!(owned, borrowed);
+        Ok(())
+    }
+
+    #[test]
+    fn for_loop_over_owned_set() -> TestResult {
+        let mut count = 0;
+        for _ in setup() {
+            count += 1;
+        }
+        assert_eq!(count, 256 + 6 + 1 + 12);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_yields_nothing() -> TestResult {
+        assert_eq!(PrefixSet::<Ipv4>::new().into_ranges().count(), 0);
+        assert_eq!(PrefixSet::<Ipv4>::new().into_iter().count(), 0);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_round_trip {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn setup_ipv4() -> PrefixSet<Ipv4> {
+        [
+            "10.0.0.0/8,16,24",
+            "192.0.2.0/24,24,24",
+            "198.51.100.0/24,25,26",
+        ]
+        .into_iter()
+        .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+        .collect()
+    }
+
+    fn setup_ipv6() -> PrefixSet<Ipv6> {
+        ["2001:db8::/36,37,37", "2001:db8:f00::/48,48,64"]
+            .into_iter()
+            .map(|r| r.parse::<ip::concrete::PrefixRange<Ipv6>>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn json_uses_range_strings() -> TestResult {
+        let json = serde_json::to_string(&setup_ipv6())?;
+        assert_eq!(json, r#"["2001:db8::/36,37,37","2001:db8:f00::/48,48,64"]"#);
+        Ok(())
+    }
+
+    #[test]
+    fn json_round_trips() -> TestResult {
+        let s = setup_ipv4();
+        let t: PrefixSet<Ipv4> = serde_json::from_str(&serde_json::to_string(&s)?)?;
+        assert_eq!(s, t);
+        let s = setup_ipv6();
+        let t: PrefixSet<Ipv6> = serde_json::from_str(&serde_json::to_string(&s)?)?;
+        assert_eq!(s, t);
+        Ok(())
+    }
+
+    #[test]
+    fn binary_round_trips() -> TestResult {
+        let s = setup_ipv4();
+        let t: PrefixSet<Ipv4> = serde_cbor::from_slice(&serde_cbor::to_vec(&s)?)?;
+        assert_eq!(s, t);
+        let s = setup_ipv6();
+        let t: PrefixSet<Ipv6> = serde_cbor::from_slice(&serde_cbor::to_vec(&s)?)?;
+        assert_eq!(s, t);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_round_trips() -> TestResult {
+        let json = serde_json::to_string(&PrefixSet::<Ipv4>::new())?;
+        assert_eq!(json, "[]");
+        let t: PrefixSet<Ipv4> = serde_json::from_str(&json)?;
+        assert_eq!(t, PrefixSet::new());
+        Ok(())
+    }
+
+    #[test]
+    fn deserializing_aggregates_overlapping_ranges() -> TestResult {
+        let t: PrefixSet<Ipv4> = serde_json::from_str(
+            r#"["192.0.2.0/25,25,25","192.0.2.128/25","192.0.2.0/24,25,25"]"#,
+        )?;
+        assert_eq!(
+            t.ranges().collect::<Vec<_>>(),
+            vec!["192.0.2.0/24,25,25".parse()?]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_range_is_an_error() -> TestResult {
+        assert!(serde_json::from_str::<PrefixSet<Ipv4>>(r#"["192.0.2.0/24,23,24"]"#).is_err());
+        assert!(serde_json::from_str::<PrefixSet<Ipv4>>(r#"["2001:db8::/32,32,32"]"#).is_err());
+        Ok(())
+    }
+}
+
+mod from_str {
+    use ip::Ipv6;
+
+    use crate::error::{Error, RangeField};
+
+    use super::*;
+
+    fn set_of(ranges: &[&str]) -> PrefixSet<Ipv4> {
+        ranges
+            .iter()
+            .map(|r| r.parse::<PrefixRange<Ipv4>>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parses_whitespace_separated_entries() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.0/24\n198.51.100.0/24,25,26\t203.0.113.0/24".parse()?;
+        assert_eq!(
+            s,
+            set_of(&[
+                "192.0.2.0/24,24,24",
+                "198.51.100.0/24,25,26",
+                "203.0.113.0/24,24,24"
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parses_comma_separated_entries() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.0/24,198.51.100.0/24,25,26, 203.0.113.0/24,".parse()?;
+        assert_eq!(
+            s,
+            set_of(&[
+                "192.0.2.0/24,24,24",
+                "198.51.100.0/24,25,26",
+                "203.0.113.0/24,24,24"
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parses_ipv6_entries() -> TestResult {
+        let s: PrefixSet<Ipv6> = "2001:db8::/36,37,37\n2001:db8:f00::/48".parse()?;
+        assert_eq!(s.ranges().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_input_is_empty_set() -> TestResult {
+        let s: PrefixSet<Ipv4> = " \n ".parse()?;
+        assert_eq!(s, PrefixSet::new());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_entry_is_reported() -> TestResult {
+        match "192.0.2.0/24 192.0.2.0/33".parse::<PrefixSet<Ipv4>>() {
+            Err(Error::EntryParse { token, .. }) => assert_eq!(token, "192.0.2.0/33"),
+            res => panic!("expected parse error, got {:?}", res),
+        };
+        match "192.0.2.0/24,25".parse::<PrefixSet<Ipv4>>() {
+            Err(Error::EntryParse { token, .. }) => assert_eq!(token, "192.0.2.0/24,25"),
+            res => panic!("expected parse error, got {:?}", res),
+        };
+        match "24 192.0.2.0/24".parse::<PrefixSet<Ipv4>>() {
+            Err(Error::EntryParse { token, .. }) => assert_eq!(token, "24"),
+            res => panic!("expected parse error, got {:?}", res),
+        };
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_range_bound_is_reported() {
+        let input = "192.0.2.0/24 198.51.100.0/24,25,x26";
+        match input.parse::<PrefixSet<Ipv4>>() {
+            Err(Error::RangeParse {
+                field,
+                token,
+                source,
+                ..
+            }) => {
+                assert_eq!(field, RangeField::Upper);
+                assert_eq!(token, "x26");
+                assert!(source.is_some());
+            }
+            res => panic!("expected range parse error, got {:?}", res),
+        };
+        let err = input.parse::<PrefixSet<Ipv4>>().unwrap_err();
+        assert!(err.to_string().contains("'x26'"));
+    }
+
+    #[test]
+    fn invalid_range_fields_are_identified() {
+        let cases = [
+            ("1.0.0.0/8,abc,16", RangeField::Lower, "abc", true),
+            ("1.0.0.0/8,8,33", RangeField::Upper, "33", false),
+            ("1.0.0.0/33,8,16", RangeField::Prefix, "1.0.0.0/33", false),
+        ];
+        for (entry, expected_field, expected_token, has_source) in cases {
+            match entry.parse::<PrefixSet<Ipv4>>() {
+                Err(Error::RangeParse {
+                    input,
+                    field,
+                    token,
+                    source,
+                }) => {
+                    assert_eq!(input, entry);
+                    assert_eq!(field, expected_field);
+                    assert_eq!(token, expected_token);
+                    assert_eq!(source.is_some(), has_source);
+                }
+                res => panic!("expected range parse error, got {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn range_error_display_names_field() {
+        let err = "1.0.0.0/8,abc,16".parse::<PrefixSet<Ipv4>>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid IP prefix range '1.0.0.0/8,abc,16': bad lower bound 'abc'"
+        );
+    }
+
+    #[test]
+    fn parse_int_error_converts_to_range_error() {
+        fn parse_bound(s: &str) -> Result<u8, Error> {
+            Ok(s.parse()?)
+        }
+        match parse_bound("x") {
+            Err(Error::RangeParse {
+                input,
+                token,
+                source,
+                ..
+            }) => {
+                assert!(input.is_empty());
+                assert!(token.is_empty());
+                assert!(source.is_some());
+            }
+            res => panic!("expected range parse error, got {:?}", res),
+        }
+    }
+}
+
+mod longest_match {
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        "1.0.0.0/8 1.2.0.0/16 1.2.3.0/24".parse().unwrap()
+    }
+
+    #[test]
+    fn finds_most_specific_covering_prefix() -> TestResult {
+        let s = setup();
+        assert_eq!(
+            s.longest_match("1.2.3.4/32".parse()?),
+            Some("1.2.3.0/24".parse()?)
+        );
+        assert_eq!(
+            s.longest_match("1.2.4.0/24".parse()?),
+            Some("1.2.0.0/16".parse()?)
+        );
+        assert_eq!(
+            s.longest_match("1.3.0.0/16".parse()?),
+            Some("1.0.0.0/8".parse()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn matches_equal_prefix() -> TestResult {
+        let s = setup();
+        assert_eq!(
+            s.longest_match("1.2.3.0/24".parse()?),
+            Some("1.2.3.0/24".parse()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_match_more_specific_prefixes() -> TestResult {
+        let s = setup();
+        assert_eq!(s.longest_match("1.0.0.0/7".parse()?), None);
+        assert_eq!(s.longest_match("2.0.0.0/8".parse()?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_prefix_within_a_range() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,16,20".parse()?;
+        assert_eq!(
+            s.longest_match("10.1.2.3/32".parse()?),
+            Some("10.1.0.0/20".parse()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_matches_nothing() -> TestResult {
+        let s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.longest_match("1.2.3.4/32".parse()?), None);
+        Ok(())
+    }
+}
+
+mod covering {
+    use super::*;
+
+    fn covering(s: &PrefixSet<Ipv4>, query: &str) -> Vec<Prefix<Ipv4>> {
+        s.covering(query.parse().unwrap()).collect()
+    }
+
+    fn prefixes(prefixes: &[&str]) -> Vec<Prefix<Ipv4>> {
+        prefixes.iter().map(|p| p.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn yields_covering_prefixes_by_ascending_length() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.0.0.0/8 1.2.0.0/16 1.2.3.0/24 1.3.0.0/16".parse()?;
+        assert_eq!(
+            covering(&s, "1.2.3.4/32"),
+            prefixes(&["1.0.0.0/8", "1.2.0.0/16", "1.2.3.0/24"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn includes_query_equal_to_stored_prefix() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.0.0.0/8 1.2.0.0/16 1.2.3.0/24".parse()?;
+        assert_eq!(
+            covering(&s, "1.2.0.0/16"),
+            prefixes(&["1.0.0.0/8", "1.2.0.0/16"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expands_multiple_lengths_at_one_node() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,8,9 10.0.0.0/8,11,11".parse()?;
+        assert_eq!(
+            covering(&s, "10.1.2.3/32"),
+            prefixes(&["10.0.0.0/8", "10.0.0.0/9", "10.0.0.0/11"])
+        );
+        assert_eq!(
+            covering(&s, "10.0.0.0/10"),
+            prefixes(&["10.0.0.0/8", "10.0.0.0/9"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn yields_nothing_when_uncovered() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.2.0.0/16".parse()?;
+        assert!(covering(&s, "1.0.0.0/8").is_empty());
+        assert!(covering(&s, "2.2.0.0/16").is_empty());
+        assert!(covering(&PrefixSet::new(), "1.2.0.0/16").is_empty());
+        Ok(())
+    }
+}
+
+mod is_empty {
+    use super::*;
+
+    #[test]
+    fn inserting_then_removing_a_prefix_is_empty() -> TestResult {
+        let p: Prefix<Ipv4> = "192.0.2.0/24".parse()?;
+        let mut s = PrefixSet::new();
+        s.insert(p);
+        assert!(!s.is_empty());
+        s.remove(p);
+        assert!(s.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn glue_only_tree_does_not_survive_aggregation() -> TestResult {
+        let p: Prefix<Ipv4> = "192.0.2.0/24".parse()?;
+        let q: Prefix<Ipv4> = "198.51.100.0/24".parse()?;
+        let mut s = PrefixSet::new();
+        s.insert_node(Node::from(p).boxed())
+            .insert_node(Node::from(q).boxed())
+            .remove_node(Node::from(p).boxed())
+            .remove_node(Node::from(q).boxed());
+        assert!(s.root.is_some());
+        assert_eq!(s.ranges().count(), 0);
+        s.aggregate();
+        assert!(s.root.is_none());
+        assert!(s.is_empty());
+        Ok(())
+    }
+}
+
+mod explicit_aggregation {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        let mut s = PrefixSet::new();
+        s.insert_node(Node::from("192.0.2.0/25".parse::<Prefix<Ipv4>>().unwrap()).boxed())
+            .insert_node(Node::from("192.0.2.128/25".parse::<Prefix<Ipv4>>().unwrap()).boxed());
+        s.prefix_count = s.walk_prefix_count();
+        s
+    }
+
+    #[test]
+    fn aggregate_collapses_sibling_prefixes() -> TestResult {
+        let mut s = setup();
+        assert_eq!(
+            s.ranges().collect::<Vec<_>>(),
+            vec![
+                "192.0.2.128/25,25,25".parse::<PrefixRange<Ipv4>>()?,
+                "192.0.2.0/25,25,25".parse()?,
+            ]
+        );
+        s.aggregate();
+        assert_eq!(
+            s.ranges().collect::<Vec<_>>(),
+            vec!["192.0.2.0/24,25,25".parse::<PrefixRange<Ipv4>>()?]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_is_idempotent() -> TestResult {
+        let mut s = setup();
+        let once: Vec<_> = s.aggregate().ranges().collect();
+        let twice: Vec<_> = s.aggregate().ranges().collect();
+        assert_eq!(once, twice);
+        Ok(())
+    }
+
+    #[test]
+    fn aggregated_leaves_original_untouched() -> TestResult {
+        let s = setup();
+        let t = s.aggregated();
+        assert_eq!(s.ranges().count(), 2);
+        assert_eq!(t.ranges().count(), 1);
+        assert_eq!(
+            t.prefixes().collect::<HashSet<_>>(),
+            s.prefixes().collect::<HashSet<_>>()
+        );
+        Ok(())
+    }
+}
+
+mod retain {
+    use ip::traits::Prefix as _;
+
+    use super::*;
+
+    #[test]
+    fn retains_prefixes_up_to_length() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "10.0.0.0/16,22,26 192.0.2.0/24,24,24".parse()?;
+        let max = 24.try_into()?;
+        let expected: Vec<_> = s.prefixes().filter(|p| p.length() <= max).collect();
+        s.retain(|p| p.length() <= max);
+        assert!(s.prefixes().all(|p| p.length() <= max));
+        let mut retained: Vec<_> = s.prefixes().collect();
+        let mut expected = expected;
+        retained.sort_by_key(|p| (p.network(), p.length()));
+        expected.sort_by_key(|p| (p.network(), p.length()));
+        assert_eq!(retained, expected);
+        assert_eq!(s, "10.0.0.0/16,22,24 192.0.2.0/24".parse()?);
+        Ok(())
+    }
+
+    #[test]
+    fn retaining_everything_is_unchanged() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "10.0.0.0/16,22,26".parse()?;
+        let before = s.clone();
+        s.retain(|_| true);
+        assert_eq!(s, before);
+        Ok(())
+    }
+
+    #[test]
+    fn retaining_nothing_is_empty() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "10.0.0.0/16,22,24".parse()?;
+        s.retain(|_| false);
+        assert!(s.is_empty());
+        Ok(())
+    }
+}
+
+mod filter_length {
+    use ip::concrete::PrefixLength;
+
+    use super::*;
+
+    fn length(l: u8) -> PrefixLength<Ipv4> {
+        PrefixLength::from_primitive(l).unwrap()
+    }
+
+    fn setup() -> PrefixSet<Ipv4> {
+        "10.0.0.0/8,8,16 192.0.2.0/24,24,26 198.51.100.0/24"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn keeps_lengths_in_window() -> TestResult {
+        let s = setup().filter_length(length(16)..=length(24));
+        assert_eq!(s, "10.0.0.0/8,16,16 192.0.2.0/24 198.51.100.0/24".parse()?);
+        Ok(())
+    }
+
+    #[test]
+    fn window_excluding_everything_is_zero() -> TestResult {
+        let s = setup();
+        assert!(s.filter_length(length(27)..=length(32)).is_empty());
+        assert!(s.filter_length(length(17)..=length(23)).is_empty());
+        let empty = length(24)..=length(8);
+        assert!(s.filter_length(empty).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn window_containing_everything_is_equal() -> TestResult {
+        let s = setup();
+        assert_eq!(s.filter_length(length(8)..=length(26)), s);
+        assert_eq!(s.filter_length(length(0)..=length(32)), s);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_retain_by_length() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "10.0.0.0/12,12,16 192.0.2.0/24,24,26".parse()?;
+        let filtered = s.filter_length(length(14)..=length(25));
+        s.retain(|p| (length(14)..=length(25)).contains(&p.length()));
+        assert_eq!(filtered, s);
+        Ok(())
+    }
+}
+
+mod reverse_iteration {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        "10.0.0.0/8,8,10 10.128.0.0/9,12,12 192.0.2.0/24,24,26 192.0.2.0/25,27,27 198.51.100.0/24"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn reversed_ranges_are_forward_ranges_reversed() {
+        let s = setup();
+        let mut forward: Vec<_> = s.ranges().collect();
+        forward.reverse();
+        assert_eq!(s.ranges().rev().collect::<Vec<_>>(), forward);
+    }
+
+    #[test]
+    fn reversed_prefixes_are_forward_prefixes_reversed() {
+        let s = setup();
+        let mut forward: Vec<_> = s.prefixes().collect();
+        forward.reverse();
+        assert_eq!(s.prefixes().rev().collect::<Vec<_>>(), forward);
+    }
+
+    #[test]
+    fn reversed_ipv6_prefixes_are_forward_prefixes_reversed() -> TestResult {
+        let s: PrefixSet<Ipv6> = "2001:db8::/32,32,34 2001:db8:f00::/40,44,46 ::/0".parse()?;
+        let mut forward: Vec<_> = s.prefixes().collect();
+        forward.reverse();
+        assert_eq!(s.prefixes().rev().collect::<Vec<_>>(), forward);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_yields_nothing_from_the_back() {
+        let s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.ranges().next_back(), None);
+        assert_eq!(s.prefixes().next_back(), None);
+    }
+
+    #[test]
+    fn alternating_ends_yield_each_item_once() {
+        let s = setup();
+        let forward: Vec<_> = s.prefixes().collect();
+        for skew in 0..3 {
+            let mut iter = s.prefixes();
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            let mut turn = 0;
+            loop {
+                let item = if turn % 3 < skew {
+                    iter.next().map(|p| front.push(p))
+                } else {
+                    iter.next_back().map(|p| back.push(p))
+                };
+                if item.is_none() {
+                    break;
+                }
+                turn += 1;
+            }
+            back.reverse();
+            front.extend(back);
+            assert_eq!(front, forward);
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+        }
+    }
+
+    #[test]
+    fn alternating_ranges_yield_each_item_once() {
+        let s = setup();
+        let forward: Vec<_> = s.ranges().collect();
+        let mut iter = s.ranges();
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        while let Some(range) = iter.next() {
+            front.push(range);
+            match iter.next_back() {
+                Some(range) => back.push(range),
+                None => break,
+            }
+        }
+        back.reverse();
+        front.extend(back);
+        assert_eq!(front, forward);
+    }
+}
+
+mod iterator_sizes {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        "10.0.0.0/8,8,10 10.128.0.0/9,12,12 192.0.2.0/24,24,26 198.51.100.0/24"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn prefixes_size_hint_is_exact() {
+        let s = setup();
+        let mut iter = s.prefixes();
+        let mut expected = s.prefixes().count();
+        assert_eq!(iter.size_hint(), (expected, Some(expected)));
+        while iter.next().is_some() {
+            expected -= 1;
+            assert_eq!(iter.size_hint(), (expected, Some(expected)));
+            if iter.next_back().is_some() {
+                expected -= 1;
+            }
+            assert_eq!(iter.size_hint(), (expected, Some(expected)));
+        }
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn empty_set_prefixes_size_hint_is_zero() {
+        assert_eq!(
+            PrefixSet::<Ipv4>::new().prefixes().size_hint(),
+            (0, Some(0))
+        );
+    }
+
+    #[test]
+    fn first_prefix_of_huge_set() -> TestResult {
+        let s: PrefixSet<Ipv6> = "::/0,0,128".parse()?;
+        let mut iter = s.prefixes();
+        assert_eq!(iter.next(), Some("::/0".parse()?));
+        assert_eq!(
+            iter.next_back(),
+            Some("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128".parse()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_prefixes_size_hint_is_unbounded() -> TestResult {
+        let s: PrefixSet<Ipv6> = "::/0,0,128".parse()?;
+        assert_eq!(s.prefixes().size_hint(), (usize::MAX, None));
+        assert_eq!(s.len(), usize::MAX);
+        Ok(())
+    }
+
+    #[test]
+    fn ranges_size_hint_is_a_lower_bound() {
+        let s = setup();
+        let total = s.ranges().count();
+        let mut iter = s.ranges();
+        let mut yielded = 0;
+        loop {
+            let (lower, upper) = iter.size_hint();
+            assert!(lower <= total - yielded);
+            assert!(upper.is_none_or(|upper| upper >= total - yielded));
+            if iter.next().is_none() {
+                break;
+            }
+            yielded += 1;
+        }
+        assert_eq!(yielded, total);
+    }
+
+    #[test]
+    fn ranges_size_hint_counts_started_nodes() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,8,8 10.0.0.0/8,10,10 10.0.0.0/8,12,12".parse()?;
+        let mut iter = s.ranges();
+        iter.next();
+        assert_eq!(iter.size_hint().0, 2);
+        Ok(())
+    }
+}
+
+mod display {
+    use super::*;
+
+    fn setup() -> PrefixSet<Ipv4> {
+        vec![
+            "192.0.2.0/27",
+            "192.0.2.32/27",
+            "192.0.2.64/27",
+            "192.0.2.96/27",
+            "192.0.2.128/26",
+            "192.0.2.128/27",
+            "192.0.2.160/27",
+            "192.0.2.192/27",
+            "192.0.2.224/27",
+        ]
+        .into_iter()
+        .map(|prefix| prefix.parse::<Prefix<Ipv4>>().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn ranges_match_fixture() {
+        assert_eq!(
+            setup().to_string(),
+            include_str!("../../test_data/RS-EXAMPLE-ranges.txt")
+        );
+    }
+
+    #[test]
+    fn prefixes_match_fixture() {
+        assert_eq!(
+            format!("{:#}", setup()),
+            include_str!("../../test_data/RS-EXAMPLE-prefixes.txt")
+        );
+    }
+
+    #[test]
+    fn empty_set_is_empty_string() {
+        let s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.to_string(), "");
+        assert_eq!(format!("{:#}", s), "");
+    }
+
+    #[test]
+    fn output_round_trips() -> TestResult {
+        let s = setup();
+        assert_eq!(s.to_string().parse::<PrefixSet<Ipv4>>()?, s);
+        assert_eq!(format!("{:#}", s).parse::<PrefixSet<Ipv4>>()?, s);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+mod prefix_list {
+    use ip::Ipv6;
+
+    use crate::set::Vendor;
+
+    use super::*;
+
+    fn rs_example() -> PrefixSet<Ipv4> {
+        vec![
+            "192.0.2.0/27",
+            "192.0.2.32/27",
+            "192.0.2.64/27",
+            "192.0.2.96/27",
+            "192.0.2.128/26",
+            "192.0.2.128/27",
+            "192.0.2.160/27",
+            "192.0.2.192/27",
+            "192.0.2.224/27",
+        ]
+        .into_iter()
+        .map(|prefix| prefix.parse::<Prefix<Ipv4>>().unwrap())
+        .collect()
+    }
+
+    fn write<A: ip::Afi>(s: &PrefixSet<A>, vendor: Vendor) -> String {
+        let mut buf = Vec::new();
+        s.write_prefix_list(&mut buf, "RS-EXAMPLE", vendor).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn cisco_rs_example() {
+        assert_eq!(
+            write(&rs_example(), Vendor::Cisco),
+            "ip prefix-list RS-EXAMPLE permit 192.0.2.0/24 ge 27 le 27\n\
+             ip prefix-list RS-EXAMPLE permit 192.0.2.128/26\n"
+        );
+    }
+
+    // bgpq3 splits the same set into three entries, rather than the two
+    // produced by aggregating the tree.
+    #[test]
+    fn cisco_rs_example_is_equivalent_to_bgpq3() -> TestResult {
+        let bgpq3: PrefixSet<Ipv4> = vec![
+            "192.0.2.0/25,27,27",
+            "192.0.2.128/26,26,27",
+            "192.0.2.192/26,27,27",
+        ]
+        .into_iter()
+        .map(|range| range.parse::<PrefixRange<Ipv4>>())
+        .collect::<Result<_, _>>()?;
+        assert_eq!(bgpq3, rs_example());
+        assert_eq!(
+            write(&bgpq3, Vendor::Cisco),
+            write(&rs_example(), Vendor::Cisco)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cisco_ipv6() -> TestResult {
+        let s: PrefixSet<Ipv6> = "2001:db8::/32,32,40 2001:db8:f00::/40,44,44".parse()?;
+        assert_eq!(
+            write(&s, Vendor::Cisco),
+            "ipv6 prefix-list RS-EXAMPLE permit 2001:db8::/32 le 40\n\
+             ipv6 prefix-list RS-EXAMPLE permit 2001:db8:f00::/40 ge 44 le 44\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cisco_empty_denies_default() {
+        assert_eq!(
+            write(&PrefixSet::<Ipv4>::new(), Vendor::Cisco),
+            "ip prefix-list RS-EXAMPLE deny 0.0.0.0/0\n"
+        );
+        assert_eq!(
+            write(&PrefixSet::<Ipv6>::new(), Vendor::Cisco),
+            "ipv6 prefix-list RS-EXAMPLE deny ::/0\n"
+        );
+    }
+
+    #[test]
+    fn juniper_rs_example() -> TestResult {
+        let mut s = rs_example();
+        s.insert("198.51.100.0/24,24,25".parse::<PrefixRange<Ipv4>>()?);
+        assert_eq!(
+            write(&s, Vendor::Juniper),
+            "policy-options {\n\
+             replace:\n \
+             route-filter-list RS-EXAMPLE {\n    \
+             192.0.2.0/24 prefix-length-range /27-/27;\n    \
+             192.0.2.128/26 exact;\n    \
+             198.51.100.0/24 upto /25;\n \
+             }\n\
+             }\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bird_rs_example() {
+        assert_eq!(
+            write(&rs_example(), Vendor::Bird),
+            "RS-EXAMPLE = [\n    \
+             192.0.2.0/24{27,27},\n    \
+             192.0.2.128/26\n\
+             ];\n"
+        );
+    }
+}
+
+mod permit_entries {
+    use ip::concrete::PrefixLength;
+
+    use crate::set::PermitEntry;
+
+    use super::*;
+
+    fn length(l: u8) -> Option<PrefixLength<Ipv4>> {
+        Some(PrefixLength::from_primitive(l).unwrap())
+    }
+
+    fn entry(range: &str) -> PermitEntry<Ipv4> {
+        range.parse::<PrefixRange<Ipv4>>().unwrap().into()
+    }
+
+    #[test]
+    fn bgpq3_rs_example_entries() -> TestResult {
+        // the three entries emitted by bgpq3 for RS-EXAMPLE.
+        assert_eq!(
+            entry("192.0.2.0/25,27,27"),
+            PermitEntry {
+                prefix: "192.0.2.0/25".parse()?,
+                ge: length(27),
+                le: length(27),
+            }
+        );
+        assert_eq!(
+            entry("192.0.2.128/26,26,27"),
+            PermitEntry {
+                prefix: "192.0.2.128/26".parse()?,
+                ge: None,
+                le: length(27),
+            }
+        );
+        assert_eq!(
+            entry("192.0.2.192/26,27,27"),
+            PermitEntry {
+                prefix: "192.0.2.192/26".parse()?,
+                ge: length(27),
+                le: length(27),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rs_example_set() -> TestResult {
+        let s: PrefixSet<Ipv4> = include_str!("../../test_data/RS-EXAMPLE-prefixes.txt").parse()?;
+        let entries: Vec<_> = s.permit_entries().collect();
+        assert_eq!(
+            entries,
+            vec![
+                PermitEntry {
+                    prefix: "192.0.2.0/24".parse()?,
+                    ge: length(27),
+                    le: length(27),
+                },
+                PermitEntry {
+                    prefix: "192.0.2.128/26".parse()?,
+                    ge: None,
+                    le: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bare_prefix_has_no_bounds() -> TestResult {
+        let e = entry("2.0.0.0/8,8,8");
+        assert_eq!((e.ge, e.le), (None, None));
+        Ok(())
+    }
+
+    #[test]
+    fn ordered_by_base_prefix() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,16,16 1.0.0.0/8 10.0.0.0/16,17,24".parse()?;
+        let prefixes: Vec<_> = s.permit_entries().map(|e| e.prefix.to_string()).collect();
+        assert_eq!(prefixes, vec!["1.0.0.0/8", "10.0.0.0/8", "10.0.0.0/16"]);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_has_no_entries() {
+        assert_eq!(PrefixSet::<Ipv4>::new().permit_entries().count(), 0);
+    }
+}
+
+#[cfg(feature = "std")]
+mod jsonl_and_rpsl {
+    use ip::{
+        concrete::{self, PrefixLength},
+        Afi, Ipv6,
+    };
+
+    use super::*;
+
+    // read the output of `PrefixSet::write_jsonl()` back into a set.
+    fn read_jsonl<A: Afi>(buf: &[u8]) -> TestResult<PrefixSet<A>> {
+        let mut s = PrefixSet::new();
+        for line in std::str::from_utf8(buf).unwrap().lines() {
+            let value: serde_json::Value = serde_json::from_str(line)?;
+            let prefix: Prefix<A> = value["prefix"].as_str().unwrap().parse()?;
+            let length = |key: &str| match value.get(key) {
+                Some(length) => {
+                    PrefixLength::<A>::try_from(length.as_u64().unwrap() as usize).unwrap()
+                }
+                None => prefix.length(),
+            };
+            s.insert(concrete::PrefixRange::new(
+                prefix,
+                length("ge")..=length("le"),
+            )?);
+        }
+        Ok(s)
+    }
+
+    // read the output of `PrefixSet::write_rpsl_members()` back into a set.
+    fn read_rpsl_members<A: Afi>(buf: &[u8]) -> TestResult<PrefixSet<A>> {
+        std::str::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(|line| Ok(line.strip_prefix("mp-members: ").unwrap().parse()?))
+            .collect::<TestResult<Vec<Prefix<A>>>>()
+            .map(|prefixes| prefixes.into_iter().collect())
+    }
+
+    fn ipv4() -> PrefixSet<Ipv4> {
+        "10.0.0.0/8,8,10 10.128.0.0/9,12,12 192.0.2.0/24,24,26 198.51.100.0/24"
+            .parse()
+            .unwrap()
+    }
+
+    fn ipv6() -> PrefixSet<Ipv6> {
+        "2001:db8::/32,32,34 2001:db8:f00::/40,44,46 2001:db8:ff00::/48"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn jsonl_omits_base_lengths() -> TestResult {
+        let s: PrefixSet<Ipv4> =
+            "192.0.2.0/24 198.51.100.0/24,24,25 203.0.113.0/24,26,26".parse()?;
+        let mut buf = Vec::new();
+        s.write_jsonl(&mut buf)?;
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "{\"prefix\":\"192.0.2.0/24\"}\n\
+             {\"prefix\":\"198.51.100.0/24\",\"le\":25}\n\
+             {\"prefix\":\"203.0.113.0/24\",\"ge\":26,\"le\":26}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ipv4_jsonl_round_trips() -> TestResult {
+        let s = ipv4();
+        let mut buf = Vec::new();
+        s.write_jsonl(&mut buf)?;
+        assert_eq!(read_jsonl::<Ipv4>(&buf)?, s);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_jsonl_round_trips() -> TestResult {
+        let s = ipv6();
+        let mut buf = Vec::new();
+        s.write_jsonl(&mut buf)?;
+        assert_eq!(read_jsonl::<Ipv6>(&buf)?, s);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv4_rpsl_members_round_trip() -> TestResult {
+        let s = ipv4();
+        let mut buf = Vec::new();
+        s.write_rpsl_members(&mut buf)?;
+        assert_eq!(buf.iter().filter(|&&b| b == b'\n').count(), s.len());
+        assert_eq!(read_rpsl_members::<Ipv4>(&buf)?, s);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_rpsl_members_round_trip() -> TestResult {
+        let s = ipv6();
+        let mut buf = Vec::new();
+        s.write_rpsl_members(&mut buf)?;
+        assert_eq!(read_rpsl_members::<Ipv6>(&buf)?, s);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_writes_nothing() -> TestResult {
+        let s = PrefixSet::<Ipv4>::new();
+        let mut buf = Vec::new();
+        s.write_jsonl(&mut buf)?;
+        s.write_rpsl_members(&mut buf)?;
+        assert!(buf.is_empty());
+        Ok(())
+    }
+}
+
+mod host_prefix_ranges {
+    use ip::Ipv6;
+
+    use super::*;
+
+    #[test]
+    fn ipv4_host_prefix_ranges_are_exact() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.1/32 192.0.2.0/24 198.51.100.0/24,25,26".parse()?;
+        let mut ranges: Vec<_> = s.ranges().map(|r| r.to_string()).collect();
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            vec![
+                "192.0.2.0/24^24-24",
+                "192.0.2.1/32^32-32",
+                "198.51.100.0/24^25-26"
+            ]
+        );
+        assert_eq!(s.len(), 1 + 1 + 2 + 4);
+        assert_eq!(s.ranges().rev().count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_host_prefix_ranges_are_exact() -> TestResult {
+        let s: PrefixSet<Ipv6> =
+            "2001:db8::1/128 2001:db8::/32 2001:db8:f00::/40,127,128".parse()?;
+        let mut ranges: Vec<_> = s.ranges().map(|r| r.to_string()).collect();
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            vec![
+                "2001:db8::/32^32-32",
+                "2001:db8::1/128^128-128",
+                "2001:db8:f00::/40^127-128",
+            ]
+        );
+        assert!(s.contains("2001:db8::1/128".parse()?));
+        assert!(!s.contains("2001:db8::/127".parse()?));
+        Ok(())
+    }
+}
+
+mod maximal_depth_chain {
+    use ip::{
+        concrete::{self, Address, PrefixLength},
+        traits::Prefix as _,
+        Ipv6,
+    };
+
+    use super::*;
+
+    // a chain of nested prefixes, each one bit longer than the last, with
+    // alternating branch directions.
+    fn chain() -> Vec<concrete::Prefix<Ipv6>> {
+        (1..=128)
+            .map(|length| {
+                concrete::Prefix::new(
+                    Address::new(0x5555_5555_5555_5555_5555_5555_5555_5555),
+                    PrefixLength::from_primitive(length).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    // the prefix of the same length as `prefix` on the other side of its
+    // parent.
+    fn sibling(prefix: concrete::Prefix<Ipv6>) -> concrete::Prefix<Ipv6> {
+        let length = prefix.length();
+        let bit = 1u128 << (128 - u32::from(length.into_primitive()));
+        concrete::Prefix::new(
+            Address::new(prefix.network().into_primitive() ^ bit),
+            length,
+        )
+    }
+
+    fn check(s: &PrefixSet<Ipv6>) {
+        assert_eq!(s.depth(), 127);
+        assert_eq!(s.len(), 128);
+        chain().into_iter().for_each(|prefix| {
+            assert!(s.contains(prefix));
+            assert!(!s.contains(sibling(prefix)));
+        });
+    }
+
+    #[test]
+    fn inserted_shortest_first() {
+        let mut s = PrefixSet::new();
+        chain().into_iter().for_each(|prefix| {
+            s.insert(prefix);
+        });
+        check(&s);
+    }
+
+    #[test]
+    fn inserted_longest_first() {
+        let mut s = PrefixSet::new();
+        chain().into_iter().rev().for_each(|prefix| {
+            s.insert(prefix);
+        });
+        check(&s);
+    }
+
+    #[test]
+    fn merged_with_itself() {
+        let s: PrefixSet<_> = chain().into_iter().collect();
+        check(&(s.clone() | s));
+    }
+}
+
+#[cfg(feature = "rayon")]
+mod parallel_operations {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn ipv4_sets() -> Vec<PrefixSet<Ipv4>> {
+        vec![
+            PrefixSet::new(),
+            "192.0.2.0/24".parse().unwrap(),
+            "0.0.0.0/0,0,8".parse().unwrap(),
+            "10.0.0.0/8,8,10 10.128.0.0/9,12,12 192.0.2.0/24,24,26 198.51.100.0/24"
+                .parse()
+                .unwrap(),
+            "10.0.0.0/10,12,16 192.0.2.0/25,25,28 198.51.100.0/22,22,24 203.0.113.0/24"
+                .parse()
+                .unwrap(),
+        ]
+    }
+
+    fn ipv6_sets() -> Vec<PrefixSet<Ipv6>> {
+        vec![
+            PrefixSet::new(),
+            "2001:db8::/32,32,34 2001:db8:f00::/40,44,46"
+                .parse()
+                .unwrap(),
+            "2001:db8::/34,36,48 2001:db8:ff00::/48".parse().unwrap(),
+        ]
+    }
+
+    #[test]
+    fn ipv4_operations_match_serial() {
+        let sets = ipv4_sets();
+        sets.iter().for_each(|s| {
+            sets.iter().for_each(|t| {
+                assert_eq!(s.par_union(t), s.union(t));
+                assert_eq!(s.par_intersection(t), s.intersection(t));
+                assert_eq!(s.par_difference(t), s.difference(t));
+            })
+        });
+    }
+
+    #[test]
+    fn ipv6_operations_match_serial() {
+        let sets = ipv6_sets();
+        sets.iter().for_each(|s| {
+            sets.iter().for_each(|t| {
+                assert_eq!(s.par_union(t), s.union(t));
+                assert_eq!(s.par_intersection(t), s.intersection(t));
+                assert_eq!(s.par_difference(t), s.difference(t));
+            })
+        });
+    }
+}
+
+mod append {
+    use super::*;
+
+    fn sets() -> Vec<PrefixSet<Ipv4>> {
+        vec![
+            PrefixSet::new(),
+            "192.0.2.0/25".parse().unwrap(),
+            "192.0.2.128/25".parse().unwrap(),
+            "192.0.2.0/24,24,26 10.0.0.0/8,16,16".parse().unwrap(),
+            "10.0.0.0/12,12,16 198.51.100.0/24".parse().unwrap(),
+        ]
+    }
+
+    #[test]
+    fn append_is_union() {
+        let sets = sets();
+        sets.iter().for_each(|s| {
+            sets.iter().for_each(|t| {
+                let (mut a, mut b) = (s.clone(), t.clone());
+                a.append(&mut b);
+                assert_eq!(a, s.clone() | t.clone());
+                assert_eq!(a.ranges().count(), (s.clone() | t.clone()).ranges().count());
+            })
+        });
+    }
+
+    #[test]
+    fn other_is_empty_after_append() {
+        let sets = sets();
+        sets.iter().for_each(|s| {
+            sets.iter().for_each(|t| {
+                let (mut a, mut b) = (s.clone(), t.clone());
+                a.append(&mut b);
+                assert!(b.is_empty());
+                assert_eq!(b.ranges().count(), 0);
+            })
+        });
+    }
+
+    #[test]
+    fn append_clears_other_ranges_cache() -> TestResult {
+        let mut a: PrefixSet<Ipv4> = "192.0.2.0/25".parse()?;
+        let mut b: PrefixSet<Ipv4> = "192.0.2.128/25".parse()?;
+        b.materialize_ranges();
+        a.materialize_ranges();
+        a.append(&mut b);
+        assert!(b.materialize_ranges().is_empty());
+        assert_eq!(a.materialize_ranges().len(), 1);
+        Ok(())
+    }
+}
+
+mod insert_sorted {
+    use ip::{concrete, traits::Prefix as _, Afi, Ipv6};
+
+    use super::*;
+
+    fn ranges() -> Vec<PrefixRange<Ipv4>> {
+        [
+            "10.0.0.0/8,8,10",
+            "10.128.0.0/9,12,12",
+            "10.128.0.0/9,9,9",
+            "192.0.2.0/24,24,26",
+            "192.0.2.0/25,27,27",
+            "192.0.2.128/25,25,25",
+            "198.51.100.0/24,24,24",
+            "0.0.0.0/0,0,0",
+            "203.0.113.0/26,28,28",
+        ]
+        .into_iter()
+        .map(|range| range.parse().unwrap())
+        .collect()
+    }
+
+    fn sorted<T: Clone, A: Afi>(items: &[T], key: impl Fn(&T) -> concrete::Prefix<A>) -> Vec<T> {
+        let mut items = items.to_vec();
+        items.sort_by_key(|item| {
+            let prefix = key(item);
+            (prefix.network(), prefix.length())
+        });
+        items
+    }
+
+    #[test]
+    fn sorted_input_matches_insert_from() {
+        let ranges = ranges();
+        let expected = PrefixSet::new().insert_from(ranges.clone()).to_owned();
+        let s = PrefixSet::new()
+            .insert_sorted(sorted(&ranges, |range| range.prefix()))
+            .to_owned();
+        assert_eq!(s, expected);
+        assert_eq!(s.ranges().count(), expected.ranges().count());
+    }
+
+    #[test]
+    fn unsorted_input_matches_insert_from() {
+        let ranges = ranges();
+        let expected = PrefixSet::new().insert_from(ranges.clone()).to_owned();
+        let mut reversed = ranges.clone();
+        reversed.reverse();
+        assert_eq!(PrefixSet::new().insert_sorted(ranges).to_owned(), expected);
+        assert_eq!(
+            PrefixSet::new().insert_sorted(reversed).to_owned(),
+            expected
+        );
+    }
+
+    #[test]
+    fn inserts_into_existing_set() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "192.0.2.0/24 198.51.100.0/24,25,25".parse()?;
+        let mut expected = s.clone();
+        expected.insert_from(ranges());
+        s.insert_sorted(sorted(&ranges(), |range| range.prefix()));
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_input_is_no_op() -> TestResult {
+        let empty: Vec<Prefix<Ipv4>> = Vec::new();
+        assert!(PrefixSet::new().insert_sorted(empty.clone()).is_empty());
+        let mut s: PrefixSet<Ipv4> = "192.0.2.0/24".parse()?;
+        let before = s.clone();
+        s.insert_sorted(empty);
+        assert_eq!(s, before);
+        Ok(())
+    }
+
+    #[test]
+    fn sorted_ipv6_prefixes_match_insert_from() -> TestResult {
+        let prefixes: Vec<Prefix<Ipv6>> = [
+            "2001:db8::/32",
+            "2001:db8::/48",
+            "2001:db8:1::/48",
+            "2001:db8:f00::/40",
+            "2001:db8:f00::/44",
+            "2001:db8:ff00::/48",
+            "::/0",
+        ]
+        .into_iter()
+        .map(|prefix| prefix.parse())
+        .collect::<Result<_, _>>()?;
+        let expected = PrefixSet::new().insert_from(prefixes.clone()).to_owned();
+        let s = PrefixSet::new()
+            .insert_sorted(sorted(&prefixes, |prefix| *prefix))
+            .to_owned();
+        assert_eq!(s, expected);
+        Ok(())
+    }
+}
+
+mod hashing {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
+    use super::*;
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn prefixes_and_equivalent_ranges_hash_equally() -> TestResult {
+        let from_prefixes: PrefixSet<Ipv4> = "192.0.2.0/25 192.0.2.128/25 192.0.2.0/24".parse()?;
+        let from_ranges: PrefixSet<Ipv4> = "192.0.2.0/24,24,25".parse()?;
+        assert_eq!(from_prefixes, from_ranges);
+        assert_eq!(hash_of(&from_prefixes), hash_of(&from_ranges));
+        Ok(())
+    }
+
+    #[test]
+    fn dedupes_in_hash_set() -> TestResult {
+        let sets: HashSet<PrefixSet<Ipv4>> = [
+            "192.0.2.0/24,24,25",
+            "192.0.2.0/25 192.0.2.128/25 192.0.2.0/24",
+            "198.51.100.0/24",
+            "",
+        ]
+        .into_iter()
+        .map(|s| s.parse())
+        .collect::<Result<_, _>>()?;
+        assert_eq!(sets.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn differing_sets_hash_differently() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.0/24,24,25".parse()?;
+        let t: PrefixSet<Ipv4> = "192.0.2.0/24,24,26".parse()?;
+        assert_ne!(hash_of(&s), hash_of(&t));
+        Ok(())
+    }
+}
+
+mod statistics {
+    use ip::concrete::PrefixLength;
+
+    use super::*;
+
+    fn length(l: u8) -> PrefixLength<Ipv4> {
+        PrefixLength::from_primitive(l).unwrap()
+    }
+
+    #[test]
+    fn histogram_counts_expanded_prefixes() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.0.0.0/8,8,10".parse()?;
+        let histogram = s.length_histogram();
+        assert_eq!(histogram.get(&length(8)), Some(&1));
+        assert_eq!(histogram.get(&length(9)), Some(&2));
+        assert_eq!(histogram.get(&length(10)), Some(&4));
+        assert_eq!(histogram.len(), 3);
+        assert_eq!(histogram.values().sum::<usize>(), s.len());
+        Ok(())
+    }
+
+    #[test]
+    fn histogram_sums_across_nodes() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.0.0.0/8,8,10 2.0.0.0/8,9,9 1.2.0.0/16".parse()?;
+        let histogram = s.length_histogram();
+        assert_eq!(histogram.get(&length(8)), Some(&1));
+        assert_eq!(histogram.get(&length(9)), Some(&4));
+        assert_eq!(histogram.get(&length(10)), Some(&4));
+        assert_eq!(histogram.get(&length(16)), Some(&1));
+        assert_eq!(histogram.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_has_no_ranges() {
+        let s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.count_ranges(), 0);
+        assert!(s.length_histogram().is_empty());
+    }
+
+    #[test]
+    fn count_ranges_matches_iteration() -> TestResult {
+        let mut s: PrefixSet<Ipv4> =
+            "1.0.0.0/8,8,10 1.0.0.0/8,12,12 2.0.0.0/8,9,9 1.2.0.0/16".parse()?;
+        assert_eq!(s.count_ranges(), s.ranges().count());
+        s.materialize_ranges();
+        assert_eq!(s.count_ranges(), s.ranges().count());
+        Ok(())
+    }
+}
+
+mod tree_shape {
+    use super::*;
+
+    #[test]
+    fn empty_set_has_no_nodes() {
+        let s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.depth(), 0);
+        assert_eq!(s.node_count(), 0);
+    }
+
+    #[test]
+    fn single_prefix_is_single_node() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.0/24".parse()?;
+        assert_eq!(s.depth(), 0);
+        assert_eq!(s.node_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn divergent_prefixes_are_joined_by_glue() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.0/24 198.51.100.0/24".parse()?;
+        assert_eq!(s.depth(), 1);
+        assert_eq!(s.node_count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn aggregation_collapses_glue() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "192.0.2.0/25 192.0.2.128/25".parse()?;
+        assert_eq!(s.node_count(), 1);
+        s.remove("192.0.2.128/25".parse::<Prefix<Ipv4>>()?);
+        assert_eq!(s.node_count(), 1);
+        assert_eq!(s.depth(), 0);
+        Ok(())
+    }
+}
+
+mod heap_size {
+    use super::*;
+
+    #[test]
+    fn grows_with_insertion() -> TestResult {
+        let mut s = PrefixSet::<Ipv4>::new();
+        let mut size = s.heap_size();
+        assert_eq!(size, 0);
+        for i in 0..16 {
+            s.insert(format!("10.{}.0.0/16", i * 2).parse::<Prefix<Ipv4>>()?);
+            assert!(s.heap_size() > size);
+            size = s.heap_size();
+        }
+        assert_eq!(size, s.node_count() * std::mem::size_of::<Node<Ipv4>>());
+        Ok(())
+    }
+
+    #[test]
+    fn released_by_clear() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "10.0.0.0/16 10.2.0.0/16 10.4.0.0/16".parse()?;
+        s.materialize_ranges();
+        assert!(s.heap_size() > s.node_count() * std::mem::size_of::<Node<Ipv4>>());
+        s.clear();
+        assert_eq!(s.heap_size(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn shrink_to_fit_after_clear_is_minimal() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "10.0.0.0/16 10.2.0.0/16 10.4.0.0/16".parse()?;
+        s.materialize_ranges();
+        let size = s.heap_size();
+        s.shrink_to_fit();
+        assert!(s.heap_size() <= size);
+        assert_eq!(
+            s.heap_size(),
+            s.node_count() * std::mem::size_of::<Node<Ipv4>>()
+                + s.count_ranges() * std::mem::size_of::<ip::concrete::PrefixRange<Ipv4>>()
+        );
+        s.clear();
+        s.shrink_to_fit();
+        assert_eq!(s.heap_size(), 0);
+        Ok(())
+    }
+}
+
+mod overlaps {
+    use ip::Ipv6;
+
+    use super::*;
+
+    macro_rules! assert_overlap {
+        ( $afi:ty: $lhs:literal, $rhs:literal => $expect:expr ) => {
+            let lhs: PrefixSet<$afi> = $lhs.parse()?;
+            let rhs: PrefixSet<$afi> = $rhs.parse()?;
+            assert_eq!(lhs.overlaps(&rhs), $expect);
+            assert_eq!(rhs.overlaps(&lhs), $expect);
+        };
+    }
+
+    #[test]
+    fn covering_prefix_overlaps() -> TestResult {
+        assert_overlap!(Ipv4: "1.0.0.0/8", "1.0.0.0/24" => true);
+        Ok(())
+    }
+
+    #[test]
+    fn divergent_prefixes_do_not_overlap() -> TestResult {
+        assert_overlap!(Ipv4: "1.0.0.0/8", "2.0.0.0/8" => false);
+        Ok(())
+    }
+
+    #[test]
+    fn identical_prefixes_overlap() -> TestResult {
+        assert_overlap!(Ipv4: "1.0.0.0/8", "1.0.0.0/8" => true);
+        Ok(())
+    }
+
+    #[test]
+    fn range_overlaps_longer_prefix() -> TestResult {
+        assert_overlap!(Ipv4: "1.0.0.0/8,16,16", "1.2.3.0/24" => true);
+        Ok(())
+    }
+
+    #[test]
+    fn glue_does_not_overlap() -> TestResult {
+        // the root of the left-hand tree is a glue node at 1.0.0.0/14.
+        assert_overlap!(Ipv4: "1.0.0.0/16 1.2.0.0/16", "1.1.0.0/16 1.3.0.0/24" => false);
+        assert_overlap!(Ipv4: "1.0.0.0/16 1.2.0.0/16", "1.0.0.0/14" => true);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_sets_do_not_overlap() -> TestResult {
+        assert_overlap!(Ipv4: "", "0.0.0.0/0" => false);
+        assert_overlap!(Ipv4: "", "" => false);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_overlap() -> TestResult {
+        assert_overlap!(Ipv6: "2001:db8::/32", "2001:db8:f00::/48,48,64" => true);
+        assert_overlap!(Ipv6: "2001:db8::/33", "2001:db8:8000::/33" => false);
+        Ok(())
+    }
+}
+
+mod complement_within {
+    use ip::Ipv6;
+
+    use super::*;
+
+    #[test]
+    fn complement_of_half() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.0.0.0/9".parse()?;
+        let complement = s.complement_within("1.0.0.0/8,8,9".parse()?);
+        let expected: PrefixSet<Ipv4> = "1.0.0.0/8 1.128.0.0/9".parse()?;
+        assert_eq!(complement, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn ignores_prefixes_outside_space() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.0.0.0/9 2.0.0.0/8".parse()?;
+        let complement = s.complement_within("1.0.0.0/8,9,9".parse()?);
+        let expected: PrefixSet<Ipv4> = "1.128.0.0/9".parse()?;
+        assert_eq!(complement, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn complement_of_empty_set_is_space() -> TestResult {
+        let space: PrefixRange<Ipv6> = "2001:db8::/32,48,48".parse()?;
+        let complement = PrefixSet::new().complement_within(space.clone());
+        assert_eq!(complement, PrefixSet::new().insert(space).to_owned());
+        Ok(())
+    }
+
+    #[test]
+    fn complement_of_superset_is_empty() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.0.0.0/8,8,12".parse()?;
+        assert!(s.complement_within("1.0.0.0/8,9,10".parse()?).is_empty());
+        Ok(())
+    }
+}
+
+mod split_to_max_length {
+    use ip::{concrete::PrefixLength, Ipv6};
+
+    use super::*;
+
+    fn split<A: ip::Afi>(s: &str, max: PrefixLength<A>) -> TestResult<PrefixSet<A>> {
+        Ok(s.parse::<PrefixSet<A>>()?.split_to_max_length(max))
+    }
+
+    #[test]
+    fn range_is_truncated_at_max() -> TestResult {
+        let expected: PrefixSet<Ipv4> = "1.0.0.0/8 1.0.0.0/9 1.128.0.0/9".parse()?;
+        assert_eq!(
+            split::<Ipv4>("1.0.0.0/8,8,10", PrefixLength::from_primitive(9)?)?,
+            expected
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn range_entirely_above_max_becomes_subprefixes() -> TestResult {
+        let expected: PrefixSet<Ipv4> = "1.0.0.0/8,9,9".parse()?;
+        assert_eq!(
+            split::<Ipv4>("1.0.0.0/8,12,16", PrefixLength::from_primitive(9)?)?,
+            expected
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn long_prefix_becomes_supernet() -> TestResult {
+        let expected: PrefixSet<Ipv4> = "192.0.2.0/24 198.51.100.0/24".parse()?;
+        let s = split::<Ipv4>(
+            "192.0.2.0/24,25,32 198.51.100.7/32",
+            PrefixLength::from_primitive(24)?,
+        )?;
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn short_prefixes_are_unchanged() -> TestResult {
+        let s: PrefixSet<Ipv6> = "2001:db8::/32,32,48 2001:db8:f00::/40".parse()?;
+        assert_eq!(
+            split::<Ipv6>(
+                "2001:db8::/32,32,48 2001:db8:f00::/40",
+                PrefixLength::from_primitive(48)?
+            )?,
+            s
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn max_length_is_respected() -> TestResult {
+        let s = split::<Ipv6>(
+            "2001:db8::/32,32,64 2001:db8:f00::/56,56,128",
+            PrefixLength::from_primitive(48)?,
+        )?;
+        assert!(s
+            .prefixes()
+            .all(|p| p.length() <= PrefixLength::from_primitive(48).unwrap()));
+        assert!(s.contains("2001:db8:f00::/48".parse()?));
+        Ok(())
+    }
+}
+
+mod clamp_length {
+    use ip::concrete::PrefixLength;
+
+    use super::*;
+
+    fn length(l: u8) -> PrefixLength<Ipv4> {
+        PrefixLength::from_primitive(l).unwrap()
+    }
+
+    #[test]
+    fn folds_long_prefixes_to_supernets() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.0/30 192.0.2.252/30 198.51.100.0/24,30,30".parse()?;
+        let clamped = s.clamp_length(length(0), length(24));
+        assert!(clamped.contains("192.0.2.0/24".parse()?));
+        assert!(clamped.contains("198.51.100.0/24".parse()?));
+        assert!(!clamped.contains("192.0.2.0/30".parse()?));
+        assert_eq!(clamped.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn truncates_ranges_spanning_max() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,20,28".parse()?;
+        let expected: PrefixSet<Ipv4> = "10.0.0.0/8,20,24".parse()?;
+        assert_eq!(s.clamp_length(length(8), length(24)), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn promotes_short_prefixes_to_subprefixes() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,8,10 192.0.2.0/24".parse()?;
+        let expected: PrefixSet<Ipv4> = "10.0.0.0/8,12,12 192.0.2.0/24".parse()?;
+        assert_eq!(s.clamp_length(length(12), length(24)), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_split_to_max_length() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,16,24 192.0.2.128/25 198.51.100.0/24,24,28".parse()?;
+        assert_eq!(
+            s.clamp_length(length(0), length(20)),
+            s.split_to_max_length(length(20))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn preserves_lengths_within_bounds() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,16,24 192.0.2.0/24,25,26".parse()?;
+        assert_eq!(s.clamp_length(length(8), length(26)), s);
+        Ok(())
+    }
+
+    #[test]
+    fn exact_length() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8 10.1.2.0/23,23,25".parse()?;
+        let expected: PrefixSet<Ipv4> = "10.0.0.0/8,16,16".parse()?;
+        assert_eq!(s.clamp_length(length(16), length(16)), expected);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn min_longer_than_max() {
+        PrefixSet::<Ipv4>::new().clamp_length(length(24), length(16));
+    }
+}
+
+mod supernets_and_subnets {
+    use super::*;
+
+    fn set() -> TestResult<PrefixSet<Ipv4>> {
+        Ok("1.0.0.0/8 1.2.0.0/16 1.2.3.0/24".parse()?)
+    }
+
+    fn p(s: &str) -> Prefix<Ipv4> {
+        s.parse().unwrap()
+    }
+
+    fn sorted(iter: impl Iterator<Item = Prefix<Ipv4>>) -> Vec<Prefix<Ipv4>> {
+        let mut prefixes: Vec<_> = iter.collect();
+        prefixes.sort_by_key(|p| p.length());
+        prefixes
+    }
+
+    #[test]
+    fn supernets_of_leaf() -> TestResult {
+        let supernets: Vec<_> = set()?.supernets("1.2.3.0/24".parse()?).collect();
+        assert_eq!(supernets, vec![p("1.0.0.0/8"), p("1.2.0.0/16")]);
+        Ok(())
+    }
+
+    #[test]
+    fn subnets_of_root() -> TestResult {
+        let subnets = sorted(set()?.subnets("1.0.0.0/8".parse()?));
+        assert_eq!(subnets, vec![p("1.2.0.0/16"), p("1.2.3.0/24")]);
+        Ok(())
+    }
+
+    #[test]
+    fn query_need_not_be_a_member() -> TestResult {
+        let supernets: Vec<_> = set()?.supernets("1.2.3.128/25".parse()?).collect();
+        assert_eq!(supernets.len(), 3);
+        let subnets = sorted(set()?.subnets("1.2.0.0/15".parse()?));
+        assert_eq!(subnets, vec![p("1.2.0.0/16"), p("1.2.3.0/24")]);
+        Ok(())
+    }
+
+    #[test]
+    fn gluemap_bits_are_respected() -> TestResult {
+        let s: PrefixSet<Ipv4> = "1.0.0.0/8,10,10 1.2.0.0/16".parse()?;
+        let supernets: Vec<_> = s.supernets("1.2.0.0/16".parse()?).collect();
+        assert_eq!(supernets, vec![p("1.0.0.0/10")]);
+        assert_eq!(s.subnets("1.0.0.0/8".parse()?).count(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn subnets_of_default_are_lazy() -> TestResult {
+        let s: PrefixSet<ip::Ipv6> = "2001:db8::/32,32,128".parse()?;
+        let mut subnets = s.subnets("::/0".parse()?);
+        assert_eq!(subnets.next(), Some("2001:db8::/32".parse()?));
+        assert_eq!(subnets.next(), Some("2001:db8::/33".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn host_prefix_has_no_subnets() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.1/32 192.0.2.0/24".parse()?;
+        assert_eq!(s.subnets("192.0.2.1/32".parse()?).count(), 0);
+        assert_eq!(s.supernets("192.0.2.1/32".parse()?).count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set() -> TestResult {
+        let s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.supernets("1.2.3.0/24".parse()?).count(), 0);
+        assert_eq!(s.subnets("1.0.0.0/8".parse()?).count(), 0);
+        Ok(())
+    }
+}
+
+mod any_prefix_set {
+    use ip::{concrete::Afi, Any, Ipv6};
+
+    use crate::error::Error;
+
+    use super::super::AnyPrefixSet;
+    use super::*;
+
+    fn any(s: &str) -> ip::Prefix<Any> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn partitions_mixed_input() -> TestResult {
+        let (ipv4, ipv6) = AnyPrefixSet::partition([
+            any("192.0.2.0/24"),
+            any("2001:db8::/32"),
+            any("198.51.100.0/24"),
+        ]);
+        assert_eq!(ipv4, "192.0.2.0/24 198.51.100.0/24".parse()?);
+        assert_eq!(ipv6, "2001:db8::/32".parse()?);
+        let mut ranges: Vec<_> = AnyPrefixSet::from(ipv4)
+            .ranges()
+            .chain(AnyPrefixSet::from(ipv6).ranges())
+            .map(|range| range.to_string())
+            .collect();
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            vec![
+                "192.0.2.0/24^24-24",
+                "198.51.100.0/24^24-24",
+                "2001:db8::/32^32-32"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn insert_and_contains_dispatch_on_family() -> TestResult {
+        let mut set = AnyPrefixSet::new(Afi::Ipv4);
+        set.insert(any("192.0.2.0/24"))?;
+        assert!(set.contains(any("192.0.2.0/24"))?);
+        assert!(!set.contains(any("198.51.100.0/24"))?);
+        assert_eq!(set.afi(), Afi::Ipv4);
+        Ok(())
+    }
+
+    #[test]
+    fn family_mismatch_is_an_error() -> TestResult {
+        let mut set = AnyPrefixSet::from("2001:db8::/32".parse::<PrefixSet<Ipv6>>()?);
+        let before = set.clone();
+        assert!(matches!(
+            set.insert(any("192.0.2.0/24")),
+            Err(Error::AfiMismatch {
+                expected: Afi::Ipv6,
+                found: Afi::Ipv4
+            })
+        ));
+        assert!(matches!(
+            set.contains(any("192.0.2.0/24")),
+            Err(Error::AfiMismatch { .. })
+        ));
+        assert_eq!(set, before);
+        Ok(())
+    }
+}
+
+mod drain {
+    use ip::Ipv6;
+
+    use super::*;
+
+    #[test]
+    fn drained_prefixes_match_prefixes() -> TestResult {
+        let mut s: PrefixSet<Ipv4> = "10.0.0.0/8,9,10 192.0.2.0/24 192.0.2.128/25,26,27".parse()?;
+        let expected: Vec<_> = s.prefixes().collect();
+        let drained: Vec<_> = s.drain().collect();
+        assert_eq!(drained, expected);
+        assert!(s.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn drained_set_is_reusable() -> TestResult {
+        let mut s: PrefixSet<Ipv6> = "2001:db8::/32,33,34".parse()?;
+        s.materialize_ranges();
+        assert_eq!(s.drain().count(), 6);
+        assert!(s.materialize_ranges().is_empty());
+        s.insert("2001:db8::/48".parse::<Prefix<Ipv6>>()?);
+        assert_eq!(s.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn draining_empty_set() {
+        let mut s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.drain().next(), None);
+    }
+}
+
+mod reference_operators {
+    use super::*;
+
+    fn operands() -> TestResult<(PrefixSet<Ipv4>, PrefixSet<Ipv4>)> {
+        Ok((
+            "10.0.0.0/8,8,10 192.0.2.0/24".parse()?,
+            "10.0.0.0/8,9,12 198.51.100.0/24".parse()?,
+        ))
+    }
+
+    #[test]
+    fn match_owned_operators() -> TestResult {
+        let (a, b) = operands()?;
+        assert_eq!(&a & &b, a.clone() & b.clone());
+        assert_eq!(&a | &b, a.clone() | b.clone());
+        assert_eq!(&a ^ &b, a.clone() ^ b.clone());
+        assert_eq!(&a - &b, a.clone() - b.clone());
+        Ok(())
+    }
+
+    #[test]
+    fn operands_are_not_consumed() -> TestResult {
+        let (a, b) = operands()?;
+        let (c, d) = operands()?;
+        let _ = &(&a & &b) | &(&a - &b);
+        let _ = &a ^ &b;
+        assert_eq!((a, b), (c, d));
+        Ok(())
+    }
+}
+
+mod assignment_operators {
+    use super::*;
+
+    fn operands() -> TestResult<(PrefixSet<Ipv4>, PrefixSet<Ipv4>)> {
+        Ok((
+            "10.0.0.0/8,8,10 192.0.2.0/24".parse()?,
+            "10.0.0.0/8,9,12 198.51.100.0/24".parse()?,
+        ))
+    }
+
+    #[test]
+    fn bitand_assign_matches_bitand() -> TestResult {
+        let (mut s, t) = operands()?;
+        let expected = s.clone() & t.clone();
+        s &= t;
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn bitor_assign_matches_bitor() -> TestResult {
+        let (mut s, t) = operands()?;
+        let expected = s.clone() | t.clone();
+        s |= t;
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn bitxor_assign_matches_bitxor() -> TestResult {
+        let (mut s, t) = operands()?;
+        let expected = s.clone() ^ t.clone();
+        s ^= t;
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn sub_assign_matches_sub() -> TestResult {
+        let (mut s, t) = operands()?;
+        let expected = s.clone() - t.clone();
+        s -= t;
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_operands() -> TestResult {
+        let (s, _) = operands()?;
+        let mut empty = PrefixSet::new();
+        empty |= s.clone();
+        assert_eq!(empty, s);
+        empty -= s.clone();
+        assert!(empty.is_empty());
+        let mut t = s.clone();
+        t &= PrefixSet::new();
+        assert!(t.is_empty());
+        let mut t = s.clone();
+        t ^= PrefixSet::new();
+        assert_eq!(t, s);
+        Ok(())
+    }
+
+    #[test]
+    fn accumulate() -> TestResult {
+        let mut acc = PrefixSet::<Ipv4>::new();
+        for s in ["10.0.0.0/8", "192.0.2.0/24", "10.0.0.0/8,9,9"] {
+            acc |= s.parse()?;
+        }
+        assert_eq!(acc, "10.0.0.0/8,8,9 192.0.2.0/24".parse()?);
+        Ok(())
+    }
+
+    #[test]
+    fn invalidates_cached_ranges() -> TestResult {
+        let (mut s, t) = operands()?;
+        let expected: Vec<_> = (s.clone() - t.clone()).ranges().collect();
+        let _ = s.materialize_ranges();
+        s -= t;
+        assert_eq!(s.materialize_ranges(), expected);
+        Ok(())
+    }
+}
+
+mod union_and_intersection_all {
+    use num::Zero;
+
+    use super::*;
+
+    fn sets() -> TestResult<Vec<PrefixSet<Ipv4>>> {
+        Ok(vec![
+            "10.0.0.0/8,8,16 192.0.2.0/25".parse()?,
+            "10.0.0.0/8,12,20 192.0.2.128/25 198.51.100.0/24".parse()?,
+            "10.0.0.0/9,14,24 192.0.2.0/24,25,25".parse()?,
+        ])
+    }
+
+    #[test]
+    fn union_all_matches_folding() -> TestResult {
+        let folded = sets()?
+            .into_iter()
+            .fold(PrefixSet::new(), |acc, set| acc | set);
+        let union = PrefixSet::union_all(sets()?);
+        assert_eq!(union, folded);
+        assert!(union.is_aggregated());
+        Ok(())
+    }
+
+    #[test]
+    fn intersection_all_matches_folding() -> TestResult {
+        let folded = sets()?.into_iter().reduce(|acc, set| acc & set).unwrap();
+        let intersection = PrefixSet::intersection_all(sets()?);
+        assert_eq!(intersection, folded);
+        assert!(!intersection.is_empty());
+        assert!(intersection.is_aggregated());
+        Ok(())
+    }
+
+    #[test]
+    fn union_of_no_sets_is_empty() {
+        assert_eq!(
+            PrefixSet::<Ipv4>::union_all(Vec::new()),
+            PrefixSet::<Ipv4>::zero()
+        );
+    }
+
+    #[test]
+    fn intersection_of_no_sets_is_universe() {
+        assert_eq!(
+            PrefixSet::<Ipv4>::intersection_all(Vec::new()),
+            PrefixSet::<Ipv4>::universe()
+        );
+    }
+
+    #[test]
+    fn single_set_is_unchanged() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8,8,16 192.0.2.0/25".parse()?;
+        assert_eq!(PrefixSet::union_all([set.clone()]), set);
+        assert_eq!(PrefixSet::intersection_all([set.clone()]), set);
+        Ok(())
+    }
+
+    #[test]
+    fn intersection_with_an_empty_set_is_empty() -> TestResult {
+        let mut sets = sets()?;
+        sets.insert(1, PrefixSet::new());
+        assert!(PrefixSet::intersection_all(sets).is_empty());
+        Ok(())
+    }
+}
+
+mod sum_and_product {
+    use num::{One, Zero};
+
+    use super::*;
+
+    fn set(s: &str) -> PrefixSet<Ipv4> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn sum_of_disjoint_singletons_is_union() {
+        let sum: PrefixSet<Ipv4> = ["192.0.2.0/24", "198.51.100.0/24", "203.0.113.0/24"]
+            .into_iter()
+            .map(set)
+            .sum();
+        assert_eq!(sum, set("192.0.2.0/24 198.51.100.0/24 203.0.113.0/24"));
+    }
+
+    #[test]
+    fn product_of_overlapping_sets_is_intersection() {
+        let (s, t) = (set("10.0.0.0/8,8,16"), set("10.0.0.0/8,12,24"));
+        let product: PrefixSet<Ipv4> = [s.clone(), t.clone()].into_iter().product();
+        assert_eq!(product, s & t);
+        assert_eq!(product, set("10.0.0.0/8,12,16"));
+    }
+
+    #[test]
+    fn empty_sum_is_zero() {
+        let sum: PrefixSet<Ipv4> = core::iter::empty().sum();
+        assert_eq!(sum, PrefixSet::zero());
+    }
+
+    #[test]
+    fn empty_product_is_one() {
+        let product: PrefixSet<Ipv4> = core::iter::empty().product();
+        assert_eq!(product, PrefixSet::one());
+    }
+
+    #[test]
+    fn matches_folding_with_operators() {
+        let sets = || {
+            [
+                "10.0.0.0/8,8,16",
+                "10.0.0.0/9,12,20",
+                "10.0.0.0/8,14,14 11.0.0.0/8",
+            ]
+            .into_iter()
+            .map(set)
+        };
+        assert_eq!(
+            sets().sum::<PrefixSet<Ipv4>>(),
+            sets().fold(PrefixSet::zero(), |acc, s| acc + s)
+        );
+        assert_eq!(
+            sets().product::<PrefixSet<Ipv4>>(),
+            sets().fold(PrefixSet::one(), |acc, s| acc * s)
+        );
+    }
+}
+
+mod in_place_updates {
+    use super::*;
+
+    fn set(s: &str) -> PrefixSet<Ipv4> {
+        s.parse().unwrap()
+    }
+
+    fn pairs() -> Vec<(PrefixSet<Ipv4>, PrefixSet<Ipv4>)> {
+        vec![
+            (set("192.0.2.0/24,24,26"), set("192.0.2.0/24,26,28")),
+            (set("10.0.0.0/8,8,16"), set("10.1.0.0/16 10.2.0.0/15,16,24")),
+            (set("10.0.0.0/8"), set("11.0.0.0/8")),
+            (set("10.0.0.0/8"), PrefixSet::new()),
+            (PrefixSet::new(), set("10.0.0.0/8")),
+        ]
+    }
+
+    #[test]
+    fn difference_update_matches_operator() {
+        for (s, t) in pairs() {
+            let mut updated = s.clone();
+            updated.difference_update(&t);
+            assert_eq!(updated, s.clone() - t.clone());
+            assert!(updated.is_aggregated());
+        }
+    }
+
+    #[test]
+    fn intersect_update_matches_operator() {
+        for (s, t) in pairs() {
+            let mut updated = s.clone();
+            updated.intersect_update(&t);
+            assert_eq!(updated, s.clone() & t.clone());
+        }
+    }
+
+    #[test]
+    fn other_is_unchanged() {
+        for (s, t) in pairs() {
+            let before = t.clone();
+            s.clone().difference_update(&t).intersect_update(&t);
+            assert_eq!(t, before);
+        }
+    }
+
+    #[test]
+    fn repeated_refinement() {
+        let mut s = set("10.0.0.0/8,8,24");
+        s.intersect_update(&set("10.0.0.0/8,16,24"))
+            .difference_update(&set("10.0.0.0/9,16,24"))
+            .difference_update(&set("10.128.0.0/9,17,24"));
+        assert_eq!(s, set("10.128.0.0/9,16,16"));
+    }
+}
+
+mod universe {
+    use ip::Ipv6;
+    use num::One;
+
+    use super::*;
+
+    #[test]
+    fn contains_prefixes_of_every_length() -> TestResult {
+        let universe = PrefixSet::<Ipv4>::universe();
+        for length in 0..=32 {
+            let prefix = format!("203.0.113.255/{}", length).parse::<Prefix<Ipv4>>()?;
+            assert!(universe.contains(prefix), "{} not contained", prefix);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn contains_ipv6_sample() -> TestResult {
+        let universe = PrefixSet::<Ipv6>::universe();
+        for prefix in ["::/0", "2001:db8::/32", "fe80::/64", "::1/128", "ff00::/8"] {
+            assert!(universe.contains(prefix.parse()?));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn is_one() {
+        assert_eq!(PrefixSet::<Ipv4>::universe(), PrefixSet::one());
+        assert_eq!(PrefixSet::<Ipv4>::universe().count_ranges(), 1);
+    }
+}
+
+mod batch_contains {
+    use super::*;
+
+    fn allow_set() -> TestResult<PrefixSet<Ipv4>> {
+        Ok("10.0.0.0/8,16,24 192.0.2.0/24".parse()?)
+    }
+
+    fn prefixes(items: &[&str]) -> Vec<Prefix<Ipv4>> {
+        items.iter().map(|item| item.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn fully_covered_query() -> TestResult {
+        let queries = prefixes(&["10.1.0.0/16", "10.1.2.0/24", "192.0.2.0/24"]);
+        assert!(allow_set()?.contains_all(queries.clone()));
+        assert!(allow_set()?.contains_any(queries));
+        Ok(())
+    }
+
+    #[test]
+    fn partially_covered_query() -> TestResult {
+        let queries = prefixes(&["10.1.0.0/16", "10.0.0.0/8", "192.0.2.0/25"]);
+        assert!(!allow_set()?.contains_all(queries.clone()));
+        assert!(allow_set()?.contains_any(queries));
+        Ok(())
+    }
+
+    #[test]
+    fn uncovered_query() -> TestResult {
+        let queries = prefixes(&["10.0.0.0/8", "198.51.100.0/24"]);
+        assert!(!allow_set()?.contains_all(queries.clone()));
+        assert!(!allow_set()?.contains_any(queries));
+        Ok(())
+    }
+
+    #[test]
+    fn short_circuits() -> TestResult {
+        let s = allow_set()?;
+        let mut iter = prefixes(&["198.51.100.0/24", "10.1.0.0/16"]).into_iter();
+        assert!(!s.contains_all(&mut iter));
+        assert_eq!(iter.len(), 1);
+        let mut iter = prefixes(&["10.1.0.0/16", "198.51.100.0/24"]).into_iter();
+        assert!(s.contains_any(&mut iter));
+        assert_eq!(iter.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_query() -> TestResult {
+        assert!(allow_set()?.contains_all(Vec::new()));
+        assert!(!allow_set()?.contains_any(Vec::new()));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ipnet")]
+mod ipnet_interop {
+    use ::ipnet::{IpNet, Ipv4Net, Ipv6Net};
+    use ip::{concrete::Afi, Ipv6};
+
+    use crate::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn ipv4_round_trip() -> TestResult {
+        let mut nets: Vec<Ipv4Net> = ["192.0.2.0/24", "10.0.0.0/8", "10.1.0.0/16", "0.0.0.0/0"]
+            .into_iter()
+            .map(|net| net.parse().unwrap())
+            .collect();
+        let s: PrefixSet<Ipv4> = nets.iter().copied().collect();
+        assert_eq!(s, "192.0.2.0/24 10.0.0.0/8 10.1.0.0/16 0.0.0.0/0".parse()?);
+        let mut round_tripped: Vec<Ipv4Net> = s
+            .ipnets()
+            .map(|net| match net {
+                IpNet::V4(net) => net,
+                IpNet::V6(_) => unreachable!(),
+            })
+            .collect();
+        nets.sort();
+        round_tripped.sort();
+        assert_eq!(round_tripped, nets);
+        Ok(())
+    }
+
+    #[test]
+    fn try_from_slice() -> TestResult {
+        let nets: Vec<IpNet> = vec![
+            "2001:db8::/32".parse().unwrap(),
+            "2001:db8::/48".parse().unwrap(),
+        ];
+        let s = PrefixSet::<Ipv6>::try_from(nets.as_slice())?;
+        assert_eq!(s, "2001:db8::/32 2001:db8::/48".parse()?);
+        assert_eq!(s.ipnets().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn try_from_mixed_slice_fails() {
+        let nets: Vec<IpNet> = vec![
+            "192.0.2.0/24".parse().unwrap(),
+            "2001:db8::/32".parse().unwrap(),
+        ];
+        assert!(matches!(
+            PrefixSet::<Ipv4>::try_from(nets.as_slice()),
+            Err(Error::AfiMismatch {
+                expected: Afi::Ipv4,
+                found: Afi::Ipv6
+            })
+        ));
+        assert!(PrefixSet::<Ipv6>::try_from(nets.as_slice()).is_err());
+    }
+
+    #[test]
+    fn insert_ipnet() -> TestResult {
+        let net: Ipv6Net = "2001:db8::/32".parse().unwrap();
+        let s = PrefixSet::new().insert(net).to_owned();
+        assert!(s.contains("2001:db8::/32".parse()?));
+        Ok(())
+    }
+}
+
+mod fused_iterators {
+    use super::*;
+
+    fn set() -> TestResult<PrefixSet<Ipv4>> {
+        Ok("10.0.0.0/8,8,9 192.0.2.0/24 198.51.100.0/24,25,25".parse()?)
+    }
+
+    #[test]
+    fn ranges_stay_exhausted() -> TestResult {
+        let s = set()?;
+        let mut ranges = s.ranges();
+        assert_eq!(ranges.by_ref().count(), 3);
+        for _ in 0..3 {
+            assert_eq!(ranges.next(), None);
+            assert_eq!(ranges.next_back(), None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn prefixes_stay_exhausted() -> TestResult {
+        let s = set()?;
+        let mut prefixes = s.prefixes();
+        assert_eq!(prefixes.by_ref().count(), 6);
+        for _ in 0..3 {
+            assert_eq!(prefixes.next(), None);
+            assert_eq!(prefixes.next_back(), None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn meeting_in_the_middle_stays_exhausted() -> TestResult {
+        let s = set()?;
+        let mut prefixes = s.prefixes();
+        for _ in 0..3 {
+            assert!(prefixes.next().is_some());
+            assert!(prefixes.next_back().is_some());
+        }
+        for _ in 0..3 {
+            assert_eq!(prefixes.next_back(), None);
+            assert_eq!(prefixes.next(), None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_iterators_are_exhausted() {
+        let s = PrefixSet::<Ipv4>::new();
+        let (mut ranges, mut prefixes) = (s.ranges(), s.prefixes());
+        for _ in 0..3 {
+            assert_eq!(ranges.next(), None);
+            assert_eq!(prefixes.next(), None);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod from_reader {
+    use std::io::BufReader;
+
+    use crate::error::Error;
+
+    use super::*;
+
+    const INPUT: &str = "# AS-EXAMPLE
+192.0.2.0/24
+
+198.51.100.0/24,25,25   # customer A
+   203.0.113.0/24
+# end
+";
+
+    #[test]
+    fn skips_comments_and_blank_lines() -> TestResult {
+        let s = PrefixSet::<Ipv4>::from_reader(BufReader::new(INPUT.as_bytes()))?;
+        let expected: PrefixSet<Ipv4> =
+            "192.0.2.0/24 198.51.100.0/24,25,25 203.0.113.0/24".parse()?;
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn reads_test_data() -> TestResult {
+        let path = "./test_data/RS-EXAMPLE-prefixes.txt";
+        let s = PrefixSet::<Ipv4>::from_reader(BufReader::new(std::fs::File::open(path)?))?;
+        let expected: PrefixSet<Ipv4> = std::fs::read_to_string(path)?.parse()?;
+        assert_eq!(s, expected);
+        assert_eq!(s.len(), 9);
+        Ok(())
+    }
+
+    #[test]
+    fn reports_line_number() {
+        let input = "192.0.2.0/24\n\n# comment\n192.0.2.0/33\n";
+        match PrefixSet::<Ipv4>::from_reader(input.as_bytes()) {
+            Err(Error::Line { number, source }) => {
+                assert_eq!(number, 4);
+                assert!(matches!(*source, Error::EntryParse { .. }));
+            }
+            res => panic!("expected line error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn reports_io_errors() {
+        let input: &[u8] = b"192.0.2.0/24\n\xff\xfe\n";
+        assert!(matches!(
+            PrefixSet::<Ipv4>::from_reader(input),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn empty_input() -> TestResult {
+        assert!(PrefixSet::<Ipv4>::from_reader("".as_bytes())?.is_empty());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+mod from_file {
+    use std::{env, fs, path::PathBuf, process};
+
+    use crate::error::Error;
+
+    use super::*;
+
+    // a scratch file path, unique to this process and test.
+    fn scratch(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("prefixset-{}-{}.txt", process::id(), name))
+    }
+
+    #[test]
+    fn reads_mixed_prefixes_and_ranges() -> TestResult {
+        let path = scratch("mixed");
+        fs::write(&path, "192.0.2.0/24\n198.51.100.0/24,25,25\n# done\n")?;
+        let res = PrefixSet::<Ipv4>::from_file(&path);
+        fs::remove_file(&path)?;
+        let expected: PrefixSet<Ipv4> = "192.0.2.0/24 198.51.100.0/24,25,25".parse()?;
+        assert_eq!(res?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn reports_parse_errors() -> TestResult {
+        let path = scratch("invalid");
+        fs::write(&path, "192.0.2.0/24\nnot-a-prefix\n")?;
+        let res = PrefixSet::<Ipv4>::from_file(&path);
+        fs::remove_file(&path)?;
+        assert!(matches!(res, Err(Error::Line { number: 2, .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn reports_missing_file() {
+        let path = scratch("missing");
+        assert!(matches!(
+            PrefixSet::<Ipv4>::from_file(path),
+            Err(Error::Io(_))
+        ));
+    }
+}
+
+mod try_from_strs {
+    use crate::error::{Error, RangeField};
+
+    use super::*;
+
+    #[test]
+    fn parses_prefixes_and_ranges() -> TestResult {
+        let set = PrefixSet::<Ipv4>::try_from_strs([
+            "192.0.2.0/24",
+            "198.51.100.0/24,25,25",
+            "203.0.113.0/24",
+        ])?;
+        let expected: PrefixSet<Ipv4> =
+            "192.0.2.0/24 198.51.100.0/24,25,25 203.0.113.0/24".parse()?;
+        assert_eq!(set, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_input_is_empty_set() -> TestResult {
+        assert!(PrefixSet::<Ipv4>::try_from_strs([])?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn returns_malformed_prefix_error() {
+        let res = PrefixSet::<Ipv4>::try_from_strs(["192.0.2.0/24", "not-a-prefix"]);
+        assert!(matches!(res, Err(Error::EntryParse { token, .. }) if token == "not-a-prefix"));
+    }
+
+    #[test]
+    fn returns_malformed_range_error() {
+        let res = PrefixSet::<Ipv4>::try_from_strs(["192.0.2.0/24,24,x", "also-bad"]);
+        assert!(matches!(
+            res,
+            Err(Error::RangeParse {
+                field: RangeField::Upper,
+                ..
+            })
+        ));
+    }
+}
+
+mod nth_prefix {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn assert_matches_iteration<A: ip::Afi>(s: &PrefixSet<A>) {
+        let prefixes: Vec<_> = s.prefixes().collect();
+        for (k, prefix) in prefixes.iter().enumerate() {
+            assert_eq!(s.nth_prefix(k), Some(*prefix), "mismatch at index {}", k);
+        }
+        assert_eq!(s.nth_prefix(prefixes.len()), None);
+        assert_eq!(s.nth_prefix(usize::MAX), None);
+    }
+
+    #[test]
+    fn ipv4_matches_iteration() -> TestResult {
+        let s: PrefixSet<Ipv4> =
+            "10.0.0.0/8,8,10 10.0.0.0/8,12,13 192.0.2.0/24 192.0.2.128/25,27,28 198.51.100.7/32"
+                .parse()?;
+        assert_matches_iteration(&s);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_matches_iteration() -> TestResult {
+        let s: PrefixSet<Ipv6> =
+            "2001:db8::/32,33,36 2001:db8:f00::/40 2001:db8:f00::/124,126,128 ::/0".parse()?;
+        assert_matches_iteration(&s);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_has_no_prefixes() {
+        assert_eq!(PrefixSet::<Ipv4>::new().nth_prefix(0), None);
+    }
+
+    #[test]
+    fn large_range_is_indexed_directly() -> TestResult {
+        let s: PrefixSet<Ipv6> = "2001:db8::/32,64,64".parse()?;
+        assert_eq!(s.nth_prefix(0), Some("2001:db8::/64".parse()?));
+        assert_eq!(
+            s.nth_prefix(0xffff_ffff),
+            Some("2001:db8:ffff:ffff::/64".parse()?)
+        );
+        assert_eq!(s.nth_prefix(0x1_0000_0000), None);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand")]
+mod random_sampling {
+    use std::collections::{HashMap, HashSet};
+
+    use ip::Ipv6;
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::*;
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::seed_from_u64(0x5eed)
+    }
+
+    #[test]
+    fn empty_set_samples_nothing() {
+        let set = PrefixSet::<Ipv4>::new();
+        assert_eq!(set.sample(&mut rng()), None);
+        assert!(set.sample_n(&mut rng(), 4).is_empty());
+    }
+
+    #[test]
+    fn sample_is_uniform() -> TestResult {
+        // 1 + 2 + 4 + 1 = 8 prefixes, spread unevenly across the ranges.
+        let set: PrefixSet<Ipv4> = vec![
+            "192.0.2.0/24,24,26".parse::<PrefixRange<Ipv4>>()?,
+            "198.51.100.0/24,24,24".parse()?,
+        ]
+        .into_iter()
+        .collect();
+        let total = set.count_prefixes() as usize;
+        assert_eq!(total, 8);
+        let draws = 80_000;
+        let mut rng = rng();
+        let mut counts = HashMap::new();
+        for _ in 0..draws {
+            let prefix = set.sample(&mut rng).unwrap();
+            assert!(set.contains(prefix));
+            *counts.entry(prefix).or_insert(0usize) += 1;
+        }
+        assert_eq!(counts.len(), total);
+        // chi-squared goodness of fit, with 7 degrees of freedom. The
+        // critical value at p = 0.001 is 24.32.
+        let expected = (draws / total) as f64;
+        let chi_squared: f64 = counts
+            .values()
+            .map(|&observed| (observed as f64 - expected).powi(2) / expected)
+            .sum();
+        assert!(chi_squared < 24.32, "chi-squared = {chi_squared}");
+        Ok(())
+    }
+
+    #[test]
+    fn sample_n_is_without_replacement() -> TestResult {
+        let set: PrefixSet<Ipv6> = vec!["2001:db8::/32,32,40".parse::<PrefixRange<Ipv6>>()?]
+            .into_iter()
+            .collect();
+        let mut rng = rng();
+        let sample = set.sample_n(&mut rng, 100);
+        assert_eq!(sample.len(), 100);
+        assert_eq!(sample.iter().collect::<HashSet<_>>().len(), 100);
+        assert!(sample.iter().all(|prefix| set.contains(*prefix)));
+        Ok(())
+    }
+
+    #[test]
+    fn sample_n_saturates() -> TestResult {
+        let set: PrefixSet<Ipv4> = vec!["192.0.2.0/24,24,25".parse::<PrefixRange<Ipv4>>()?]
+            .into_iter()
+            .collect();
+        let sample = set.sample_n(&mut rng(), 10);
+        assert_eq!(sample, set.prefixes().collect::<Vec<_>>());
+        Ok(())
+    }
+}
+
+mod unaggregated_equality {
+    use super::*;
+
+    fn node(s: &str) -> Box<Node<Ipv4>> {
+        s.parse::<Node<Ipv4>>().unwrap().boxed()
+    }
+
+    fn with_redundant_glue() -> PrefixSet<Ipv4> {
+        // 10.0.0.0/8 and 11.0.0.0/8 are joined under a glue node at
+        // 10.0.0.0/7. removing 11.0.0.0/8 without aggregating leaves the
+        // glue in place, along with an empty node for 11.0.0.0/8.
+        let root = node("10.0.0.0/8").add(node("11.0.0.0/8"));
+        let root = root.remove(&node("11.0.0.0/8"));
+        let mut set = PrefixSet::new_with_root(Some(root));
+        set.prefix_count = set.walk_prefix_count();
+        set
+    }
+
+    #[test]
+    fn tree_has_redundant_glue() {
+        let set = with_redundant_glue();
+        assert!(set.node_count() > 1);
+    }
+
+    #[test]
+    fn equals_aggregated_set() -> TestResult {
+        let aggregated: PrefixSet<Ipv4> = "10.0.0.0/8".parse()?;
+        let unaggregated = with_redundant_glue();
+        assert_eq!(unaggregated, aggregated);
+        assert_eq!(aggregated, unaggregated);
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_equal_superset() -> TestResult {
+        let superset: PrefixSet<Ipv4> = "10.0.0.0/8 10.0.0.0/16".parse()?;
+        assert_ne!(with_redundant_glue(), superset);
+        assert_ne!(superset, with_redundant_glue());
+        Ok(())
+    }
+}
+
+mod prefixes_with_length {
+    use ip::{concrete::PrefixLength, traits::Prefix as _};
+
+    use super::*;
+
+    fn p(s: &str) -> Prefix<Ipv4> {
+        s.parse().unwrap()
+    }
+
+    fn setup() -> TestResult<PrefixSet<Ipv4>> {
+        Ok("1.0.0.0/8,8,10 2.0.0.0/16 2.0.0.0/15,16,16".parse()?)
+    }
+
+    #[test]
+    fn yields_four_slash_tens() -> TestResult {
+        let set = setup()?;
+        let prefixes: Vec<_> = set
+            .prefixes_with_length(PrefixLength::from_primitive(10)?)
+            .collect();
+        assert_eq!(
+            prefixes,
+            vec![
+                p("1.0.0.0/10"),
+                p("1.64.0.0/10"),
+                p("1.128.0.0/10"),
+                p("1.192.0.0/10")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn yields_one_slash_eight() -> TestResult {
+        let set = setup()?;
+        let prefixes: Vec<_> = set
+            .prefixes_with_length(PrefixLength::from_primitive(8)?)
+            .collect();
+        assert_eq!(prefixes, vec![p("1.0.0.0/8")]);
+        Ok(())
+    }
+
+    #[test]
+    fn matches_filtered_prefixes() -> TestResult {
+        let set = setup()?;
+        for length in 0..=32 {
+            let length = PrefixLength::from_primitive(length)?;
+            let mut expected: Vec<_> = set
+                .prefixes()
+                .filter(|prefix| prefix.length() == length)
+                .collect();
+            let mut actual: Vec<_> = set.prefixes_with_length(length).collect();
+            expected.sort_by_key(|prefix| prefix.network());
+            actual.sort_by_key(|prefix| prefix.network());
+            assert_eq!(actual, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_yields_nothing() -> TestResult {
+        let set = PrefixSet::<Ipv4>::new();
+        assert_eq!(
+            set.prefixes_with_length(PrefixLength::from_primitive(8)?)
+                .count(),
+            0
+        );
+        Ok(())
+    }
+}
+
+mod bounded_ranges {
+    use std::collections::HashSet;
+
+    use ip::{concrete::PrefixLength, traits::Prefix as _};
+
+    use super::*;
+
+    fn length(l: u8) -> PrefixLength<Ipv4> {
+        PrefixLength::from_primitive(l).unwrap()
+    }
+
+    fn ranges(set: &PrefixSet<Ipv4>, floor: PrefixLength<Ipv4>) -> HashSet<PrefixRange<Ipv4>> {
+        set.bounded_ranges(floor).collect()
+    }
+
+    fn ranges_of(ranges: &[&str]) -> HashSet<PrefixRange<Ipv4>> {
+        ranges.iter().map(|r| r.parse().unwrap()).collect()
+    }
+
+    // the prefixes covered by `ranges`, which must be disjoint.
+    fn prefixes(ranges: impl Iterator<Item = PrefixRange<Ipv4>>) -> Vec<Prefix<Ipv4>> {
+        let mut prefixes: Vec<_> = ranges.flat_map(PrefixRange::<Ipv4>::into_iter).collect();
+        prefixes.sort_by_key(|p| (p.network(), p.length()));
+        prefixes
+    }
+
+    #[test]
+    fn siblings_are_not_merged_above_floor() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.3.0/24".parse()?;
+        assert_eq!(
+            ranges(&set, length(24)),
+            ranges_of(&["192.0.2.0/24,24,24", "192.0.3.0/24,24,24"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn siblings_are_merged_at_floor() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.3.0/24".parse()?;
+        assert_eq!(ranges(&set, length(23)), ranges_of(&["192.0.2.0/23,24,24"]));
+        Ok(())
+    }
+
+    #[test]
+    fn splits_mixed_length_ranges() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8,8,11".parse()?;
+        assert_eq!(
+            ranges(&set, length(10)),
+            ranges_of(&[
+                "10.0.0.0/8,8,8",
+                "10.0.0.0/9,9,9",
+                "10.128.0.0/9,9,9",
+                "10.0.0.0/10,10,11",
+                "10.64.0.0/10,10,11",
+                "10.128.0.0/10,10,11",
+                "10.192.0.0/10,10,11",
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ranges_respect_floor_and_cover_same_prefixes() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8,12,16 10.16.0.0/12,12,24 192.0.2.0/24".parse()?;
+        assert!(set.bounded_ranges(length(14)).all(|range| {
+            range.prefix().length() >= length(14) || range.lower() == range.upper()
+        }));
+        assert_eq!(
+            prefixes(set.bounded_ranges(length(14))),
+            prefixes(set.ranges())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn floor_of_min_length_is_default_aggregation() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.3.0/24 10.0.0.0/8,9,10".parse()?;
+        assert_eq!(
+            set.bounded_ranges(PrefixLength::MIN).collect::<Vec<_>>(),
+            set.ranges().collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn leaves_set_unchanged() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.3.0/24".parse()?;
+        let before = set.clone();
+        assert_eq!(set.bounded_ranges(length(24)).count(), 2);
+        assert_eq!(set, before);
+        assert!(set.is_aggregated());
+        Ok(())
+    }
+
+    #[test]
+    fn wide_split_is_lazy() -> TestResult {
+        let set: PrefixSet<Ipv4> = "0.0.0.0/0,0,32".parse()?;
+        let mut ranges = set.bounded_ranges(length(32));
+        assert_eq!(ranges.next(), Some("0.0.0.0/0,0,0".parse()?));
+        assert_eq!(ranges.next(), Some("0.0.0.0/1,1,1".parse()?));
+        assert_eq!(ranges.next(), Some("128.0.0.0/1,1,1".parse()?));
+        Ok(())
+    }
+}
+
+mod covers {
+    use ip::Ipv6;
+
+    use super::*;
+
+    #[test]
+    fn covers_but_does_not_contain_subprefix() -> TestResult {
+        let set: PrefixSet<Ipv4> = "1.0.0.0/8".parse()?;
+        assert!(set.covers("1.2.3.0/24".parse()?));
+        assert!(!set.contains("1.2.3.0/24".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn covers_and_contains_member() -> TestResult {
+        let set: PrefixSet<Ipv4> = "1.0.0.0/8".parse()?;
+        assert!(set.covers("1.0.0.0/8".parse()?));
+        assert!(set.contains("1.0.0.0/8".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_cover_supernet_or_divergent() -> TestResult {
+        let set: PrefixSet<Ipv4> = "1.0.0.0/8".parse()?;
+        assert!(!set.covers("0.0.0.0/7".parse()?));
+        assert!(!set.covers("2.0.0.0/8".parse()?));
+        assert!(!set.covers("2.2.3.0/24".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn only_lengths_present_cover() -> TestResult {
+        // 10.0.0.0/8 itself is not a member, so prefixes shorter than /16
+        // are not covered.
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8,16,16".parse()?;
+        assert!(set.covers("10.1.2.0/24".parse()?));
+        assert!(!set.covers("10.0.0.0/12".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_host_is_covered() -> TestResult {
+        let set: PrefixSet<Ipv6> = "2001:db8::/32".parse()?;
+        assert!(set.covers("2001:db8::1/128".parse()?));
+        assert!(!set.contains("2001:db8::1/128".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_covers_nothing() -> TestResult {
+        let set = PrefixSet::<Ipv4>::new();
+        assert!(!set.covers("0.0.0.0/0".parse()?));
+        Ok(())
+    }
+}
+
+mod hosts {
+    use ip::{concrete::Address, Ipv6};
+
+    use super::*;
+
+    #[test]
+    fn insert_ipv4_host() -> TestResult {
+        let addr: Address<Ipv4> = "192.0.2.1".parse()?;
+        let mut set = PrefixSet::new();
+        set.insert_host(addr);
+        assert_eq!(set.len(), 1);
+        assert!(set.contains_host(addr));
+        assert!(set.contains("192.0.2.1/32".parse()?));
+        assert!(!set.contains_host("192.0.2.2".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn insert_ipv6_host() -> TestResult {
+        let addr: Address<Ipv6> = "2001:db8::1".parse()?;
+        let mut set = PrefixSet::new();
+        set.insert_host(addr);
+        assert_eq!(set.len(), 1);
+        assert!(set.contains_host(addr));
+        assert!(set.contains("2001:db8::1/128".parse()?));
+        assert!(!set.contains("2001:db8::/64".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn host_membership_is_exact() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24".parse()?;
+        let addr: Address<Ipv4> = "192.0.2.1".parse()?;
+        assert!(!set.contains_host(addr));
+        assert!(set.covers("192.0.2.1/32".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn adjacent_hosts_aggregate() -> TestResult {
+        let mut set = PrefixSet::<Ipv4>::new();
+        set.insert_host("192.0.2.0".parse()?)
+            .insert_host("192.0.2.1".parse()?);
+        assert_eq!(set.ranges().count(), 1);
+        assert_eq!(set.len(), 2);
+        Ok(())
+    }
+}
+
+mod trim_to {
+    use ip::{
+        concrete::{self, PrefixLength},
+        Ipv6,
+    };
+
+    use super::*;
+
+    fn intersection<A: ip::Afi>(set: &PrefixSet<A>, space: Prefix<A>) -> PrefixSet<A> {
+        let range = concrete::PrefixRange::new(space, space.length()..=PrefixLength::MAX).unwrap();
+        set & &PrefixSet::new().insert(range).to_owned()
+    }
+
+    fn setup() -> TestResult<PrefixSet<Ipv4>> {
+        Ok(
+            "1.0.0.0/8,8,8 1.0.0.0/8,12,20 1.2.0.0/16,24,24 1.2.3.0/24,28,32 1.3.0.0/16 2.0.0.0/8,16,16"
+                .parse()?,
+        )
+    }
+
+    #[test]
+    fn matches_intersection() -> TestResult {
+        let set = setup()?;
+        let space = "1.2.0.0/16".parse()?;
+        assert_eq!(set.trim_to(space), intersection(&set, space));
+        Ok(())
+    }
+
+    #[test]
+    fn matches_intersection_for_many_spaces() -> TestResult {
+        let set = setup()?;
+        for space in [
+            "0.0.0.0/0",
+            "0.0.0.0/7",
+            "1.0.0.0/8",
+            "1.0.0.0/10",
+            "1.2.0.0/15",
+            "1.2.3.0/24",
+            "1.2.3.4/30",
+            "1.2.3.4/32",
+            "1.3.0.0/16",
+            "1.3.0.0/17",
+            "2.1.0.0/16",
+            "2.1.0.0/24",
+            "3.0.0.0/8",
+        ] {
+            let space = space.parse()?;
+            assert_eq!(set.trim_to(space), intersection(&set, space), "{space}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn space_outside_set_is_empty() -> TestResult {
+        let set = setup()?;
+        assert!(set.trim_to("192.0.2.0/24".parse()?).is_empty());
+        assert!(PrefixSet::<Ipv4>::new()
+            .trim_to("0.0.0.0/0".parse()?)
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_matches_intersection() -> TestResult {
+        let set: PrefixSet<Ipv6> = "2001:db8::/32,32,48 2001:db8:1::/48,56,64".parse()?;
+        for space in [
+            "2001:db8::/32",
+            "2001:db8:1::/48",
+            "2001:db8:1:100::/56",
+            "::/0",
+        ] {
+            let space = space.parse()?;
+            assert_eq!(set.trim_to(space), intersection(&set, space), "{space}");
+        }
+        Ok(())
+    }
+}
+
+mod prefixes_in {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn range(s: &str) -> PrefixRange<Ipv4> {
+        s.parse().unwrap()
+    }
+
+    fn setup() -> TestResult<PrefixSet<Ipv4>> {
+        Ok("10.0.0.0/8,8,12 10.0.0.0/8,24,24 10.1.0.0/16,16,25 11.0.0.0/8".parse()?)
+    }
+
+    // brute force: filter every prefix of `s`.
+    fn expected(s: &PrefixSet<Ipv4>, r: PrefixRange<Ipv4>) -> HashSet<Prefix<Ipv4>> {
+        let window = PrefixSet::singleton(r);
+        s.prefixes().filter(|&p| window.contains(p)).collect()
+    }
+
+    #[test]
+    fn yields_window_of_slash_8() -> TestResult {
+        let s = setup()?;
+        let found: Vec<_> = s.prefixes_in(range("10.1.0.0/16,24,24")).collect();
+        assert_eq!(found.len(), 256);
+        assert!(found.iter().all(|p| p.length().into_primitive() == 24));
+        assert!(found.contains(&"10.1.255.0/24".parse()?));
+        assert!(!found.contains(&"10.2.0.0/24".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn matches_filtering_all_prefixes() -> TestResult {
+        let s = setup()?;
+        for r in [
+            "10.1.0.0/16,24,24",
+            "10.1.0.0/16,16,18",
+            "10.0.0.0/8,9,10",
+            "10.1.2.0/24,24,25",
+            "10.2.0.0/16,16,24",
+            "11.0.0.0/8,8,8",
+            "11.0.0.0/8,9,9",
+            "12.0.0.0/8,8,24",
+        ] {
+            let found: HashSet<_> = s.prefixes_in(range(r)).collect();
+            assert_eq!(found, expected(&s, range(r)), "{}", r);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn includes_covering_ranges_within_window() -> TestResult {
+        let s: PrefixSet<Ipv4> = "10.0.0.0/8,8,16".parse()?;
+        let found: Vec<_> = s.prefixes_in(range("10.1.0.0/16,16,24")).collect();
+        assert_eq!(found, vec!["10.1.0.0/16".parse::<Prefix<Ipv4>>()?]);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_yields_nothing() {
+        let s = PrefixSet::<Ipv4>::new();
+        assert_eq!(s.prefixes_in(range("0.0.0.0/0,0,32")).count(), 0);
+    }
+}
+
+mod length_bounds {
+    use ip::{concrete::PrefixLength, Ipv6};
+
+    use super::*;
+
+    #[test]
+    fn spans_slash_8_to_slash_24() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8 172.16.0.0/12,16,20 192.0.2.0/24".parse()?;
+        assert_eq!(set.min_length(), Some(PrefixLength::from_primitive(8)?));
+        assert_eq!(set.max_length(), Some(PrefixLength::from_primitive(24)?));
+        Ok(())
+    }
+
+    #[test]
+    fn matches_expanded_prefixes() -> TestResult {
+        let set: PrefixSet<Ipv4> =
+            "10.0.0.0/8,9,11 10.0.0.0/16,20,20 192.0.2.0/24,26,28".parse()?;
+        let lengths: Vec<_> = set.prefixes().map(|prefix| prefix.length()).collect();
+        assert_eq!(set.min_length(), lengths.iter().copied().min());
+        assert_eq!(set.max_length(), lengths.iter().copied().max());
+        Ok(())
+    }
+
+    #[test]
+    fn aggregated_siblings() -> TestResult {
+        // the two /24s are aggregated into a range covered by a /23 node.
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.3.0/24".parse()?;
+        assert_eq!(set.min_length(), Some(PrefixLength::from_primitive(24)?));
+        assert_eq!(set.max_length(), Some(PrefixLength::from_primitive(24)?));
+        Ok(())
+    }
+
+    #[test]
+    fn full_ipv6_span() -> TestResult {
+        let set: PrefixSet<Ipv6> = "::/0,0,0 2001:db8::1/128".parse()?;
+        assert_eq!(set.min_length(), Some(PrefixLength::MIN));
+        assert_eq!(set.max_length(), Some(PrefixLength::MAX));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_has_no_bounds() {
+        let set = PrefixSet::<Ipv4>::new();
+        assert_eq!(set.min_length(), None);
+        assert_eq!(set.max_length(), None);
+    }
+}
+
+mod is_aggregated {
+    use super::*;
+
+    fn node(s: &str) -> Box<Node<Ipv4>> {
+        match s.parse::<ip::concrete::PrefixRange<Ipv4>>() {
+            Ok(range) => Node::from(range).boxed(),
+            Err(_) => s.parse::<Node<Ipv4>>().unwrap().boxed(),
+        }
+    }
+
+    fn unaggregated(nodes: &[&str]) -> PrefixSet<Ipv4> {
+        let root = nodes
+            .iter()
+            .map(|s| node(s))
+            .reduce(|root, node| root.add(node));
+        PrefixSet::new_with_root(root)
+    }
+
+    #[test]
+    fn empty_set_is_aggregated() {
+        assert!(PrefixSet::<Ipv4>::new().is_aggregated());
+    }
+
+    #[test]
+    fn parsed_set_is_aggregated() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8,8,16 10.1.0.0/16,24,24 192.0.2.0/24".parse()?;
+        assert!(set.is_aggregated());
+        Ok(())
+    }
+
+    #[test]
+    fn mergeable_siblings() {
+        let mut set = unaggregated(&["192.0.2.0/24", "192.0.3.0/24"]);
+        assert!(!set.is_aggregated());
+        assert!(set.aggregate().is_aggregated());
+    }
+
+    #[test]
+    fn duplicated_lengths() {
+        let mut set = unaggregated(&["10.0.0.0/8,8,16", "10.1.0.0/16"]);
+        assert!(!set.is_aggregated());
+        assert!(set.aggregate().is_aggregated());
+    }
+
+    #[test]
+    fn redundant_glue() {
+        let root = node("10.0.0.0/8")
+            .add(node("11.0.0.0/8"))
+            .remove(&node("11.0.0.0/8"));
+        let mut set = PrefixSet::new_with_root(Some(root));
+        assert!(!set.is_aggregated());
+        assert!(set.aggregate().is_aggregated());
+    }
+
+    #[test]
+    fn divergent_nodes_are_aggregated() {
+        let set = unaggregated(&["10.0.0.0/8", "192.0.2.0/24"]);
+        assert!(set.is_aggregated());
+    }
+}
+
+mod singleton {
+    use ip::Ipv6;
+
+    use super::*;
+
+    #[test]
+    fn contains_one_prefix() -> TestResult {
+        let prefix = "192.0.2.0/24".parse::<Prefix<Ipv4>>()?;
+        let set = PrefixSet::singleton(prefix);
+        assert_eq!(set.len(), 1);
+        assert!(set.contains(prefix));
+        assert_eq!(set, PrefixSet::new().insert(prefix).to_owned());
+        Ok(())
+    }
+
+    #[test]
+    fn contains_one_range() -> TestResult {
+        let range = "2001:db8::/32,48,48".parse::<PrefixRange<Ipv6>>()?;
+        let set = PrefixSet::singleton(range.clone());
+        assert_eq!(set.len(), 1 << 16);
+        assert_eq!(set.ranges().collect::<Vec<_>>(), vec![range.clone()]);
+        assert_eq!(set, PrefixSet::new().insert(range).to_owned());
+        Ok(())
+    }
+
+    #[test]
+    fn is_aggregated() -> TestResult {
+        let set = PrefixSet::singleton("10.0.0.0/8,8,16".parse::<PrefixRange<Ipv4>>()?);
+        assert!(set.is_aggregated());
+        Ok(())
+    }
+}
+
+mod to_vec {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn setup() -> TestResult<PrefixSet<Ipv4>> {
+        Ok("10.0.0.0/8,8,10 10.1.0.0/16,24,24 192.0.2.0/24 192.0.3.0/24".parse()?)
+    }
+
+    #[test]
+    fn ranges_vec_matches_iterator() -> TestResult {
+        let set = setup()?;
+        assert_eq!(set.to_ranges_vec(), set.ranges().collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn ranges_vec_matches_cache() -> TestResult {
+        let mut set = setup()?;
+        let expected = set.materialize_ranges().to_vec();
+        assert_eq!(set.to_ranges_vec(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn prefixes_vec_matches_iterator() -> TestResult {
+        let set = setup()?;
+        let prefixes = set.to_prefixes_vec();
+        assert_eq!(prefixes, set.prefixes().collect::<Vec<_>>());
+        assert_eq!(prefixes.capacity(), set.len());
+        Ok(())
+    }
+
+    #[test]
+    fn ranges_are_canonical() -> TestResult {
+        let set = setup()?;
+        // built in a different order, with redundant entries.
+        let other: PrefixSet<Ipv4> =
+            "192.0.3.0/24 10.1.0.0/16,24,24 192.0.2.0/24 10.0.0.0/8,9,10 10.0.0.0/8 10.0.0.0/9"
+                .parse()?;
+        assert_eq!(set.to_ranges_vec(), other.to_ranges_vec());
+        assert_eq!(set.to_prefixes_vec(), other.to_prefixes_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set() {
+        let set = PrefixSet::<Ipv6>::new();
+        assert!(set.to_ranges_vec().is_empty());
+        assert!(set.to_prefixes_vec().is_empty());
+    }
+}
+
+mod relation {
+    use super::*;
+
+    use crate::set::SetRelation;
+
+    fn set(s: &str) -> Result<PrefixSet<Ipv4>, crate::Error> {
+        s.parse()
+    }
+
+    #[test]
+    fn equal_sets() -> TestResult {
+        let s = set("10.0.0.0/8,8,16")?;
+        let t = set("10.0.0.0/9,9,16 10.128.0.0/9,9,16 10.0.0.0/8")?;
+        assert_eq!(s.relation(&t), SetRelation::Equal);
+        assert_eq!(t.relation(&s), SetRelation::Equal);
+        Ok(())
+    }
+
+    #[test]
+    fn subset_and_superset() -> TestResult {
+        let s = set("10.0.0.0/8,12,16")?;
+        let t = set("10.0.0.0/8,8,16 192.0.2.0/24")?;
+        assert_eq!(s.relation(&t), SetRelation::Subset);
+        assert_eq!(t.relation(&s), SetRelation::Superset);
+        Ok(())
+    }
+
+    #[test]
+    fn overlapping_sets() -> TestResult {
+        let s = set("10.0.0.0/8,8,16")?;
+        let t = set("10.0.0.0/8,16,24")?;
+        assert_eq!(s.relation(&t), SetRelation::Overlapping);
+        assert_eq!(t.relation(&s), SetRelation::Overlapping);
+        Ok(())
+    }
+
+    #[test]
+    fn disjoint_sets() -> TestResult {
+        let s = set("10.0.0.0/8,8,11")?;
+        let t = set("10.0.0.0/8,12,15 192.0.2.0/24")?;
+        assert_eq!(s.relation(&t), SetRelation::Disjoint);
+        assert_eq!(t.relation(&s), SetRelation::Disjoint);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_sets() -> TestResult {
+        let empty = PrefixSet::<Ipv4>::new();
+        let s = set("192.0.2.0/24")?;
+        assert_eq!(empty.relation(&PrefixSet::new()), SetRelation::Equal);
+        assert_eq!(empty.relation(&s), SetRelation::Subset);
+        assert_eq!(s.relation(&empty), SetRelation::Superset);
+        Ok(())
+    }
+
+    #[test]
+    fn agrees_with_partial_cmp() -> TestResult {
+        let sets = [
+            set("10.0.0.0/8,8,16")?,
+            set("10.0.0.0/8,12,16")?,
+            set("10.0.0.0/8,16,24")?,
+            set("192.0.2.0/24")?,
+            PrefixSet::new(),
+        ];
+        for s in &sets {
+            for t in &sets {
+                let expected = match s.partial_cmp(t) {
+                    Some(core::cmp::Ordering::Equal) => Some(SetRelation::Equal),
+                    Some(core::cmp::Ordering::Less) => Some(SetRelation::Subset),
+                    Some(core::cmp::Ordering::Greater) => Some(SetRelation::Superset),
+                    None => None,
+                };
+                match expected {
+                    Some(relation) => assert_eq!(s.relation(t), relation),
+                    None => assert!(matches!(
+                        s.relation(t),
+                        SetRelation::Overlapping | SetRelation::Disjoint
+                    )),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+mod ranges_by_length {
+    use super::*;
+
+    #[test]
+    fn sorts_by_length_then_bounds_then_network() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8,8,8 10.0.0.0/8,12,12 10.0.0.0/8,20,24 \
+            172.16.0.0/12,16,16 172.16.0.0/12,20,20 192.0.2.0/24 198.51.100.0/24,25,25 \
+            203.0.113.0/24"
+            .parse()?;
+        let ranges: Vec<_> = set.ranges_by_length().map(|r| r.to_string()).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                "10.0.0.0/8^8-8",
+                "10.0.0.0/8^12-12",
+                "10.0.0.0/8^20-24",
+                "172.16.0.0/12^16-16",
+                "172.16.0.0/12^20-20",
+                "192.0.2.0/24^24-24",
+                "203.0.113.0/24^24-24",
+                "198.51.100.0/24^25-25",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn yields_same_ranges_as_tree_order() -> TestResult {
+        let set: PrefixSet<Ipv4> =
+            "10.0.0.0/8,8,10 10.0.0.0/8,12,13 192.0.2.128/25 1.0.0.0/8".parse()?;
+        assert_eq!(set.ranges_by_length().count(), set.ranges().count());
+        assert_eq!(set.ranges_by_length().collect::<PrefixSet<_>>(), set);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_yields_nothing() {
+        assert_eq!(PrefixSet::<Ipv4>::new().ranges_by_length().count(), 0);
+    }
+}
+
+mod validate {
+    use ip::{concrete::PrefixLength, Ipv6};
+
+    use crate::set::{PolicyViolation, SetPolicy};
+
+    use super::*;
+
+    fn length<A: ip::Afi>(n: u8) -> PrefixLength<A> {
+        PrefixLength::try_from(usize::from(n)).unwrap()
+    }
+
+    fn setup() -> Result<PrefixSet<Ipv4>, crate::Error> {
+        "10.0.0.0/8,16,20 192.0.2.0/24".parse()
+    }
+
+    #[test]
+    fn empty_policy_accepts_anything() -> TestResult {
+        assert_eq!(setup()?.validate(&SetPolicy::default()), Ok(()));
+        Ok(())
+    }
+
+    #[test]
+    fn max_prefixes() -> TestResult {
+        let s = setup()?;
+        let count = s.len();
+        let policy = SetPolicy {
+            max_prefixes: Some(count),
+            ..Default::default()
+        };
+        assert_eq!(s.validate(&policy), Ok(()));
+        let policy = SetPolicy {
+            max_prefixes: Some(count - 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            s.validate(&policy),
+            Err(PolicyViolation::TooManyPrefixes {
+                limit: count - 1,
+                found: count
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn max_length() -> TestResult {
+        let s = setup()?;
+        let policy = SetPolicy {
+            max_length: Some(length(24)),
+            ..Default::default()
+        };
+        assert_eq!(s.validate(&policy), Ok(()));
+        let policy = SetPolicy {
+            max_length: Some(length(19)),
+            ..Default::default()
+        };
+        assert_eq!(
+            s.validate(&policy),
+            Err(PolicyViolation::TooLong {
+                limit: length(19),
+                found: length(24)
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn min_length() -> TestResult {
+        let s = setup()?;
+        let policy = SetPolicy {
+            min_length: Some(length(16)),
+            ..Default::default()
+        };
+        assert_eq!(s.validate(&policy), Ok(()));
+        let policy = SetPolicy {
+            min_length: Some(length(17)),
+            ..Default::default()
+        };
+        assert_eq!(
+            s.validate(&policy),
+            Err(PolicyViolation::TooShort {
+                limit: length(17),
+                found: length(16)
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reports_prefix_count_first() -> TestResult {
+        let policy = SetPolicy {
+            max_prefixes: Some(1),
+            max_length: Some(length(8)),
+            min_length: Some(length(32)),
+        };
+        assert!(matches!(
+            setup()?.validate(&policy),
+            Err(PolicyViolation::TooManyPrefixes { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_satisfies_length_limits() {
+        let policy = SetPolicy {
+            max_prefixes: Some(0),
+            max_length: Some(length(48)),
+            min_length: Some(length(64)),
+        };
+        assert_eq!(PrefixSet::<Ipv6>::new().validate(&policy), Ok(()));
+    }
+
+    #[test]
+    fn ipv6_route_server_limit() -> TestResult {
+        let s: PrefixSet<Ipv6> = "2001:db8::/32,32,48 2001:db8:f00::/56".parse()?;
+        let policy = SetPolicy {
+            max_length: Some(length(48)),
+            ..Default::default()
+        };
+        assert_eq!(
+            s.validate(&policy),
+            Err(PolicyViolation::TooLong {
+                limit: length(48),
+                found: length(56)
+            })
+        );
+        Ok(())
+    }
+}
+
+mod debug {
+    use ip::Ipv6;
+
+    use super::*;
+
+    #[test]
+    fn summarizes_counts() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8,16,16 192.0.2.0/24 198.51.100.0/24".parse()?;
+        let debug = format!("{set:?}");
+        assert_eq!(
+            debug,
+            "PrefixSet { prefixes: 258, ranges: 3, family: Ipv4 }"
+        );
+        assert!(!debug.contains("gluemap"));
+        assert!(!debug.contains("192.0.2.0"));
+        Ok(())
+    }
+
+    #[test]
+    fn summarizes_empty_set() {
+        assert_eq!(
+            format!("{:?}", PrefixSet::<Ipv6>::new()),
+            "PrefixSet { prefixes: 0, ranges: 0, family: Ipv6 }"
+        );
+    }
+
+    #[test]
+    fn alternate_form_shows_tree() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24 198.51.100.0/24".parse()?;
+        let debug = format!("{set:#?}");
+        assert!(debug.contains("root"));
+        assert!(debug.contains("left"));
+        assert!(debug.contains("gluemap"));
+        Ok(())
+    }
+}
+
+mod stats {
+    use ip::{concrete::PrefixLength, Ipv6};
+
+    use super::*;
+
+    fn length<A: ip::Afi>(n: u8) -> PrefixLength<A> {
+        PrefixLength::try_from(usize::from(n)).unwrap()
+    }
+
+    #[test]
+    fn modal_length_of_mostly_slash_24s() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8 10.0.0.0/16,24,24 172.16.0.0/12,16,16 \
+            192.0.2.0/24 198.51.100.0/24,25,25"
+            .parse()?;
+        let stats = set.stats();
+        assert_eq!(stats.prefix_count, 1 + 256 + 16 + 1 + 2);
+        assert_eq!(stats.modal_length, Some(length(24)));
+        assert_eq!(stats.range_count, set.count_ranges());
+        assert!(stats.mean_length > 23.0 && stats.mean_length < 24.0);
+        Ok(())
+    }
+
+    #[test]
+    fn modal_length_prefers_shortest_on_tie() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24,25,25 198.51.100.0/26,27,27".parse()?;
+        assert_eq!(set.stats().modal_length, Some(length(25)));
+        Ok(())
+    }
+
+    #[test]
+    fn matches_length_histogram() -> TestResult {
+        let set: PrefixSet<Ipv6> = "2001:db8::/32,40,48 2001:db8:f00::/56".parse()?;
+        let stats = set.stats();
+        let histogram = set.length_histogram();
+        assert_eq!(
+            stats.prefix_count,
+            histogram.values().map(|&n| n as u128).sum::<u128>()
+        );
+        assert_eq!(
+            stats.modal_length,
+            histogram
+                .iter()
+                .max_by_key(|(_, &n)| n)
+                .map(|(&length, _)| length)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn aggregation_ratio() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24,26,26".parse()?;
+        assert_eq!(set.stats().aggregation_ratio(), 0.25);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set() {
+        let stats = PrefixSet::<Ipv4>::new().stats();
+        assert_eq!(stats.prefix_count, 0);
+        assert_eq!(stats.range_count, 0);
+        assert_eq!(stats.mean_length, 0.0);
+        assert_eq!(stats.modal_length, None);
+        assert_eq!(stats.aggregation_ratio(), 0.0);
+    }
+}
+
+mod try_insert_from {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Failed(usize);
+
+    // yield the prefixes in `items` as `Ok`, failing at index `fail_at`.
+    fn fallible<'a>(
+        items: &'a [&'a str],
+        fail_at: usize,
+    ) -> impl Iterator<Item = Result<Prefix<Ipv4>, Failed>> + 'a {
+        items.iter().enumerate().map(move |(i, s)| {
+            if i == fail_at {
+                Err(Failed(i))
+            } else {
+                Ok(s.parse().unwrap())
+            }
+        })
+    }
+
+    const ITEMS: &[&str] = &["192.0.2.0/25", "192.0.2.128/25", "198.51.100.0/24"];
+
+    #[test]
+    fn inserts_all_items() -> TestResult {
+        let mut set = PrefixSet::new();
+        assert_eq!(set.try_insert_from(fallible(ITEMS, usize::MAX)).err(), None);
+        let expected: PrefixSet<Ipv4> = "192.0.2.0/24,25,25 198.51.100.0/24".parse()?;
+        assert_eq!(set, expected);
+        assert!(set.is_aggregated());
+        Ok(())
+    }
+
+    #[test]
+    fn returns_first_error() {
+        let mut set = PrefixSet::new();
+        assert_eq!(
+            set.try_insert_from(fallible(ITEMS, 1)).err(),
+            Some(Failed(1))
+        );
+    }
+
+    #[test]
+    fn leaves_set_unchanged_on_error() -> TestResult {
+        let mut set: PrefixSet<Ipv4> = "192.0.2.0/25 203.0.113.0/24".parse()?;
+        let before = set.clone();
+        assert!(set.try_insert_from(fallible(ITEMS, 2)).is_err());
+        assert_eq!(set, before);
+        assert!(set.is_aggregated());
+        assert_eq!(set.ranges().count(), 2);
+        Ok(())
+    }
+}
+
+mod into_deaggregated {
+    use ip::concrete::PrefixLength;
+
+    use super::*;
+
+    fn length(n: u8) -> PrefixLength<Ipv4> {
+        PrefixLength::try_from(usize::from(n)).unwrap()
+    }
+
+    #[test]
+    fn matches_clamp_length() -> TestResult {
+        let set: PrefixSet<Ipv4> =
+            "10.0.0.0/8,8,12 10.0.0.0/8,20,24 192.0.2.0/24 203.0.113.0/25".parse()?;
+        let expected = set.clamp_length(length(16), PrefixLength::MAX);
+        assert_eq!(set.into_deaggregated(length(16)), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_longer_prefixes() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/25 198.51.100.0/24,26,28".parse()?;
+        assert_eq!(set.clone().into_deaggregated(length(24)), set);
+        Ok(())
+    }
+
+    #[test]
+    fn yields_host_prefixes() -> TestResult {
+        let set: PrefixSet<Ipv4> =
+            "192.0.2.0/28 192.0.2.8/29 198.51.100.0/30,31,32 203.0.113.7/32".parse()?;
+        let hosts: Vec<_> = set.into_host_prefixes().collect();
+        assert_eq!(hosts.len(), 16 + 4 + 1);
+        assert!(hosts.iter().all(|host| host.length() == PrefixLength::MAX));
+        assert!(hosts.contains(&"192.0.2.15/32".parse()?));
+        assert!(hosts.contains(&"198.51.100.3/32".parse()?));
+        assert!(!hosts.contains(&"192.0.2.16/32".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_yields_no_hosts() {
+        assert_eq!(PrefixSet::<Ipv4>::new().into_host_prefixes().count(), 0);
+    }
+}
+
+mod intersect_range {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn assert_matches_intersection<A: ip::Afi>(s: &PrefixSet<A>, range: PrefixRange<A>) {
+        assert_eq!(
+            s.intersect_range(range.clone()),
+            s & &PrefixSet::singleton(range.clone()),
+            "mismatch for range {}",
+            range
+        );
+    }
+
+    #[test]
+    fn ipv4_matches_intersection() -> TestResult {
+        let s: PrefixSet<Ipv4> =
+            "10.0.0.0/8,8,10 10.0.0.0/8,12,13 10.1.0.0/16,20,24 192.0.2.0/24 192.0.2.128/25,27,28"
+                .parse()?;
+        for range in [
+            "0.0.0.0/0,0,32",
+            "10.0.0.0/8,8,8",
+            "10.0.0.0/7,9,12",
+            "10.1.0.0/16,16,22",
+            "10.1.2.0/24,24,32",
+            "192.0.2.0/24,25,28",
+            "198.51.100.0/24,24,32",
+        ] {
+            assert_matches_intersection(&s, range.parse()?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_matches_intersection() -> TestResult {
+        let s: PrefixSet<Ipv6> = "2001:db8::/32,32,48 2001:db8:f00::/40,56,64".parse()?;
+        for range in [
+            "::/0,0,128",
+            "2001:db8::/32,40,40",
+            "2001:db8:f00::/48,48,64",
+            "2001:db9::/32,32,48",
+        ] {
+            assert_matches_intersection(&s, range.parse()?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set() -> TestResult {
+        let s = PrefixSet::<Ipv4>::new();
+        assert!(s.intersect_range("0.0.0.0/0,0,32".parse()?).is_empty());
+        Ok(())
+    }
+}
+
+mod aggregated_cow {
+    use alloc::borrow::Cow;
+
+    use super::*;
+
+    #[test]
+    fn borrows_aggregated_set() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.3.0/24 198.51.100.0/24".parse()?;
+        let cow = set.aggregated_cow();
+        assert!(matches!(cow, Cow::Borrowed(_)));
+        assert_eq!(*cow, set);
+        Ok(())
+    }
+
+    #[test]
+    fn borrows_empty_set() {
+        let set = PrefixSet::<Ipv4>::new();
+        assert!(matches!(set.aggregated_cow(), Cow::Borrowed(_)));
+    }
+}
+
+mod gaps {
+    use ip::Ipv6;
+
+    use super::*;
+
+    fn gaps<A: ip::Afi>(s: &PrefixSet<A>) -> Vec<String> {
+        s.gaps().map(|gap| gap.prefix().to_string()).collect()
+    }
+
+    #[test]
+    fn reports_gap_between_members() -> TestResult {
+        let set: PrefixSet<Ipv4> = "1.0.0.0/24 1.0.2.0/24".parse()?;
+        assert_eq!(gaps(&set), vec!["1.0.1.0/24"]);
+        Ok(())
+    }
+
+    #[test]
+    fn reports_maximal_gaps_in_address_order() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/24 10.0.3.128/25 10.0.8.0/24".parse()?;
+        assert_eq!(
+            gaps(&set),
+            vec!["10.0.1.0/24", "10.0.2.0/24", "10.0.3.0/25", "10.0.4.0/22"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ignores_covered_lengths() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24,26,26 192.0.2.0/28 192.0.4.0/24".parse()?;
+        assert_eq!(gaps(&set), vec!["192.0.3.0/24"]);
+        Ok(())
+    }
+
+    #[test]
+    fn gaps_and_members_are_disjoint() -> TestResult {
+        let set: PrefixSet<Ipv6> = "2001:db8::/48 2001:db8:2::/47 2001:db8:8::/45,48,64".parse()?;
+        set.gaps().for_each(|gap| {
+            assert!(!set.overlaps(&PrefixSet::singleton(gap.prefix())));
+        });
+        assert_eq!(gaps(&set), vec!["2001:db8:1::/48", "2001:db8:4::/46"]);
+        Ok(())
+    }
+
+    #[test]
+    fn contiguous_set_has_no_gaps() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/25 192.0.2.128/25 192.0.3.0/24".parse()?;
+        assert_eq!(set.gaps().count(), 0);
+        assert_eq!(PrefixSet::<Ipv4>::new().gaps().count(), 0);
+        Ok(())
+    }
+}
+
+mod coalescing {
+    use super::*;
+
+    fn ranges(s: &PrefixSet<Ipv4>) -> Vec<String> {
+        s.ranges_by_length()
+            .map(|range| range.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn four_subprefixes_coalesce_to_covering_range() -> TestResult {
+        let set: PrefixSet<Ipv4> =
+            "192.0.2.0/26 192.0.2.64/26 192.0.2.128/26 192.0.2.192/26".parse()?;
+        assert_eq!(ranges(&set), vec!["192.0.2.0/24^26-26"]);
+        Ok(())
+    }
+
+    #[test]
+    fn intermediate_lengths_coalesce_into_base_prefix() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/26 192.0.2.64/26 192.0.2.128/26 192.0.2.192/26 \
+            192.0.2.0/25 192.0.2.128/25 192.0.2.0/24"
+            .parse()?;
+        assert_eq!(ranges(&set), vec!["192.0.2.0/24^24-26"]);
+        Ok(())
+    }
+
+    #[test]
+    fn coverage_split_across_depths_coalesces() -> TestResult {
+        // the left half is covered by a single range, the right half by
+        // separate /26s, with no node at 192.0.2.128/25.
+        let set: PrefixSet<Ipv4> = "192.0.2.0/25,26,26 192.0.2.128/26 192.0.2.192/26".parse()?;
+        assert_eq!(ranges(&set), vec!["192.0.2.0/24^26-26"]);
+        Ok(())
+    }
+
+    #[test]
+    fn coalesces_after_removal_and_reinsertion() -> TestResult {
+        let mut set: PrefixSet<Ipv4> = "192.0.2.0/24,26,26".parse()?;
+        set.remove("192.0.2.64/26".parse::<Prefix<Ipv4>>()?);
+        assert_eq!(set.ranges().count(), 2);
+        set.insert("192.0.2.64/26".parse::<Prefix<Ipv4>>()?);
+        assert_eq!(ranges(&set), vec!["192.0.2.0/24^26-26"]);
+        Ok(())
+    }
+
+    #[test]
+    fn partial_coverage_is_not_coalesced() -> TestResult {
+        let set: PrefixSet<Ipv4> = "192.0.2.0/26 192.0.2.64/26 192.0.2.128/26".parse()?;
+        assert_eq!(
+            ranges(&set),
+            vec!["192.0.2.0/25^26-26", "192.0.2.128/26^26-26"]
+        );
+        Ok(())
+    }
+}
+
+mod from_address_range {
+    use ip::{traits::Prefix as _, Address, Ipv6};
+
+    use super::*;
+
+    fn cover<A: ip::Afi>(start: &str, end: &str) -> Result<Vec<String>, crate::Error> {
+        let set = PrefixSet::<A>::from_address_range(start.parse()?, end.parse()?);
+        Ok(set.prefixes().map(|prefix| prefix.to_string()).collect())
+    }
+
+    #[test]
+    fn aligned_range_is_single_prefix() -> TestResult {
+        assert_eq!(
+            cover::<Ipv4>("10.0.0.0", "10.0.0.255")?,
+            vec!["10.0.0.0/24"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unaligned_range() -> TestResult {
+        let mut prefixes = cover::<Ipv4>("10.0.0.1", "10.0.0.6")?;
+        prefixes.sort();
+        assert_eq!(
+            prefixes,
+            vec!["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/31", "10.0.0.6/32"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn covers_exactly_the_range() -> TestResult {
+        let start = "192.0.2.13".parse::<Address<Ipv4>>()?;
+        let end = "192.0.3.200".parse::<Address<Ipv4>>()?;
+        let set = PrefixSet::from_address_range(start, end);
+        let hosts: Vec<_> = set.into_host_prefixes().collect();
+        assert_eq!(hosts.len(), 256 - 13 + 201);
+        assert!(hosts
+            .iter()
+            .all(|host| host.network() >= start && host.network() <= end));
+        Ok(())
+    }
+
+    #[test]
+    fn single_address() -> TestResult {
+        assert_eq!(
+            cover::<Ipv6>("2001:db8::1", "2001:db8::1")?,
+            vec!["2001:db8::1/128"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn whole_address_space() -> TestResult {
+        assert_eq!(
+            cover::<Ipv4>("0.0.0.0", "255.255.255.255")?,
+            vec!["0.0.0.0/0"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reversed_range_is_empty() -> TestResult {
+        assert!(cover::<Ipv4>("10.0.0.6", "10.0.0.1")?.is_empty());
+        Ok(())
+    }
+}
+
+mod contains_address {
+    use ip::{Address, Ipv6};
+
+    use super::*;
+
+    #[test]
+    fn ipv4_coverage() -> TestResult {
+        let set: PrefixSet<Ipv4> = "10.0.0.0/8".parse()?;
+        assert!(set.contains_address("10.1.2.3".parse()?));
+        assert!(set.contains_address("10.255.255.255".parse()?));
+        assert!(!set.contains_address("11.0.0.1".parse()?));
+        assert!(!set.contains_address("9.255.255.255".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn ignores_prefix_lengths_of_ranges() -> TestResult {
+        // the addresses of 192.0.2.0/24 are only covered by its /26
+        // subprefixes, which are all members.
+        let set: PrefixSet<Ipv4> = "192.0.2.0/24,26,26".parse()?;
+        assert!(set.contains_address("192.0.2.200".parse()?));
+        assert!(!set.contains("192.0.2.0/24".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_coverage() -> TestResult {
+        let set: PrefixSet<Ipv6> = "2001:db8::/32 2001:db8:f00::1/128".parse()?;
+        assert!(set.contains_address("2001:db8:1::1".parse()?));
+        assert!(!set.contains_address("2001:db9::1".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_contains_no_address() -> TestResult {
+        let addr: Address<Ipv4> = "0.0.0.0".parse()?;
+        assert!(!PrefixSet::new().contains_address(addr));
+        Ok(())
+    }
+}
+
+mod eq_fast_path {
+    use super::*;
+
+    fn setup() -> Result<(PrefixSet<Ipv4>, PrefixSet<Ipv4>), crate::Error> {
+        Ok((
+            "192.0.2.0/24 198.51.100.0/24".parse()?,
+            "192.0.2.0/24".parse()?,
+        ))
+    }
+
+    #[test]
+    fn sets_of_different_sizes_compare_unequal() -> TestResult {
+        let (s, t) = setup()?;
+        assert_ne!(s.len(), t.len());
+        assert_ne!(s, t);
+        assert_ne!(t, s);
+        Ok(())
+    }
+
+    #[test]
+    fn sets_of_equal_size_compare_by_contents() -> TestResult {
+        let s: PrefixSet<Ipv4> = "192.0.2.0/24".parse()?;
+        let t: PrefixSet<Ipv4> = "198.51.100.0/24".parse()?;
+        assert_eq!(s.len(), t.len());
+        assert_ne!(s, t);
+        let u: PrefixSet<Ipv4> = "192.0.2.0/25 192.0.2.128/25 192.0.2.0/24".parse()?;
+        assert_eq!(s.len() + 2, u.len());
+        assert_ne!(s, u);
+        Ok(())
+    }
+
+    #[test]
+    fn sets_built_differently_compare_equal() -> TestResult {
+        let (s, t) = setup()?;
+        let mut u = PrefixSet::new();
+        u.insert_from(["198.51.100.0/24".parse::<ip::Prefix<Ipv4>>()?]);
+        u.append(&mut t.clone());
+        assert_eq!(s, u);
+        assert_eq!(&(&s | &t) - &PrefixSet::new(), u);
+        let mut v = s.clone();
+        v.remove("198.51.100.0/24".parse::<ip::Prefix<Ipv4>>()?);
+        assert_eq!(v, t);
+        Ok(())
+    }
+
+    #[test]
+    fn counts_stay_consistent_across_mutations() -> TestResult {
+        let (mut s, mut t) = setup()?;
+        s.append(&mut t);
+        assert!(t.is_empty());
+        assert_eq!(t, PrefixSet::new());
+        assert_eq!(s.len(), 2);
+        let drained: Vec<_> = s.drain().collect();
+        assert_eq!(drained.len(), 2);
+        assert_eq!(s.len(), 0);
+        assert_eq!(s, PrefixSet::new());
+        let (mut s, t) = setup()?;
+        s ^= t.clone();
+        assert_eq!(s.len(), 1);
+        s.clear();
+        assert_eq!(s.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn cached_and_uncached_sets_compare_equal() -> TestResult {
+        let (mut s, _) = setup()?;
+        let t = s.clone();
+        s.materialize_ranges();
+        assert_eq!(s, t);
+        assert_eq!(t, s);
+        let mut u = t.clone();
+        u.materialize_ranges();
+        assert_eq!(s, u);
+        Ok(())
+    }
+
+    #[test]
+    fn differing_sets_compare_unequal() -> TestResult {
+        let (mut s, mut t) = setup()?;
+        assert_ne!(s, t);
+        s.materialize_ranges();
+        assert_ne!(s, t);
+        t.materialize_ranges();
+        assert_ne!(s, t);
+        assert_ne!(s, PrefixSet::new());
+        assert_ne!(PrefixSet::new(), t);
+        Ok(())
+    }
+}
+
+mod nodes {
+    use alloc::collections::BTreeSet;
+
+    use ip::{traits::Prefix as _, Ipv6};
+
+    use super::*;
+
+    fn assert_matches_ranges<A: ip::Afi>(s: &PrefixSet<A>) {
+        let bases: BTreeSet<_> = s
+            .ranges()
+            .map(|range| (range.prefix().network(), range.prefix().length()))
+            .collect();
+        assert_eq!(s.nodes().count(), bases.len());
+        s.nodes().for_each(|(prefix, lengths)| {
+            let expected: Vec<_> = s
+                .ranges()
+                .filter(|range| range.prefix() == prefix)
+                .flat_map(|range| {
+                    PrefixSet::singleton(range)
+                        .length_histogram()
+                        .into_keys()
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            assert_eq!(lengths.collect::<Vec<_>>(), expected);
+        });
+    }
+
+    #[test]
+    fn ipv4_matches_ranges() -> TestResult {
+        let s: PrefixSet<Ipv4> =
+            "10.0.0.0/8,8,10 10.0.0.0/8,12,13 192.0.2.0/24 192.0.2.128/25,27,28 198.51.100.7/32"
+                .parse()?;
+        assert_matches_ranges(&s);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_matches_ranges() -> TestResult {
+        let s: PrefixSet<Ipv6> =
+            "2001:db8::/32,32,48 2001:db8:f00::/40,56,64 2001:db9::/32".parse()?;
+        assert_matches_ranges(&s);
+        Ok(())
+    }
+
+    #[test]
+    fn skips_glue_nodes() -> TestResult {
+        // the two /24s are joined by a glue node at 192.0.0.0/21.
+        let s: PrefixSet<Ipv4> = "192.0.2.0/24 192.0.4.0/24".parse()?;
+        let prefixes: Vec<_> = s.nodes().map(|(prefix, _)| prefix).collect();
+        assert_eq!(prefixes.len(), 2);
+        assert!(prefixes.contains(&"192.0.2.0/24".parse()?));
+        assert!(prefixes.contains(&"192.0.4.0/24".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_set_has_no_nodes() {
+        assert_eq!(PrefixSet::<Ipv4>::new().nodes().count(), 0);
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod arbitrary {
+    use ip::Ipv6;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    macro_rules! arbitrary_tests {
+        ( $( $mod:ident => $afi:ty ),* $(,)? ) => {
+            $(
+                mod $mod {
+                    use super::*;
+
+                    proptest! {
+                        #[test]
+                        fn generates_aggregated_sets(s in any::<PrefixSet<$afi>>()) {
+                            prop_assert!(s.is_aggregated());
+                        }
+
+                        #[test]
+                        fn generates_valid_sets(s in any::<PrefixSet<$afi>>()) {
+                            prop_assert_eq!(s.prefixes().count(), s.len());
+                            prop_assert_eq!(s.prefixes().collect::<PrefixSet<$afi>>(), s.clone());
+                            prop_assert!(s.prefixes().all(|p| s.contains(p)));
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    arbitrary_tests! {
+        ipv4 => Ipv4,
+        ipv6 => Ipv6,
+    }
+}
+
+mod v4_mapped {
+    use ip::Ipv6;
+
+    use super::*;
+
+    #[test]
+    fn offsets_lengths_by_96() -> TestResult {
+        let s: PrefixSet<Ipv4> = "0.0.0.0/0 10.0.0.0/8,16,24 192.0.2.1/32".parse()?;
+        let mapped = s.to_v4_mapped();
+        assert!(mapped.contains("::ffff:0.0.0.0/96".parse()?));
+        assert!(mapped.contains("::ffff:10.1.0.0/112".parse()?));
+        assert!(mapped.contains("::ffff:10.1.2.0/120".parse()?));
+        assert!(!mapped.contains("::ffff:10.0.0.0/104".parse()?));
+        assert!(mapped.contains("::ffff:192.0.2.1/128".parse()?));
+        assert_eq!(mapped.len(), s.len());
+        Ok(())
+    }
+
+    #[test]
+    fn round_trips() -> TestResult {
+        let s: PrefixSet<Ipv4> =
+            "10.0.0.0/8,8,10 172.16.0.0/12,16,24 192.0.2.0/24 198.51.100.128/25,28,32".parse()?;
+        assert_eq!(s.to_v4_mapped().extract_v4_mapped(), s);
+        Ok(())
+    }
+
+    #[test]
+    fn round_trips_empty_set() {
+        let s = PrefixSet::<Ipv4>::new();
+        assert!(s.to_v4_mapped().is_empty());
+        assert!(PrefixSet::<Ipv6>::new().extract_v4_mapped().is_empty());
+    }
+
+    #[test]
+    fn ignores_unmapped_prefixes() -> TestResult {
+        let s: PrefixSet<Ipv6> =
+            "2001:db8::/32,32,48 ::/0,0,95 ::192.0.2.0/120 ::ffff:198.51.100.0/120".parse()?;
+        assert_eq!(
+            s.extract_v4_mapped(),
+            "198.51.100.0/24".parse::<PrefixSet<Ipv4>>()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn extracts_from_covering_range() -> TestResult {
+        let s: PrefixSet<Ipv6> = "::/0,96,104".parse()?;
+        assert_eq!(
+            s.extract_v4_mapped(),
+            "0.0.0.0/0,0,8".parse::<PrefixSet<Ipv4>>()?
+        );
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt, num::ParseIntError};
+use std::{fmt, io, num::ParseIntError};
 
 use crate::error::Error;
 
@@ -7,6 +7,9 @@ pub enum TestError {
     Wrapped(Error),
     Ip(ip::Error),
     Int(ParseIntError),
+    Io(io::Error),
+    Json(serde_json::Error),
+    Cbor(serde_cbor::Error),
     Str(&'static str),
 }
 
@@ -18,6 +21,9 @@ impl fmt::Display for TestError {
             Self::Wrapped(err) => err.fmt(f),
             Self::Ip(err) => err.fmt(f),
             Self::Int(err) => err.fmt(f),
+            Self::Io(err) => err.fmt(f),
+            Self::Json(err) => err.fmt(f),
+            Self::Cbor(err) => err.fmt(f),
             Self::Str(msg) => f.write_str(msg),
         }
     }
@@ -41,10 +47,28 @@ impl From<ParseIntError> for TestError {
     }
 }
 
+impl From<io::Error> for TestError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for TestError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl From<serde_cbor::Error> for TestError {
+    fn from(err: serde_cbor::Error) -> Self {
+        Self::Cbor(err)
+    }
+}
+
 impl From<&'static str> for TestError {
     fn from(msg: &'static str) -> Self {
         Self::Str(msg)
     }
 }
 
-pub type TestResult = Result<(), TestError>;
+pub type TestResult<T = ()> = Result<T, TestError>;
@@ -187,6 +187,113 @@ mod ipv4 {
     //     let not = !s;
     //     assert_eq!(not.iter_prefixes().count(), 8_589_176_728)
     // }
+
+    #[test]
+    fn borrowing_set_operations_match_operators() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-WOLCOMM-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let t: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-HURRICANE-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        assert_eq!(s.union(&t), s.clone() | t.clone());
+        assert_eq!(s.intersection(&t), s.clone() & t.clone());
+        assert_eq!(s.difference(&t), s.clone() - t.clone());
+        assert_eq!(s.symmetric_difference(&t), s ^ t);
+    }
+
+    #[test]
+    fn in_place_set_operations_match_operators() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-WOLCOMM-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let t: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-HURRICANE-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            s.clone().difference_update(&t).to_owned(),
+            s.clone() - t.clone()
+        );
+        assert_eq!(
+            t.clone().difference_update(&s).to_owned(),
+            t.clone() - s.clone()
+        );
+        assert_eq!(
+            s.clone().intersect_update(&t).to_owned(),
+            s.clone() & t.clone()
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_set_operations_match_serial() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-WOLCOMM-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let t: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-HURRICANE-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        assert_eq!(s.par_union(&t), s.union(&t));
+        assert_eq!(s.par_intersection(&t), s.intersection(&t));
+        assert_eq!(s.par_difference(&t), s.difference(&t));
+        assert_eq!(t.par_difference(&s), t.difference(&s));
+    }
+
+    #[test]
+    fn intersect_range_matches_intersection_with_singleton() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-WOLCOMM-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let ranges: Vec<PrefixRange<Ipv4>> = data_set("AS-HURRICANE-ipv4-ranges", 0, 0).read();
+        ranges.into_iter().step_by(500).for_each(|range| {
+            assert_eq!(
+                s.intersect_range(range.clone()),
+                &s & &PrefixSet::singleton(range.clone()),
+                "mismatch for range {}",
+                range
+            );
+        });
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn binary_format_round_trips_and_is_smaller_than_text() {
+        let prefixes: PrefixSet<_> = data_set::<Prefix<Ipv4>>("AS-WOLCOMM-ipv4-prefixes", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let ranges: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-WOLCOMM-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        for s in [prefixes, ranges] {
+            let mut buf = Vec::new();
+            s.write_binary(&mut buf).unwrap();
+            assert_eq!(PrefixSet::read_binary(buf.as_slice()).unwrap(), s);
+            let text_len: usize = s.ranges().map(|range| range.to_string().len() + 1).sum();
+            assert!(buf.len() < text_len);
+        }
+    }
+
+    #[test]
+    fn delta_encoding_round_trips_and_is_smaller_than_plain_encoding() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv4>>("AS-HURRICANE-ipv4-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let plain = s.to_bytes();
+        let delta = s.to_bytes_delta();
+        assert_eq!(PrefixSet::from_bytes(&plain).unwrap(), s);
+        assert_eq!(PrefixSet::from_bytes_delta(&delta).unwrap(), s);
+        assert!(delta.len() < plain.len());
+    }
 }
 
 mod ipv6 {
@@ -367,4 +474,111 @@ mod ipv6 {
     //     let not = !s;
     //     assert_eq!(not.iter_prefixes().count(), 8_589_176_728)
     // }
+
+    #[test]
+    fn borrowing_set_operations_match_operators() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-WOLCOMM-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let t: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-HURRICANE-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        assert_eq!(s.union(&t), s.clone() | t.clone());
+        assert_eq!(s.intersection(&t), s.clone() & t.clone());
+        assert_eq!(s.difference(&t), s.clone() - t.clone());
+        assert_eq!(s.symmetric_difference(&t), s ^ t);
+    }
+
+    #[test]
+    fn in_place_set_operations_match_operators() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-WOLCOMM-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let t: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-HURRICANE-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            s.clone().difference_update(&t).to_owned(),
+            s.clone() - t.clone()
+        );
+        assert_eq!(
+            t.clone().difference_update(&s).to_owned(),
+            t.clone() - s.clone()
+        );
+        assert_eq!(
+            s.clone().intersect_update(&t).to_owned(),
+            s.clone() & t.clone()
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_set_operations_match_serial() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-WOLCOMM-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let t: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-HURRICANE-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        assert_eq!(s.par_union(&t), s.union(&t));
+        assert_eq!(s.par_intersection(&t), s.intersection(&t));
+        assert_eq!(s.par_difference(&t), s.difference(&t));
+        assert_eq!(t.par_difference(&s), t.difference(&s));
+    }
+
+    #[test]
+    fn intersect_range_matches_intersection_with_singleton() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-WOLCOMM-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let ranges: Vec<PrefixRange<Ipv6>> = data_set("AS-HURRICANE-ipv6-ranges", 0, 0).read();
+        ranges.into_iter().step_by(500).for_each(|range| {
+            assert_eq!(
+                s.intersect_range(range.clone()),
+                &s & &PrefixSet::singleton(range.clone()),
+                "mismatch for range {}",
+                range
+            );
+        });
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn binary_format_round_trips_and_is_smaller_than_text() {
+        let prefixes: PrefixSet<_> = data_set::<Prefix<Ipv6>>("AS-WOLCOMM-ipv6-prefixes", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let ranges: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-WOLCOMM-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        for s in [prefixes, ranges] {
+            let mut buf = Vec::new();
+            s.write_binary(&mut buf).unwrap();
+            assert_eq!(PrefixSet::read_binary(buf.as_slice()).unwrap(), s);
+            let text_len: usize = s.ranges().map(|range| range.to_string().len() + 1).sum();
+            assert!(buf.len() < text_len);
+        }
+    }
+
+    #[test]
+    fn delta_encoding_round_trips_and_is_smaller_than_plain_encoding() {
+        let s: PrefixSet<_> = data_set::<PrefixRange<Ipv6>>("AS-HURRICANE-ipv6-ranges", 0, 0)
+            .read()
+            .into_iter()
+            .collect();
+        let plain = s.to_bytes();
+        let delta = s.to_bytes_delta();
+        assert_eq!(PrefixSet::from_bytes(&plain).unwrap(), s);
+        assert_eq!(PrefixSet::from_bytes_delta(&delta).unwrap(), s);
+        assert!(delta.len() < plain.len());
+    }
 }